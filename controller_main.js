@@ -70,7 +70,7 @@ let touchState = {
   tapMaxTime: 300, // max ms for tap detection
 };
 let sharedMem = null;
-let pointers = { cmd: 0, gameStructure: 0 };
+let pointers = { cmd: 0, gameStructure: 0, gameStructureControl: 0 };
 let memory = null;
 
 // Trials System
@@ -154,6 +154,7 @@ async function start() {
   // Use helpers from shared/src/web.rs - updated for new structure
   pointers.cmd = sharedMem.get_commands_ptr();
   pointers.gameStructure = sharedMem.get_game_structure_ptr();
+  pointers.gameStructureControl = sharedMem.get_game_structure_control_ptr();
 
   // Start the Bevy game (after shared memory is ready)
   wasm_main();
@@ -796,7 +797,7 @@ function writeGameStructure(config) {
   // Use offsets for robust writing
   if (!offsets.decoration_seeds) return; // Not ready?
 
-  const view = new DataView(memory.buffer, pointers.gameStructure);
+  const view = new DataView(memory.buffer, pointers.gameStructureControl);
 
   // decoration_seeds (3 x u64)
   for (let i = 0; i < 3; i++) {
@@ -1384,7 +1385,7 @@ function writeGameStructure(config) {
   // target_door: u32 (4 bytes) - offset 36
   // colors: [u32; 12] (48 bytes) - offset 40 (f32 bits)
 
-  const view = new DataView(memory.buffer, pointers.gameStructure);
+  const view = new DataView(memory.buffer, pointers.gameStructureControl);
   let offset = 0;
 
   // decoration_seeds ([u64; 3] - 3 x two u32 writes, little-endian)
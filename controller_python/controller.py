@@ -180,21 +180,21 @@ class SharedMemory:
                 return False
         try:
             self.inner.write_game_structure(
-                [int(x) for x in decoration_seeds],
-                float(base_radius),
-                float(height),
-                float(start_orient),
-                int(target_door),
-                colors,
-                [int(x) for x in decorations_count],
-                [float(x) for x in decorations_size],
-                float(cosine_alignment_threshold),
-                float(door_anim_fade_out),
-                float(door_anim_stay_open),
-                float(door_anim_fade_in),
-                float(main_spotlight_intensity),
-                float(ambient_brightness),
-                float(max_spotlight_intensity),
+                decoration_seeds=[int(x) for x in decoration_seeds],
+                base_radius=float(base_radius),
+                height=float(height),
+                start_orient=float(start_orient),
+                target_door=int(target_door),
+                colors=colors,
+                decorations_count=[int(x) for x in decorations_count],
+                decorations_size=[float(x) for x in decorations_size],
+                cosine_alignment_threshold=float(cosine_alignment_threshold),
+                door_anim_fade_out=float(door_anim_fade_out),
+                door_anim_stay_open=float(door_anim_stay_open),
+                door_anim_fade_in=float(door_anim_fade_in),
+                main_spotlight_intensity=float(main_spotlight_intensity),
+                ambient_brightness=float(ambient_brightness),
+                max_spotlight_intensity=float(max_spotlight_intensity),
             )
             return True
         except Exception as exc:
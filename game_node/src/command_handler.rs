@@ -2,11 +2,16 @@
 //! This module reads from Shared Memory and updates the game resources (`PendingRotation`, etc.).
 
 use bevy::prelude::*;
+use bevy::winit::{UpdateMode, WinitSettings};
 use core::sync::atomic::Ordering;
+use std::time::Duration;
 #[cfg(not(target_arch = "wasm32"))]
 use shared::create_shared_memory;
-use shared::constants::camera_3d_constants::{CAMERA_3D_SPEED_ROTATE, CAMERA_3D_SPEED_ZOOM};
-use shared::SharedMemoryHandle;
+use shared::constants::game_constants::{IDLE_UPDATE_HZ, RESET_INPUT_LOCKOUT_FRAMES};
+use shared::{SharedCommands, SharedMemoryHandle};
+use bevy::ecs::system::SystemParam;
+use serde::{Deserialize, Serialize};
+use crate::replay::CommandRecorder;
 
 #[derive(Resource)]
 pub struct SharedMemResource(pub SharedMemoryHandle);
@@ -14,9 +19,11 @@ pub struct SharedMemResource(pub SharedMemoryHandle);
 #[derive(Resource, Default)]
 pub struct PendingReset(pub bool);
 
+/// Rotate direction held this frame: -1 left, 1 right, scaled by the rotation speed and the frame time when applied
 #[derive(Resource, Default)]
 pub struct PendingRotation(pub f32);
 
+/// Zoom direction held this frame: -1 in, 1 out, scaled by the zoom speed and the frame time when applied
 #[derive(Resource, Default)]
 pub struct PendingZoom(pub f32);
 
@@ -26,12 +33,62 @@ pub struct PendingCheckAlignment(pub bool);
 #[derive(Resource, Default)]
 pub struct PendingBlankScreen(pub bool);
 
+#[derive(Resource, Default)]
+pub struct PendingReroll(pub bool);
+
+#[derive(Resource, Default)]
+pub struct PendingReleaseCursor(pub bool);
+
+#[derive(Resource, Default)]
+pub struct PendingRebuildBase(pub bool);
+
+/// Debug force win, only acted upon together with the `PendingCheckAlignment` it raises
+#[derive(Resource, Default)]
+pub struct PendingForceWin(pub bool);
+
+#[derive(Resource, Default)]
+pub struct PendingRecenter(pub bool);
+
+/// Requested orbit pose `(azimuth, radius)` (`None` when not commanded this frame)
+#[derive(Resource, Default)]
+pub struct PendingViewpoint(pub Option<(f32, f32)>);
+
+/// Frames left during which check commands are dropped, started by every reset.
+#[derive(Resource, Default)]
+pub struct InputLockout {
+    pub frames_left: u32,
+}
+
+/// Requested absolute blank screen state (`None` when not commanded this frame)
+#[derive(Resource, Default)]
+pub struct PendingBlankScreenState(pub Option<bool>);
+
 #[derive(Resource, Default)]
 pub struct RenderingPaused(pub bool);
 
 #[derive(Resource, Default)]
 pub struct PendingAnimation(pub bool);
 
+/// Low-power mode: frames without activity, and the window settings to restore while the update rate is reduced.
+#[derive(Resource, Default)]
+pub struct IdleThrottle {
+    pub idle_frames: u32,
+    saved_settings: Option<WinitSettings>,
+    // The current frame is the first at full rate, its delta still spans a slow update
+    woke_up: bool,
+}
+
+impl IdleThrottle {
+    pub fn is_throttled(&self) -> bool {
+        self.saved_settings.is_some()
+    }
+
+    /// Whether the delta of the current frame was lengthened by the low-power mode.
+    pub fn slowed_down(&self) -> bool {
+        self.is_throttled() || self.woke_up
+    }
+}
+
 pub struct CommandHandlerPlugin;
 
 impl Plugin for CommandHandlerPlugin {
@@ -41,13 +98,27 @@ impl Plugin for CommandHandlerPlugin {
             .init_resource::<PendingZoom>()
             .init_resource::<PendingCheckAlignment>()
             .init_resource::<PendingBlankScreen>()
-            .init_resource::<PendingBlankScreen>()
+            .init_resource::<PendingBlankScreenState>()
             .init_resource::<RenderingPaused>()
             .init_resource::<PendingAnimation>()
+            .init_resource::<PendingReroll>()
+            .init_resource::<PendingReleaseCursor>()
+            .init_resource::<PendingViewpoint>()
+            .init_resource::<PendingRebuildBase>()
+            .init_resource::<PendingForceWin>()
+            .init_resource::<PendingRecenter>()
+            .init_resource::<IdleThrottle>()
+            .init_resource::<InputLockout>()
             .add_systems(Startup, init_shared_memory_system)
             .add_systems(
                 PreUpdate,
-                (clear_pending_actions, read_shared_memory).chain(),
+                (
+                    clear_pending_actions,
+                    read_shared_memory,
+                    apply_input_lockout,
+                    update_idle_throttle,
+                )
+                    .chain(),
             );
     }
 }
@@ -60,7 +131,8 @@ fn init_shared_memory_system(mut commands: Commands) {
     {
         match create_shared_memory(name) {
             Ok(handle) => {
-                info!("Shared Memory initialized successfully.");
+                // The Controller must use the same directory (MONKEY_SHM_DIR)
+                info!("Shared Memory initialized successfully in {}.", shared::shm_dir().display());
                 commands.insert_resource(SharedMemResource(handle));
             }
             Err(e) => {
@@ -70,74 +142,318 @@ fn init_shared_memory_system(mut commands: Commands) {
     }
 }
 
-fn clear_pending_actions(
-    mut pending_reset: ResMut<PendingReset>,
-    mut pending_rotation: ResMut<PendingRotation>,
-    mut pending_zoom: ResMut<PendingZoom>,
-    mut pending_check: ResMut<PendingCheckAlignment>,
-    mut pending_blank: ResMut<PendingBlankScreen>,
-    mut rendering_paused: ResMut<RenderingPaused>,
-    mut pending_anim: ResMut<PendingAnimation>,
-    
-) {
-    pending_rotation.0 = 0.0;
-    pending_zoom.0 = 0.0;
-    pending_check.0 = false;
-    pending_blank.0 = false;
-    pending_anim.0 = false;
-    pending_reset.0 = false;
-    rendering_paused.0 = false;
+/// Command flags read from shared memory in one frame.
+/// Also the unit of the recorded command stream (see `replay`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct CommandFrame {
+    pub rotate_left: bool,
+    pub rotate_right: bool,
+    pub zoom_in: bool,
+    pub zoom_out: bool,
+    pub check_alignment: bool,
+    pub reset: bool,
+    pub blank_screen: bool,
+    pub stop_rendering: bool,
+    pub resume_rendering: bool,
+    pub animation_door: bool,
+    // Default keeps recordings made before the absolute blank commands readable
+    #[serde(default)]
+    pub blank_on: bool,
+    #[serde(default)]
+    pub blank_off: bool,
+    #[serde(default)]
+    pub reroll: bool,
+    #[serde(default)]
+    pub release_cursor: bool,
+    /// `(azimuth, radius)` targets when `set_viewpoint` is raised
+    #[serde(default)]
+    pub set_viewpoint: Option<(f32, f32)>,
+    #[serde(default)]
+    pub rebuild_base: bool,
+    #[serde(default)]
+    pub force_win: bool,
+    #[serde(default)]
+    pub recenter: bool,
 }
 
-fn read_shared_memory(
-    shm_res: Option<Res<SharedMemResource>>,
-    mut pending_reset: ResMut<PendingReset>,
-    mut pending_rotation: ResMut<PendingRotation>,
-    mut pending_zoom: ResMut<PendingZoom>,
-    mut pending_check: ResMut<PendingCheckAlignment>,
-    mut pending_blank: ResMut<PendingBlankScreen>,
-    mut rendering_paused: ResMut<RenderingPaused>,
-    mut pending_anim: ResMut<PendingAnimation>,
-) {
-    let Some(shm_res) = shm_res else { return };
-    let shm = shm_res.0.get();
-
-    // Read commands from shared memory and apply pending
-    if shm.commands.rotate_left.load(Ordering::Relaxed) {
-        pending_rotation.0 -= CAMERA_3D_SPEED_ROTATE;
+impl CommandFrame {
+    /// Loads all command flags from shared memory.
+    pub fn load(commands: &SharedCommands) -> Self {
+        Self {
+            rotate_left: commands.rotate_left.load(Ordering::Relaxed),
+            rotate_right: commands.rotate_right.load(Ordering::Relaxed),
+            zoom_in: commands.zoom_in.load(Ordering::Relaxed),
+            zoom_out: commands.zoom_out.load(Ordering::Relaxed),
+            check_alignment: commands.check_alignment.load(Ordering::Relaxed),
+            reset: commands.reset.load(Ordering::Relaxed),
+            blank_screen: commands.blank_screen.load(Ordering::Relaxed),
+            stop_rendering: commands.stop_rendering.load(Ordering::Relaxed),
+            resume_rendering: commands.resume_rendering.load(Ordering::Relaxed),
+            animation_door: commands.animation_door.load(Ordering::Relaxed),
+            blank_on: commands.blank_on.load(Ordering::Relaxed),
+            blank_off: commands.blank_off.load(Ordering::Relaxed),
+            reroll: commands.reroll.load(Ordering::Relaxed),
+            release_cursor: commands.release_cursor.load(Ordering::Relaxed),
+            // Acquire pairs with the Controller's Release, the targets are written before the flag
+            set_viewpoint: commands.set_viewpoint.load(Ordering::Acquire).then(|| {
+                (
+                    f32::from_bits(commands.set_azimuth.load(Ordering::Relaxed)),
+                    f32::from_bits(commands.set_radius.load(Ordering::Relaxed)),
+                )
+            }),
+            rebuild_base: commands.rebuild_base.load(Ordering::Relaxed),
+            force_win: commands.force_win.load(Ordering::Relaxed),
+            recenter: commands.recenter.load(Ordering::Relaxed),
+        }
     }
-    if shm.commands.rotate_right.load(Ordering::Relaxed) {
-        pending_rotation.0 += CAMERA_3D_SPEED_ROTATE;
+
+    /// True when no command is set.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
     }
-    if shm.commands.zoom_in.load(Ordering::Relaxed) {
-        pending_zoom.0 -= CAMERA_3D_SPEED_ZOOM;
+}
+
+/// All the pending command resources, written by `read_shared_memory` or by the replay.
+#[derive(SystemParam)]
+pub struct PendingCommands<'w> {
+    pub reset: ResMut<'w, PendingReset>,
+    pub rotation: ResMut<'w, PendingRotation>,
+    pub zoom: ResMut<'w, PendingZoom>,
+    pub check: ResMut<'w, PendingCheckAlignment>,
+    pub blank: ResMut<'w, PendingBlankScreen>,
+    pub blank_state: ResMut<'w, PendingBlankScreenState>,
+    pub rendering_paused: ResMut<'w, RenderingPaused>,
+    pub anim: ResMut<'w, PendingAnimation>,
+    pub reroll: ResMut<'w, PendingReroll>,
+    pub release_cursor: ResMut<'w, PendingReleaseCursor>,
+    pub viewpoint: ResMut<'w, PendingViewpoint>,
+    pub rebuild_base: ResMut<'w, PendingRebuildBase>,
+    pub force_win: ResMut<'w, PendingForceWin>,
+    pub recenter: ResMut<'w, PendingRecenter>,
+}
+
+impl PendingCommands<'_> {
+    /// Resets every pending action for the new frame.
+    pub fn clear(&mut self) {
+        self.rotation.0 = 0.0;
+        self.zoom.0 = 0.0;
+        self.check.0 = false;
+        self.blank.0 = false;
+        self.blank_state.0 = None;
+        self.anim.0 = false;
+        self.reroll.0 = false;
+        self.release_cursor.0 = false;
+        self.viewpoint.0 = None;
+        self.rebuild_base.0 = false;
+        self.force_win.0 = false;
+        self.recenter.0 = false;
+        self.reset.0 = false;
+        self.rendering_paused.0 = false;
     }
-    if shm.commands.zoom_out.load(Ordering::Relaxed) {
-        pending_zoom.0 += CAMERA_3D_SPEED_ZOOM;
+
+    /// Whether any action is pending this frame (the absolute blank state is re-sent every frame, so it is ignored).
+    pub fn any_active(&self) -> bool {
+        self.rotation.0 != 0.0
+            || self.zoom.0 != 0.0
+            || self.check.0
+            || self.blank.0
+            || self.anim.0
+            || self.reroll.0
+            || self.release_cursor.0
+            || self.viewpoint.0.is_some()
+            || self.rebuild_base.0
+            || self.force_win.0
+            || self.recenter.0
+            || self.reset.0
     }
 
-    // Read Trigger Inputs (swap to clear after reading)
-    if shm.commands.check_alignment.load( Ordering::Relaxed) {
-        pending_check.0 = true;
+    /// Turns the command flags of one frame into pending actions.
+    pub fn apply(&mut self, frame: &CommandFrame) {
+        if frame.rotate_left {
+            self.rotation.0 -= 1.0;
+        }
+        if frame.rotate_right {
+            self.rotation.0 += 1.0;
+        }
+        if frame.zoom_in {
+            self.zoom.0 -= 1.0;
+        }
+        if frame.zoom_out {
+            self.zoom.0 += 1.0;
+        }
+
+        // Trigger inputs
+        if frame.check_alignment {
+            self.check.0 = true;
+        }
+
+        // Rendering control commands
+        if frame.blank_screen {
+            self.blank.0 = true;
+        }
+        if frame.blank_on {
+            self.blank_state.0 = Some(true);
+        }
+        if frame.blank_off {
+            self.blank_state.0 = Some(false);
+        }
+        if frame.stop_rendering {
+            self.rendering_paused.0 = true;
+        }
+        if frame.resume_rendering {
+            self.rendering_paused.0 = false;
+        }
+
+        if frame.animation_door {
+            self.anim.0 = true;
+        }
+
+        if frame.reset {
+            self.reset.0 = true;
+        }
+
+        if frame.reroll {
+            self.reroll.0 = true;
+        }
+
+        if frame.release_cursor {
+            self.release_cursor.0 = true;
+        }
+
+        if frame.set_viewpoint.is_some() {
+            self.viewpoint.0 = frame.set_viewpoint;
+        }
+
+        if frame.rebuild_base {
+            self.rebuild_base.0 = true;
+        }
+
+        if frame.recenter {
+            self.recenter.0 = true;
+        }
+
+        // A forced win goes through the check, so the reset lockout drops it too
+        if frame.force_win {
+            self.check.0 = true;
+            self.force_win.0 = true;
+        }
     }
+}
 
-    // New rendering control commands
-    if shm.commands.blank_screen.load( Ordering::Relaxed) {
-        pending_blank.0 = true;
+/// Run condition for systems that need shared memory: they idle until the Controller's
+/// shared memory is attached instead of logging an error every frame.
+pub fn shm_available(shm_res: Option<Res<SharedMemResource>>) -> bool {
+    shm_res.is_some()
+}
+
+pub(crate) fn clear_pending_actions(mut pending: PendingCommands) {
+    pending.clear();
+}
+
+/// Drops the check commands of the frame of a reset and of the RESET_INPUT_LOCKOUT_FRAMES - 1 frames after it,
+/// so a check spanning the round transition is not counted as the first attempt of the new round.
+pub(crate) fn apply_input_lockout(
+    reset: Res<PendingReset>,
+    mut check: ResMut<PendingCheckAlignment>,
+    mut lockout: ResMut<InputLockout>,
+) {
+    if reset.0 {
+        lockout.frames_left = RESET_INPUT_LOCKOUT_FRAMES;
     }
-    if shm.commands.stop_rendering.load( Ordering::Relaxed) {
-        rendering_paused.0 = true;
+    if lockout.frames_left == 0 {
+        return;
     }
-    if shm.commands.resume_rendering.load(Ordering::Relaxed) {
-        rendering_paused.0 = false;
+    lockout.frames_left -= 1;
+
+    if check.0 {
+        check.0 = false;
+        debug!("Check ignored right after a reset");
     }
+}
+
+/// Drops the update rate to IDLE_UPDATE_HZ after `idle_timeout_frames` frames without commands, keyboard
+/// input or door animation, and restores the full rate on the first frame with activity.
+/// A command sent while idle is picked up on the next (slow) update.
+fn update_idle_throttle(
+    shm_res: Option<Res<SharedMemResource>>,
+    pending: PendingCommands,
+    keys: Option<Res<ButtonInput<KeyCode>>>,
+    mut idle: ResMut<IdleThrottle>,
+    winit_settings: Option<ResMut<WinitSettings>>,
+) {
+    let Some(shm_res) = shm_res else { return };
+    let Some(mut winit_settings) = winit_settings else { return };
+    let shm = shm_res.0.get();
+    idle.woke_up = false;
+
+    // Live config, mirrored for the readback
+    let timeout = shm.game_structure_control.idle_timeout_frames.load(Ordering::Relaxed);
+    shm.game_structure_game.idle_timeout_frames.store(timeout, Ordering::Relaxed);
 
-    if shm.commands.animation_door.load(Ordering::Relaxed) {
-        pending_anim.0 = true;
+    let active = pending.any_active()
+        || keys.is_some_and(|keys| keys.get_pressed().next().is_some())
+        || shm.game_structure_game.is_animating.load(Ordering::Relaxed);
+    idle.idle_frames = if active { 0 } else { idle.idle_frames.saturating_add(1) };
+
+    let should_throttle = timeout > 0 && idle.idle_frames >= timeout;
+    if should_throttle && !idle.is_throttled() {
+        let wait = Duration::from_secs_f64(1.0 / IDLE_UPDATE_HZ);
+        idle.saved_settings = Some(winit_settings.clone());
+        winit_settings.focused_mode = UpdateMode::reactive_low_power(wait);
+        winit_settings.unfocused_mode = UpdateMode::reactive_low_power(wait);
+        debug!("Idle for {} frames, updating at {} Hz", idle.idle_frames, IDLE_UPDATE_HZ);
+    } else if !should_throttle {
+        if let Some(saved) = idle.saved_settings.take() {
+            *winit_settings = saved;
+            idle.woke_up = true;
+            debug!("Activity detected, back to full update rate");
+        }
     }
+}
 
-    if shm.commands.reset.load(Ordering::Relaxed) {
-        pending_reset.0 = true;
+pub(crate) fn read_shared_memory(
+    shm_res: Option<Res<SharedMemResource>>,
+    mut pending: PendingCommands,
+    recorder: Option<ResMut<CommandRecorder>>,
+    time: Res<Time>,
+) {
+    let Some(shm_res) = shm_res else { return };
+    let shm = shm_res.0.get();
+
+    // Read commands from shared memory and apply pending
+    let frame = CommandFrame::load(&shm.commands);
+    pending.apply(&frame);
+
+    if let Some(mut recorder) = recorder {
+        recorder.record(&frame, time.elapsed_secs());
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
+    #[test]
+    fn check_spanning_a_reset_is_ignored() {
+        let mut app = App::new();
+        app.init_resource::<PendingReset>()
+            .init_resource::<PendingCheckAlignment>()
+            .init_resource::<InputLockout>()
+            .add_systems(PreUpdate, apply_input_lockout);
+
+        // Controller sends a reset and keeps its check flag up across the transition
+        let mut run_frame = |reset: bool, check: bool| {
+            app.insert_resource(PendingReset(reset));
+            app.insert_resource(PendingCheckAlignment(check));
+            app.update();
+            app.world().resource::<PendingCheckAlignment>().0
+        };
+
+        assert!(run_frame(false, true), "no lockout before any reset");
+        assert!(!run_frame(true, true), "check in the reset frame");
+        for _ in 1..RESET_INPUT_LOCKOUT_FRAMES {
+            assert!(!run_frame(false, true), "check right after the reset");
+        }
+        assert!(run_frame(false, true), "check once the lockout is over");
+    }
 }
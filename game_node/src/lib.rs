@@ -19,14 +19,24 @@ pub mod state_emitter;
 /// Web adapter for WASM integration
 pub mod web_adapter;
 
+/// Recording and replay of the command stream
+pub mod replay;
+
 /// Various utility functions, constants, and objects
 pub mod utils {
+    pub mod asset_cache;
     pub mod camera;
     pub mod debug_functions;
     pub mod game_functions;
     pub mod macros;
+    pub mod mesh_validation;
     pub mod objects;
+    pub mod preload;
     pub mod pyramid;
+    pub mod render_scale;
+    #[cfg(not(target_arch = "wasm32"))]
+    pub mod round_log;
     pub mod setup;
     pub mod systems_logic;
+    pub mod window_config;
 }
\ No newline at end of file
@@ -4,7 +4,7 @@
 use bevy::{
     diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
     prelude::*,
-    window::*,
+    window::WindowPlugin,
 };
 
 // Re-export shared memory functions for WASM
@@ -16,42 +16,36 @@ use shared::constants::game_constants::REFRESH_RATE_HZ;
 
 use game_node::{
     command_handler::CommandHandlerPlugin,
+    replay::ReplayPlugin,
     state_emitter::StateEmitterPlugin,
     web_adapter::WebAdapterPlugin,
     utils::{
         debug_functions::DebugFunctionsPlugin,
         objects::{DoorWinEntities, RoundStartTimestamp},
+        preload::PreloadPlugin,
+        render_scale::RenderScalePlugin,
         systems_logic::SystemsLogicPlugin,
+        window_config,
     },
 };
 
 /// Entry point for the application
 fn main() {
-    let window = Some(Window {
-        title: "Monkey 3D Game".into(),
-        #[cfg(target_arch = "wasm32")]
-        canvas: Some("#game-canvas".into()),
-        fit_canvas_to_parent: true,
-        prevent_default_event_handling: true,
-        #[cfg(not(target_arch = "wasm32"))]
-        mode: WindowMode::BorderlessFullscreen(MonitorSelection::Primary),
-        present_mode: PresentMode::AutoVsync,
-        ..default()
-    });
+    let window = Some(window_config::primary_window());
 
-    let cursor = Some(CursorOptions {
-        grab_mode: CursorGrabMode::Locked,
-        visible: false,
-        ..default()
-    });
+    let cursor = Some(window_config::cursor_options());
+
+    let render_scale = window_config::render_scale();
 
     App::new()
         .add_plugins((
-            DefaultPlugins.set(WindowPlugin {
-                primary_window: window,
-                primary_cursor_options: cursor,
-                ..default()
-            }),
+            DefaultPlugins
+                .set(WindowPlugin {
+                    primary_window: window,
+                    primary_cursor_options: cursor,
+                    ..default()
+                })
+                .set(window_config::log_plugin()),
             LogDiagnosticsPlugin::default(),
             FrameTimeDiagnosticsPlugin::default(),
             CommandHandlerPlugin, // Read shared memory and init bevy resources, preupdate
@@ -59,7 +53,12 @@ fn main() {
             DebugFunctionsPlugin, // Debug functions, update
             StateEmitterPlugin,   // Write shared memory, update timing, init timing resource, postupdate
             WebAdapterPlugin, 
+            ReplayPlugin,         // Record/replay the command stream when enabled by env var
+            PreloadPlugin,        // Build the cached scene assets at startup
+            RenderScalePlugin { scale: render_scale }, // Downscaled 3D scene when MONKEY_RENDER_SCALE is set
         ))
+        // The window and log configuration was read before the logger existed
+        .add_systems(Startup, window_config::log_config_warnings)
         .insert_resource(Time::<Fixed>::from_hz(REFRESH_RATE_HZ)) 
         .insert_resource(DoorWinEntities::default())
         .insert_resource(RoundStartTimestamp::default())
@@ -0,0 +1,131 @@
+//! Recording and replay of the Controller command stream.
+//!
+//! With `MONKEY_RECORD_COMMANDS` set, every frame with at least one command read from shared memory
+//! is appended as a JSON line to that file. With `MONKEY_REPLAY_COMMANDS` set, the recorded frames are
+//! written back into the `Pending*` resources at the same frame index, overriding shared memory, so a
+//! past session (with the same seeds) can be re-rendered deterministically.
+
+use crate::command_handler::{apply_input_lockout, read_shared_memory, CommandFrame, PendingCommands};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use shared::constants::game_constants::{COMMAND_RECORD_ENV_VAR, COMMAND_REPLAY_ENV_VAR};
+use std::fs::File;
+use std::io::Write;
+
+/// One line of the recorded command stream.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RecordedCommands {
+    /// Frame index since startup
+    pub frame: u64,
+    pub elapsed_secs: f32,
+    pub commands: CommandFrame,
+}
+
+/// Appends the commands read from shared memory to the record file.
+#[derive(Resource)]
+pub struct CommandRecorder {
+    file: File,
+    frame: u64,
+}
+
+impl CommandRecorder {
+    /// Records the commands of the current frame (empty frames are skipped) and advances the frame index.
+    pub fn record(&mut self, commands: &CommandFrame, elapsed_secs: f32) {
+        let frame = self.frame;
+        self.frame += 1;
+        if commands.is_empty() {
+            return;
+        }
+
+        let entry = RecordedCommands {
+            frame,
+            elapsed_secs,
+            commands: *commands,
+        };
+        let result = serde_json::to_string(&entry)
+            .map_err(std::io::Error::other)
+            .and_then(|line| writeln!(self.file, "{}", line));
+        if let Err(e) = result {
+            warn!("Failed to record commands of frame {}: {}", frame, e);
+        }
+    }
+}
+
+/// Recorded command stream being played back.
+#[derive(Resource)]
+pub struct CommandReplay {
+    entries: Vec<RecordedCommands>,
+    next: usize,
+    frame: u64,
+}
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        if let Ok(path) = std::env::var(COMMAND_RECORD_ENV_VAR) {
+            match File::create(&path) {
+                Ok(file) => {
+                    info!("Recording commands to {}", path);
+                    app.insert_resource(CommandRecorder { file, frame: 0 });
+                }
+                Err(e) => error!("Failed to create command record file {}: {}", path, e),
+            }
+        }
+
+        if let Ok(path) = std::env::var(COMMAND_REPLAY_ENV_VAR) {
+            match load_replay(&path) {
+                Ok(entries) => {
+                    info!("Replaying {} recorded command frames from {}", entries.len(), path);
+                    app.insert_resource(CommandReplay {
+                        entries,
+                        next: 0,
+                        frame: 0,
+                    })
+                    // Runs after the shared memory read so the recording overrides it
+                    .add_systems(
+                        PreUpdate,
+                        replay_commands.after(read_shared_memory).before(apply_input_lockout),
+                    );
+                }
+                Err(e) => error!("Failed to load command replay {}: {}", path, e),
+            }
+        }
+    }
+}
+
+/// Parses a recorded command stream, one JSON object per line, sorted by frame.
+fn load_replay(path: &str) -> Result<Vec<RecordedCommands>, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let mut entries = content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str::<RecordedCommands>(line).map_err(|e| format!("line {}: {}", i + 1, e))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.sort_by_key(|entry| entry.frame);
+    Ok(entries)
+}
+
+/// Writes the recorded commands of the current frame into the pending resources, bypassing shared memory.
+fn replay_commands(mut replay: ResMut<CommandReplay>, mut pending: PendingCommands) {
+    let frame = replay.frame;
+    replay.frame += 1;
+
+    pending.clear();
+    while let Some(entry) = replay.entries.get(replay.next) {
+        if entry.frame > frame {
+            break;
+        }
+        if entry.frame == frame {
+            pending.apply(&entry.commands);
+        }
+        replay.next += 1;
+    }
+
+    if replay.next == replay.entries.len() && frame == replay.entries.last().map_or(0, |e| e.frame) {
+        info!("Command replay finished at frame {}", frame);
+    }
+}
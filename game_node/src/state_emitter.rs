@@ -1,22 +1,65 @@
 //! This module collects game state and writes it to atomic shared memory
+//!
+//! Sampling: `frame_number` counts fixed logic ticks (`FixedUpdate` at `REFRESH_RATE_HZ`), not
+//! rendered frames. The state is emitted in `PostUpdate`, after the camera moved in `Update`, but
+//! only when a new tick happened, so the Controller gets at most one sample per logic tick whatever
+//! the display refresh rate (on a 144Hz display some frames emit nothing, on a slow one a sample
+//! may skip ticks).
 
+use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
 use bevy::prelude::*;
-use crate::command_handler::{SharedMemResource, RenderingPaused};
-use crate::utils::objects::{BaseDoor, RoundStartTimestamp};
+use crate::command_handler::{IdleThrottle, SharedMemResource, RenderingPaused};
+use crate::utils::camera::{camera_azimuth, pyramid_yaw};
+use crate::utils::game_functions::{best_aligned_door, door_alignment};
+use crate::utils::objects::{BaseDoor, BaseFrame, FaceDecoration, PyramidFace, RoundStartTimestamp, SessionStats};
 
 use core::sync::atomic::Ordering;
+use shared::constants::game_constants::{
+    FRAME_DRIFT_WARN_MS, FRAME_TIME_STATS_PERIOD_SECS, REFRESH_RATE_HZ, TARGET_SCREEN_OFFSCREEN,
+};
 
-// Count frames since beginning of game
+// Count logic ticks since beginning of the round
 #[derive(Resource, Default)]
 pub struct FrameCounterResource(pub u64);
 
+// Wall-clock timing of the counted frames, reset together with the frame counter
+#[derive(Resource, Default)]
+pub struct FrameTimingResource {
+    pub counted_secs: f64,
+    pub dropped_frames: u64,
+    pub drift_warned: bool,
+}
+
+// Frame times (ms) of the current statistics window
+#[derive(Resource, Default)]
+pub struct FrameTimeStatsResource {
+    pub samples_ms: Vec<f32>,
+    pub window_secs: f64,
+}
+
 // Update the shared memory game state after every game loop update.
 pub struct StateEmitterPlugin;
 
 impl Plugin for StateEmitterPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<FrameCounterResource>()
-           .add_systems(PostUpdate, (increment_frame_counter, emit_state_to_shm).chain());
+           .init_resource::<FrameTimingResource>()
+           .init_resource::<FrameTimeStatsResource>()
+           .add_systems(FixedUpdate, increment_frame_counter)
+           .add_systems(PostUpdate, (
+               monitor_frame_drift,
+               update_frame_time_stats,
+               emit_state_to_shm,
+               emit_session_stats,
+               emit_visible_decoration_count,
+           ).chain())
+           // Projected with the camera and frame transforms of this frame
+           .add_systems(
+               PostUpdate,
+               emit_target_screen_position
+                   .after(TransformSystems::Propagate)
+                   .after(bevy::camera::CameraUpdateSystems),
+           );
     }
 }
 
@@ -32,6 +75,89 @@ fn increment_frame_counter(
     counter.0 += 1;
 }
 
+// Compare wall-clock time with frame_number / REFRESH_RATE_HZ, so the Controller can discard
+// trials where the game could not keep up and timing integrity was lost
+fn monitor_frame_drift(
+    time: Res<Time<Real>>,
+    frame_counter: Res<FrameCounterResource>,
+    mut timing: ResMut<FrameTimingResource>,
+    paused: Option<Res<RenderingPaused>>,
+    idle: Option<Res<IdleThrottle>>,
+    shm_res: Option<Res<SharedMemResource>>,
+) {
+    // Paused frames are not counted, so they do not count as drift either
+    if paused.is_some_and(|paused| paused.0) {
+        return;
+    }
+
+    let delta = time.delta_secs_f64();
+    timing.counted_secs += delta;
+    // A frame lasting several periods means the frames in between were dropped,
+    // unless the low-power mode slowed the updates down on purpose
+    let periods = (delta * REFRESH_RATE_HZ).round() as u64;
+    let slowed_down = idle.is_some_and(|idle| idle.slowed_down());
+    if periods > 1 && !slowed_down {
+        timing.dropped_frames += periods - 1;
+    }
+
+    let drift_ms = ((timing.counted_secs - frame_counter.0 as f64 / REFRESH_RATE_HZ) * 1000.0) as f32;
+    if drift_ms.abs() > FRAME_DRIFT_WARN_MS {
+        if !timing.drift_warned {
+            warn!(
+                "Frame timing drift of {:.1} ms at frame {} ({} dropped frames)",
+                drift_ms, frame_counter.0, timing.dropped_frames
+            );
+            timing.drift_warned = true;
+        }
+    } else {
+        timing.drift_warned = false;
+    }
+
+    let Some(shm_res) = shm_res else { return };
+    let gs_game = &shm_res.0.get().game_structure_game;
+    gs_game.frame_drift.store(drift_ms.to_bits(), Ordering::Relaxed);
+    gs_game.dropped_frames.store(timing.dropped_frames, Ordering::Relaxed);
+}
+
+// Accumulate the frame times measured by FrameTimeDiagnosticsPlugin and publish mean/p95/p99 once per
+// period, so the Controller can flag trials with rendering hitches
+fn update_frame_time_stats(
+    time: Res<Time<Real>>,
+    diagnostics: Option<Res<DiagnosticsStore>>,
+    mut stats: ResMut<FrameTimeStatsResource>,
+    shm_res: Option<Res<SharedMemResource>>,
+) {
+    let Some(frame_time) = diagnostics
+        .as_ref()
+        .and_then(|d| d.get(&FrameTimeDiagnosticsPlugin::FRAME_TIME))
+        .and_then(|d| d.value())
+    else {
+        return;
+    };
+    stats.samples_ms.push(frame_time as f32);
+    stats.window_secs += time.delta_secs_f64();
+    if stats.window_secs < FRAME_TIME_STATS_PERIOD_SECS {
+        return;
+    }
+
+    let mut samples = std::mem::take(&mut stats.samples_ms);
+    stats.window_secs = 0.0;
+    let Some(shm_res) = shm_res else { return };
+
+    samples.sort_by(f32::total_cmp);
+    let mean = samples.iter().sum::<f32>() / samples.len() as f32;
+    let gs_game = &shm_res.0.get().game_structure_game;
+    gs_game.frame_time_mean.store(mean.to_bits(), Ordering::Relaxed);
+    gs_game.frame_time_p95.store(percentile(&samples, 0.95).to_bits(), Ordering::Relaxed);
+    gs_game.frame_time_p99.store(percentile(&samples, 0.99).to_bits(), Ordering::Relaxed);
+}
+
+// Nearest-rank percentile of sorted, non-empty samples
+fn percentile(sorted: &[f32], p: f32) -> f32 {
+    let rank = (p * sorted.len() as f32).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
 // Write state of the game to shared memory to be read by controller
 fn emit_state_to_shm(
     time: Res<Time>,
@@ -40,11 +166,26 @@ fn emit_state_to_shm(
     camera_query: Query<&Transform, With<Camera3d>>,
     door_query: Query<(&BaseDoor, &Transform)>,
     shm_res: Option<Res<SharedMemResource>>,
+    mut last_emitted_frame: Local<Option<u64>>,
 ) {
     let Some(shm_res) = shm_res else { return };
     let shm = shm_res.0.get();
     let gs_game = &shm.game_structure_game;
 
+    // Liveness, every rendered frame whatever the pause and tick state
+    gs_game.heartbeat.fetch_add(1, Ordering::Relaxed);
+
+    // Every frame too, a reset despawns the doors until setup_round respawns them
+    let target_door_idx = gs_game.target_door.load(Ordering::Relaxed) as usize;
+    let scene_valid = door_query.iter().any(|(door, _)| door.door_index == target_door_idx);
+    gs_game.scene_valid.store(scene_valid, Ordering::Relaxed);
+
+    // One sample per logic tick
+    if *last_emitted_frame == Some(frame_counter.0) {
+        return;
+    }
+    *last_emitted_frame = Some(frame_counter.0);
+
     // Time & Frame
     gs_game.frame_number.store(frame_counter.0, Ordering::Relaxed);
 
@@ -64,34 +205,179 @@ fn emit_state_to_shm(
         gs_game.camera_x.store(pos.x.to_bits(), Ordering::Relaxed);
         gs_game.camera_y.store(pos.y.to_bits(), Ordering::Relaxed);
         gs_game.camera_z.store(pos.z.to_bits(), Ordering::Relaxed);
+        // The doors rotate with the pyramid
+        let yaw = pyramid_yaw(door_query.iter().map(|(_, transform)| transform));
+        let azimuth = camera_azimuth(camera_transform, yaw);
+        gs_game.camera_azimuth.store(azimuth.to_bits(), Ordering::Relaxed);
     }
 
     // Continuous Alignment Calculation
-    let current_alignment; 
-    let current_angle;
-    
-    let target_door_idx = gs_game.target_door.load(Ordering::Relaxed) as usize;
+    let mut best_door = None;
 
     if let Ok(camera_transform) = camera_query.single() {
-        let camera_forward = camera_transform.forward();
-        let camera_forward_xz = Vec3::new(camera_forward.x, 0.0, camera_forward.z).normalize_or_zero();
+        let camera_forward = camera_transform.forward().as_vec3();
 
-        // Find target door
         for (door, door_transform) in &door_query {
+            let door_normal_world = door_transform.rotation * door.normal;
+            // Keep the previous value rather than writing NaN when undefined
+            let Some(alignment) = door_alignment(camera_forward, door_normal_world) else {
+                continue;
+            };
+            best_door = best_aligned_door(best_door, (door.door_index, alignment));
+
+            // Target door
             if door.door_index == target_door_idx {
-                let door_normal_world = door_transform.rotation * door.normal;
-                let door_normal_xz = Vec3::new(door_normal_world.x, 0.0, door_normal_world.z).normalize_or_zero();
-                
-                let alignment = door_normal_xz.dot(camera_forward_xz);
-                current_alignment = alignment;
                 // Angle in radians (0 to PI) using acos, clamping to safe range
-                current_angle = alignment.clamp(-1.0, 1.0).acos();
+                let current_angle = alignment.clamp(-1.0, 1.0).acos();
 
-                gs_game.current_alignment.store(current_alignment.to_bits(), Ordering::Relaxed);
+                gs_game.current_alignment.store(alignment.to_bits(), Ordering::Relaxed);
                 gs_game.current_angle.store(current_angle.to_bits(), Ordering::Relaxed);
-                break;
             }
         }
     }
 
+    let threshold = f32::from_bits(gs_game.engagement_threshold.load(Ordering::Relaxed));
+    gs_game
+        .target_engaged
+        .store(target_engaged(best_door, target_door_idx, threshold), Ordering::Relaxed);
+}
+
+// The subject is oriented towards the target: it is the best aligned door, above the engagement threshold
+fn target_engaged(best_door: Option<(usize, f32)>, target_door: usize, threshold: f32) -> bool {
+    best_door.is_some_and(|(index, alignment)| index == target_door && alignment > threshold)
+}
+
+// Write the session aggregates, every frame since setup_round copies the control structure over them
+fn emit_session_stats(session: Res<SessionStats>, shm_res: Option<Res<SharedMemResource>>) {
+    let Some(shm_res) = shm_res else { return };
+    let gs_game = &shm_res.0.get().game_structure_game;
+    gs_game.session_wins.store(session.wins, Ordering::Relaxed);
+    gs_game.session_aborted.store(session.aborted, Ordering::Relaxed);
+    gs_game.session_checks.store(session.checks, Ordering::Relaxed);
+    gs_game.session_mean_alignment.store(session.mean_alignment().to_bits(), Ordering::Relaxed);
+    gs_game.session_win_streak.store(session.win_streak, Ordering::Relaxed);
+    gs_game.session_best_win_streak.store(session.best_win_streak, Ordering::Relaxed);
+}
+
+// Count the decorations of the faces turned towards the camera, when `emit_visible_decorations` is set.
+// Occlusion is ignored: a front face hidden behind the base still counts.
+fn emit_visible_decoration_count(
+    camera_query: Query<&Transform, With<Camera3d>>,
+    face_query: Query<(&PyramidFace, &Transform, Option<&Children>)>,
+    decoration_query: Query<(), With<FaceDecoration>>,
+    shm_res: Option<Res<SharedMemResource>>,
+) {
+    let Some(shm_res) = shm_res else { return };
+    let shm = shm_res.0.get();
+    let gs_game = &shm.game_structure_game;
+
+    // Live config, mirrored for the readback
+    let enabled = shm.game_structure_control.emit_visible_decorations.load(Ordering::Relaxed);
+    gs_game.emit_visible_decorations.store(enabled, Ordering::Relaxed);
+    if !enabled {
+        gs_game.visible_decoration_count.store(0, Ordering::Relaxed);
+        return;
+    }
+    let Ok(camera_transform) = camera_query.single() else { return };
+
+    let mut count = 0;
+    for (face, face_transform, children) in &face_query {
+        let to_camera = camera_transform.translation - face.world_center(face_transform);
+        if face.world_normal(face_transform).dot(to_camera) <= 0.0 {
+            continue;
+        }
+        // The decorations are spawned as children of their face
+        if let Some(children) = children {
+            count += decoration_query.iter_many(children).count() as u32;
+        }
+    }
+    gs_game.visible_decoration_count.store(count, Ordering::Relaxed);
+}
+
+// Project the center of the target door hole to normalized viewport coordinates, for eye trackers and reaching setups
+fn emit_target_screen_position(
+    camera_query: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    frame_query: Query<(&BaseFrame, &GlobalTransform)>,
+    shm_res: Option<Res<SharedMemResource>>,
+) {
+    let Some(shm_res) = shm_res else { return };
+    let gs_game = &shm_res.0.get().game_structure_game;
+    let target_door = gs_game.target_door.load(Ordering::Relaxed) as usize;
+
+    let position = camera_query.single().ok().and_then(|(camera, camera_transform)| {
+        let (frame, frame_transform) = frame_query.iter().find(|(frame, _)| frame.door_index == target_door)?;
+        let viewport_position = camera
+            .world_to_viewport(camera_transform, frame_transform.transform_point(frame.center))
+            .ok()?;
+        normalized_viewport_position(viewport_position, camera.logical_viewport_size()?)
+    });
+    let [x, y] = position.map_or([TARGET_SCREEN_OFFSCREEN; 2], |position| position.to_array());
+    gs_game.target_screen_x.store(x.to_bits(), Ordering::Relaxed);
+    gs_game.target_screen_y.store(y.to_bits(), Ordering::Relaxed);
+}
+
+/// Viewport position scaled to 0..1 by the viewport size, `None` outside the viewport.
+fn normalized_viewport_position(position: Vec2, viewport_size: Vec2) -> Option<Vec2> {
+    let normalized = position / viewport_size;
+    (normalized.is_finite() && normalized.cmpge(Vec2::ZERO).all() && normalized.cmple(Vec2::ONE).all())
+        .then_some(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn viewport_positions_are_normalized_or_offscreen() {
+        let size = Vec2::new(1920.0, 1080.0);
+        assert_eq!(normalized_viewport_position(Vec2::new(960.0, 270.0), size), Some(Vec2::new(0.5, 0.25)));
+        assert_eq!(normalized_viewport_position(size, size), Some(Vec2::ONE));
+        assert_eq!(normalized_viewport_position(Vec2::new(-1.0, 540.0), size), None);
+        assert_eq!(normalized_viewport_position(Vec2::new(960.0, 1081.0), size), None);
+        // No viewport yet
+        assert_eq!(normalized_viewport_position(Vec2::ZERO, Vec2::ZERO), None);
+    }
+
+    #[test]
+    fn scene_is_invalid_while_the_target_door_is_despawned() {
+        let name = format!("monkey_test_scene_valid_{}", std::process::id());
+        let handle = shared::create_shared_memory(&name).unwrap();
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .init_resource::<FrameCounterResource>()
+            .init_resource::<RoundStartTimestamp>()
+            .insert_resource(SharedMemResource(handle.clone()))
+            .add_systems(Update, emit_state_to_shm);
+        let gs_game = &handle.get().game_structure_game;
+        gs_game.target_door.store(1, Ordering::Relaxed);
+
+        let door = |door_index| {
+            (BaseDoor { door_index, normal: Vec3::Z, is_open: false }, Transform::default())
+        };
+        app.world_mut().spawn(door(0));
+        let target = app.world_mut().spawn(door(1)).id();
+        app.update();
+        assert!(gs_game.scene_valid.load(Ordering::Relaxed));
+
+        // A reset despawned the round, the next frame of the same tick still reports it
+        app.world_mut().despawn(target);
+        app.update();
+        assert!(!gs_game.scene_valid.load(Ordering::Relaxed));
+
+        app.world_mut().spawn(door(1));
+        app.update();
+        assert!(gs_game.scene_valid.load(Ordering::Relaxed));
+        shared::remove_shared_memory(&name).unwrap();
+    }
+
+    #[test]
+    fn engagement_needs_the_target_to_be_the_best_door() {
+        assert!(target_engaged(Some((1, 0.8)), 1, 0.5));
+        // Another door is better aligned, or the target is below the threshold
+        assert!(!target_engaged(Some((0, 0.8)), 1, 0.5));
+        assert!(!target_engaged(Some((1, 0.3)), 1, 0.5));
+        // Facing away from every door
+        assert!(!target_engaged(Some((1, -0.2)), 1, 0.5));
+        assert!(!target_engaged(None, 1, 0.5));
+    }
 }
@@ -0,0 +1,302 @@
+//! Reuse of the meshes and materials of the scene across rounds.
+//!
+//! Materials that only depend on a color (and texture) are shared between the entities of a round
+//! and kept for the next round, instead of one `materials.add` per entity and reset. Decoration
+//! meshes are built around the origin and shared the same way by shape and size. Meshes built in
+//! world space for one round (frames, lid, pyramid) cannot be shared, they are tracked instead and
+//! removed explicitly at the next reset. Cached entries not used during a round are evicted at the
+//! following reset, so randomized colors do not grow the cache over a long session.
+use bevy::ecs::system::SystemParam;
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+
+use crate::utils::objects::DecorationShape;
+use crate::utils::pyramid::create_decoration_mesh;
+
+/// Decoration sizes are rounded to this step (world units), so near-identical sizes share a mesh
+const DECORATION_SIZE_STEP: f32 = 0.001;
+
+/// Which entity a cached material is for, the kind fixes every parameter other than color and texture.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MaterialKind {
+    BaseFrame,
+    BaseLid,
+    PyramidTop,
+    PyramidFace,
+    /// `emissive` is the glow strength relative to the color (f32 bits, 0 for none)
+    Decoration { emissive: u32 },
+}
+
+impl MaterialKind {
+    fn build(self, color: Color, texture: Option<Handle<Image>>) -> StandardMaterial {
+        match self {
+            MaterialKind::BaseFrame => StandardMaterial {
+                base_color: color,
+                base_color_texture: texture,
+                cull_mode: None,
+                double_sided: true,
+                ..default()
+            },
+            MaterialKind::BaseLid => StandardMaterial {
+                base_color: color,
+                base_color_texture: texture,
+                cull_mode: None,
+                double_sided: false,
+                ..default()
+            },
+            MaterialKind::PyramidTop => StandardMaterial {
+                base_color: color,
+                cull_mode: None,
+                double_sided: true,
+                ..default()
+            },
+            MaterialKind::PyramidFace => StandardMaterial {
+                base_color: color,
+                cull_mode: None,
+                double_sided: false,
+                ..default()
+            },
+            MaterialKind::Decoration { emissive } => {
+                let strength = f32::from_bits(emissive);
+                let linear = color.to_linear();
+                StandardMaterial {
+                    base_color: color,
+                    emissive: LinearRgba::rgb(linear.red * strength, linear.green * strength, linear.blue * strength),
+                    reflectance: 0.0,
+                    ..default()
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct MaterialKey {
+    kind: MaterialKind,
+    color: [u32; 4], // sRGBA f32 bits
+    texture: Option<AssetId<Image>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct DecorationMeshKey {
+    shape: DecorationShape,
+    size_steps: u32,
+    // Only set for stars, the other shapes ignore them
+    star_points: u32,
+    star_inner_ratio: u32, // f32 bits
+}
+
+/// A cached handle, `used` tells whether it was requested since the last reset.
+struct CacheEntry<A: Asset> {
+    handle: Handle<A>,
+    used: bool,
+}
+
+#[derive(Resource, Default)]
+pub struct AssetCache {
+    materials: HashMap<MaterialKey, CacheEntry<StandardMaterial>>,
+    decoration_meshes: HashMap<DecorationMeshKey, CacheEntry<Mesh>>,
+    round_meshes: Vec<Handle<Mesh>>,
+    round_materials: Vec<Handle<StandardMaterial>>,
+}
+
+impl AssetCache {
+    /// Returns the shared material of this kind, color and texture, creating it on first use.
+    pub fn material(
+        &mut self,
+        materials: &mut Assets<StandardMaterial>,
+        kind: MaterialKind,
+        color: Color,
+        texture: Option<Handle<Image>>,
+    ) -> Handle<StandardMaterial> {
+        let key = MaterialKey {
+            kind,
+            color: color.to_srgba().to_f32_array().map(f32::to_bits),
+            texture: texture.as_ref().map(Handle::id),
+        };
+        let entry = self.materials.entry(key).or_insert_with(|| CacheEntry {
+            handle: materials.add(kind.build(color, texture)),
+            used: false,
+        });
+        entry.used = true;
+        entry.handle.clone()
+    }
+
+    /// Returns the shared mesh of a decoration shape and size, creating it on first use.
+    pub fn decoration_mesh(
+        &mut self,
+        meshes: &mut Assets<Mesh>,
+        shape: DecorationShape,
+        size: f32,
+        star_points: u32,
+        star_inner_ratio: f32,
+    ) -> Handle<Mesh> {
+        let is_star = shape == DecorationShape::Star;
+        let key = DecorationMeshKey {
+            shape,
+            size_steps: (size / DECORATION_SIZE_STEP).round() as u32,
+            star_points: if is_star { star_points } else { 0 },
+            star_inner_ratio: if is_star { star_inner_ratio.to_bits() } else { 0 },
+        };
+        let entry = self.decoration_meshes.entry(key).or_insert_with(|| {
+            // Built from the rounded size, so the mesh does not depend on which size came first
+            let size = key.size_steps as f32 * DECORATION_SIZE_STEP;
+            CacheEntry {
+                handle: meshes.add(create_decoration_mesh(shape, size, star_points, star_inner_ratio)),
+                used: false,
+            }
+        });
+        entry.used = true;
+        entry.handle.clone()
+    }
+
+    /// Adds a mesh that only lives for the current round.
+    pub fn round_mesh(&mut self, meshes: &mut Assets<Mesh>, mesh: Mesh) -> Handle<Mesh> {
+        let handle = meshes.add(mesh);
+        self.round_meshes.push(handle.clone());
+        handle
+    }
+
+    /// Adds a material that only lives for the current round (e.g. one modified by an animation).
+    pub fn round_material(
+        &mut self,
+        materials: &mut Assets<StandardMaterial>,
+        material: StandardMaterial,
+    ) -> Handle<StandardMaterial> {
+        let handle = materials.add(material);
+        self.round_materials.push(handle.clone());
+        handle
+    }
+
+    /// Removes the assets of the previous round and the cached assets it did not use.
+    /// Called on reset, together with the despawn of the round entities.
+    pub fn begin_round(&mut self, meshes: &mut Assets<Mesh>, materials: &mut Assets<StandardMaterial>) {
+        for handle in self.round_meshes.drain(..) {
+            meshes.remove(&handle);
+        }
+        for handle in self.round_materials.drain(..) {
+            materials.remove(&handle);
+        }
+        evict_unused(&mut self.materials, materials);
+        evict_unused(&mut self.decoration_meshes, meshes);
+    }
+}
+
+/// Removes the entries not used since the last call and marks the others as unused.
+fn evict_unused<K, A: Asset>(cache: &mut HashMap<K, CacheEntry<A>>, assets: &mut Assets<A>) {
+    cache.retain(|_, entry| {
+        if !entry.used {
+            assets.remove(&entry.handle);
+        }
+        std::mem::take(&mut entry.used)
+    });
+}
+
+/// Mesh and material stores together with the cache, for the systems spawning the scene.
+#[derive(SystemParam)]
+pub struct SceneAssets<'w> {
+    pub meshes: ResMut<'w, Assets<Mesh>>,
+    pub materials: ResMut<'w, Assets<StandardMaterial>>,
+    pub cache: ResMut<'w, AssetCache>,
+}
+
+impl SceneAssets<'_> {
+    pub fn material(
+        &mut self,
+        kind: MaterialKind,
+        color: Color,
+        texture: Option<Handle<Image>>,
+    ) -> Handle<StandardMaterial> {
+        self.cache.material(&mut self.materials, kind, color, texture)
+    }
+
+    pub fn decoration_mesh(
+        &mut self,
+        shape: DecorationShape,
+        size: f32,
+        star_points: u32,
+        star_inner_ratio: f32,
+    ) -> Handle<Mesh> {
+        self.cache.decoration_mesh(&mut self.meshes, shape, size, star_points, star_inner_ratio)
+    }
+
+    pub fn round_mesh(&mut self, mesh: Mesh) -> Handle<Mesh> {
+        self.cache.round_mesh(&mut self.meshes, mesh)
+    }
+
+    pub fn round_material(&mut self, material: StandardMaterial) -> Handle<StandardMaterial> {
+        self.cache.round_material(&mut self.materials, material)
+    }
+
+    pub fn begin_round(&mut self) {
+        self.cache.begin_round(&mut self.meshes, &mut self.materials);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decoration_mesh_is_shared_for_identical_inputs() {
+        let mut meshes = Assets::<Mesh>::default();
+        let mut cache = AssetCache::default();
+
+        let circle = cache.decoration_mesh(&mut meshes, DecorationShape::Circle, 0.1, 5, 0.5);
+        assert_eq!(circle, cache.decoration_mesh(&mut meshes, DecorationShape::Circle, 0.1, 5, 0.5));
+        // The star parameters only matter for stars
+        assert_eq!(circle, cache.decoration_mesh(&mut meshes, DecorationShape::Circle, 0.1, 7, 0.3));
+
+        assert_ne!(circle, cache.decoration_mesh(&mut meshes, DecorationShape::Circle, 0.2, 5, 0.5));
+        assert_ne!(circle, cache.decoration_mesh(&mut meshes, DecorationShape::Square, 0.1, 5, 0.5));
+        let star = cache.decoration_mesh(&mut meshes, DecorationShape::Star, 0.1, 5, 0.5);
+        assert_ne!(star, cache.decoration_mesh(&mut meshes, DecorationShape::Star, 0.1, 7, 0.5));
+        assert_eq!(meshes.len(), 5);
+    }
+
+    #[test]
+    fn decoration_emissive_glows_in_the_decoration_color() {
+        let mut materials = Assets::<StandardMaterial>::default();
+        let mut cache = AssetCache::default();
+        let color = Color::srgb(0.2, 0.4, 0.6);
+
+        let lit = cache.material(&mut materials, MaterialKind::Decoration { emissive: 0.0f32.to_bits() }, color, None);
+        assert_eq!(materials.get(&lit).unwrap().emissive, LinearRgba::BLACK);
+
+        let glowing = cache.material(&mut materials, MaterialKind::Decoration { emissive: 0.5f32.to_bits() }, color, None);
+        assert_ne!(lit, glowing);
+        let emissive = materials.get(&glowing).unwrap().emissive;
+        let linear = color.to_linear();
+        assert_eq!(
+            [emissive.red, emissive.green, emissive.blue],
+            [linear.red * 0.5, linear.green * 0.5, linear.blue * 0.5]
+        );
+    }
+
+    #[test]
+    fn assets_stay_bounded_across_resets() {
+        let mut meshes = Assets::<Mesh>::default();
+        let mut materials = Assets::<StandardMaterial>::default();
+        let mut cache = AssetCache::default();
+
+        for round in 0..100 {
+            cache.begin_round(&mut meshes, &mut materials);
+            // World-space meshes and a modified material of the round, as the pyramid and the door hint
+            for _ in 0..10 {
+                cache.round_mesh(&mut meshes, Cuboid::default().mesh().build());
+            }
+            cache.round_material(&mut materials, StandardMaterial::default());
+            // Randomized colors and sizes, which change every round
+            let shade = round as f32 / 100.0;
+            for kind in [MaterialKind::PyramidFace, MaterialKind::Decoration { emissive: 0 }] {
+                cache.material(&mut materials, kind, Color::srgb(shade, 0.5, 0.5), None);
+            }
+            cache.decoration_mesh(&mut meshes, DecorationShape::Circle, 0.1 + shade, 5, 0.5);
+            cache.decoration_mesh(&mut meshes, DecorationShape::Square, 0.1, 5, 0.5);
+
+            // Only this round's assets and the cached ones used by the previous round
+            assert!(meshes.len() <= 10 + 3, "round {}: {} meshes", round, meshes.len());
+            assert!(materials.len() <= 1 + 4, "round {}: {} materials", round, materials.len());
+        }
+    }
+}
@@ -1,14 +1,25 @@
 //! Implementation of a 3D first-person orbit camera plugin for monkey_3d_game.
 
-use crate::command_handler::{PendingRotation, PendingZoom};
+use crate::command_handler::{IdleThrottle, PendingRecenter, PendingRotation, PendingViewpoint, PendingZoom};
+use crate::replay::CommandReplay;
 
 use crate::utils::objects::RotableComponent;
+use std::f32::consts::TAU;
 use std::sync::atomic::Ordering;
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use crate::command_handler::SharedMemResource;
 use shared::constants::camera_3d_constants::{
-    CAMERA_3D_INITIAL_Y, CAMERA_3D_MAX_RADIUS, CAMERA_3D_MIN_RADIUS,
+    CAMERA_3D_FREE_FLY_LOOK_SPEED, CAMERA_3D_FREE_FLY_SPEED, CAMERA_3D_INITIAL_Y,
+    CAMERA_3D_MAX_FOV, CAMERA_3D_MAX_RADIUS, CAMERA_3D_MIN_FOV, CAMERA_3D_MIN_RADIUS,
+    HELD_COMMAND_MAX_DELTA_FRAMES, ROTATE_SPEED_DPS, ZOOM_SPEED_UPS,
 };
+use shared::constants::game_constants::REFRESH_RATE_HZ;
+use shared::{CameraMode, SharedGameStructure, ZoomMode};
+
+/// Components of the 3D cameras driven by the camera systems, UI (2D) cameras are never matched.
+pub type MainCameraQuery<'w, 's> =
+    Query<'w, 's, (&'static mut Transform, &'static mut Projection, &'static Camera), With<Camera3d>>;
 
 /// Apply rotation to all rotable entities by the given delta (in radians).
 pub fn apply_rotation(
@@ -22,33 +33,125 @@ pub fn apply_rotation(
     }
 }
 
-/// Apply zoom to the camera by the given delta.
-pub fn apply_zoom(delta: f32, camera_query: &mut Query<&mut Transform, With<Camera3d>>) {
-    let Ok(mut transform) = camera_query.single_mut() else {
+/// Transform and projection of the 3D camera driven by the controls.
+/// With several 3D cameras, the active one rendered first (lowest `Camera::order`) is used,
+/// where `single_mut` would fail and leave the controls dead.
+pub fn main_camera_mut<'a>(
+    camera_query: &'a mut MainCameraQuery,
+) -> Option<(Mut<'a, Transform>, Mut<'a, Projection>)> {
+    camera_query
+        .iter_mut()
+        .filter(|(_, _, camera)| camera.is_active)
+        .min_by_key(|(_, _, camera)| camera.order)
+        .map(|(transform, projection, _)| (transform, projection))
+}
+
+/// Apply zoom to the camera by the given delta (orbit radius units).
+/// In `ZoomMode::FovZoom` the distance is kept and the field of view changes instead.
+pub fn apply_zoom(delta: f32, mode: ZoomMode, camera_query: &mut MainCameraQuery) {
+    let Some((mut transform, mut projection)) = main_camera_mut(camera_query) else {
         return;
     };
     let (yaw, _, _) = transform.rotation.to_euler(EulerRot::YXZ);
-    let mut radius = transform.translation.xz().length();
+    let radius = transform.translation.xz().length();
+    match mode {
+        ZoomMode::Dolly => place_on_orbit(&mut transform, yaw, radius + delta),
+        ZoomMode::FovZoom => {
+            // An orthographic projection has no field of view to change
+            if let Projection::Perspective(ref mut perspective) = *projection {
+                perspective.fov = zoomed_fov(perspective.fov, radius, delta);
+            }
+        }
+    }
+}
 
-    radius += delta;
-    radius = radius.clamp(CAMERA_3D_MIN_RADIUS, CAMERA_3D_MAX_RADIUS);
+/// Field of view giving the pyramid the apparent size a dolly by `delta` from `distance` would,
+/// clamped to the FovZoom limits.
+pub fn zoomed_fov(fov: f32, distance: f32, delta: f32) -> f32 {
+    let distance = distance.max(CAMERA_3D_MIN_RADIUS);
+    let scale = (distance + delta).max(f32::EPSILON) / distance;
+    (2.0 * ((fov / 2.0).tan() * scale).atan()).clamp(CAMERA_3D_MIN_FOV, CAMERA_3D_MAX_FOV)
+}
 
+/// Puts the camera on the orbit at the given bearing (radians, 0 on +Z) and radius, looking at the pyramid.
+/// The radius is clamped to the zoom limits.
+pub fn place_on_orbit(transform: &mut Transform, yaw: f32, radius: f32) {
+    let radius = radius.clamp(CAMERA_3D_MIN_RADIUS, CAMERA_3D_MAX_RADIUS);
     transform.translation = Vec3::new(radius * yaw.sin(), CAMERA_3D_INITIAL_Y, radius * yaw.cos());
     transform.look_at(Vec3::ZERO, Vec3::Y);
 }
 
+/// Bearing of the camera around the pyramid in [0, 2π), relative to the pyramid rotated by `pyramid_yaw`.
+pub fn camera_azimuth(camera: &Transform, pyramid_yaw: f32) -> f32 {
+    let bearing = camera.translation.x.atan2(camera.translation.z);
+    (bearing - pyramid_yaw).rem_euclid(TAU)
+}
+
+/// Current yaw of the rotable entities given (they all share it), 0 before the first round.
+pub fn pyramid_yaw<'a>(rot_transforms: impl IntoIterator<Item = &'a Transform>) -> f32 {
+    rot_transforms
+        .into_iter()
+        .next()
+        .map_or(0.0, |transform| transform.rotation.to_euler(EulerRot::YXZ).0)
+}
+
+/// Speeds of the held commands from a game structure: `(rotation in rad/s, zoom in radius units/s)`,
+/// the defaults for negative or non-finite values.
+pub fn camera_speeds(gs: &SharedGameStructure) -> (f32, f32) {
+    let speed = |bits: u32, default: f32| {
+        let speed = f32::from_bits(bits);
+        if speed.is_finite() && speed >= 0.0 { speed } else { default }
+    };
+    (
+        speed(gs.rotate_speed_dps.load(Ordering::Relaxed), ROTATE_SPEED_DPS).to_radians(),
+        speed(gs.zoom_speed_ups.load(Ordering::Relaxed), ZOOM_SPEED_UPS),
+    )
+}
+
+/// Step of a command held in `direction` (-1..=1) for a frame of `delta_secs` at `speed` per second,
+/// so holding it moves as far per second whatever the refresh rate.
+pub fn held_command_step(direction: f32, speed: f32, delta_secs: f32) -> f32 {
+    direction * speed * delta_secs
+}
+
+/// Frame time a held command is scaled by: one logic frame when `fixed_step` (deterministic timing, or a
+/// replay, which replays by frame index) or when the low-power mode lengthened the frame, otherwise the
+/// frame time bounded to a few logic frames.
+pub fn held_command_delta(delta_secs: f32, fixed_step: bool, slowed_down: bool) -> f32 {
+    let frame = 1.0 / REFRESH_RATE_HZ as f32;
+    if fixed_step || slowed_down {
+        frame
+    } else {
+        delta_secs.min(HELD_COMMAND_MAX_DELTA_FRAMES * frame)
+    }
+}
+
+/// Clock of the held rotate and zoom commands.
+#[derive(SystemParam)]
+pub struct HeldCommandClock<'w> {
+    time: Res<'w, Time>,
+    idle: Option<Res<'w, IdleThrottle>>,
+    replay: Option<Res<'w, CommandReplay>>,
+}
+
+impl HeldCommandClock<'_> {
+    /// Frame time of the held commands this frame, see `held_command_delta`.
+    pub fn delta_secs(&self, gs_game: &SharedGameStructure) -> f32 {
+        let fixed_step = self.replay.is_some() || gs_game.deterministic_timing.load(Ordering::Relaxed);
+        let slowed_down = self.idle.as_ref().is_some_and(|idle| idle.slowed_down());
+        held_command_delta(self.time.delta_secs(), fixed_step, slowed_down)
+    }
+}
+
 /// System that applies pending rotation from commands.
 pub fn apply_pending_rotation(
     pending: Res<PendingRotation>,
     mut rot_entities: Query<&mut Transform, (With<RotableComponent>, Without<Camera3d>)>,
     shm_res: Option<Res<SharedMemResource>>,
-
+    clock: HeldCommandClock,
 ) {
-    // Read shared memory
-    let Some(shm_res) = shm_res else {
-        error!("Shared Memory not initialized in setup_round");
-        return;
-    };
+    // Scheduled with run_if(shm_available)
+    let Some(shm_res) = shm_res else { return };
 
     let shm = shm_res.0.get();
 
@@ -58,29 +161,290 @@ pub fn apply_pending_rotation(
     if is_animating || pending.0.abs() < 0.0001 {
         return;
     }
-    apply_rotation(pending.0, &mut rot_entities);
+    let (rotate_speed, _) = camera_speeds(&shm.game_structure_game);
+    let delta_secs = clock.delta_secs(&shm.game_structure_game);
+    apply_rotation(held_command_step(pending.0, rotate_speed, delta_secs), &mut rot_entities);
 }
 
 /// System that applies pending zoom from commands.
 pub fn apply_pending_zoom(
     pending: Res<PendingZoom>,
-    mut camera_query: Query<&mut Transform, With<Camera3d>>,
+    mut camera_query: MainCameraQuery,
     shm_res: Option<Res<SharedMemResource>>,
+    clock: HeldCommandClock,
 ) {
 
-    // Read shared memory
-    let Some(shm_res) = shm_res else {
-        error!("Shared Memory not initialized in setup_round");
+    // Scheduled with run_if(shm_available)
+    let Some(shm_res) = shm_res else { return };
+
+    let shm = shm_res.0.get();
+
+    let is_animating = shm.game_structure_game.is_animating.load(Ordering::Relaxed);
+
+
+    // Zooming snaps onto the orbit, so it is ignored while free-flying
+    let is_free_fly = CameraMode::from_u32(shm.game_structure_game.camera_mode.load(Ordering::Relaxed))
+        == CameraMode::FreeFly;
+
+    if is_animating || is_free_fly || pending.0.abs() < 0.0001 {
+        return;
+    }
+    let mode = ZoomMode::from_u32(shm.game_structure_game.zoom_mode.load(Ordering::Relaxed));
+    let (_, zoom_speed) = camera_speeds(&shm.game_structure_game);
+    let delta_secs = clock.delta_secs(&shm.game_structure_game);
+    apply_zoom(held_command_step(pending.0, zoom_speed, delta_secs), mode, &mut camera_query);
+}
+
+/// System that moves the camera to the orbit pose of the `set_viewpoint` command, in one step.
+pub fn apply_pending_viewpoint(
+    pending: Res<PendingViewpoint>,
+    mut camera_query: MainCameraQuery,
+    rot_entities: Query<&Transform, (With<RotableComponent>, Without<Camera3d>)>,
+    shm_res: Option<Res<SharedMemResource>>,
+) {
+    // Scheduled with run_if(shm_available)
+    let Some(shm_res) = shm_res else { return };
+    let Some((azimuth, radius)) = pending.0 else { return };
+
+    let gs_game = &shm_res.0.get().game_structure_game;
+    let is_animating = gs_game.is_animating.load(Ordering::Relaxed);
+    let is_free_fly = CameraMode::from_u32(gs_game.camera_mode.load(Ordering::Relaxed)) == CameraMode::FreeFly;
+    if is_animating || is_free_fly {
+        return;
+    }
+    if !azimuth.is_finite() || !radius.is_finite() {
+        warn!("Ignoring viewpoint with non-finite azimuth {} or radius {}", azimuth, radius);
+        return;
+    }
+    let Some((mut transform, _)) = main_camera_mut(&mut camera_query) else {
         return;
     };
 
+    // The azimuth is relative to the pyramid, as emitted in camera_azimuth
+    let yaw = (pyramid_yaw(rot_entities) + azimuth).rem_euclid(TAU);
+    place_on_orbit(&mut transform, yaw, radius);
+}
+
+/// Puts the camera back at the round's start pose set by the Controller, with the default field of view.
+pub fn reset_to_start_pose(transform: &mut Transform, projection: &mut Projection, gs_ctrl: &SharedGameStructure) {
+    *transform = Transform::from_xyz(
+        f32::from_bits(gs_ctrl.camera_x.load(Ordering::Relaxed)),
+        f32::from_bits(gs_ctrl.camera_y.load(Ordering::Relaxed)),
+        f32::from_bits(gs_ctrl.camera_z.load(Ordering::Relaxed)),
+    )
+    .looking_at(Vec3::ZERO, Vec3::Y);
+    if let Projection::Perspective(ref mut perspective) = *projection {
+        perspective.fov = PerspectiveProjection::default().fov;
+    }
+}
+
+/// System that moves the camera back to the start pose on a `recenter` command.
+pub fn apply_pending_recenter(
+    pending: Res<PendingRecenter>,
+    viewpoint: Res<PendingViewpoint>,
+    mut camera_query: MainCameraQuery,
+    shm_res: Option<Res<SharedMemResource>>,
+) {
+    // Scheduled with run_if(shm_available)
+    let Some(shm_res) = shm_res else { return };
+    // An explicit viewpoint of the same frame wins
+    if !pending.0 || viewpoint.0.is_some() {
+        return;
+    }
+
     let shm = shm_res.0.get();
+    let gs_game = &shm.game_structure_game;
+    let is_animating = gs_game.is_animating.load(Ordering::Relaxed);
+    let is_free_fly = CameraMode::from_u32(gs_game.camera_mode.load(Ordering::Relaxed)) == CameraMode::FreeFly;
+    if is_animating || is_free_fly {
+        return;
+    }
+    let Some((mut transform, mut projection)) = main_camera_mut(&mut camera_query) else {
+        return;
+    };
+    reset_to_start_pose(&mut transform, &mut projection, &shm.game_structure_control);
+}
 
-    let is_animating = shm.game_structure_game.is_animating.load(Ordering::Relaxed);
+/// Moves the camera back onto the valid orbit (clamped radius, fixed height, looking at the pyramid).
+pub fn snap_to_orbit(transform: &mut Transform) {
+    let horizontal = transform.translation.xz();
+    // Keep the current bearing around the pyramid, default to the initial one when at the center
+    let yaw = if horizontal.length_squared() > f32::EPSILON {
+        horizontal.x.atan2(horizontal.y)
+    } else {
+        0.0
+    };
+    place_on_orbit(transform, yaw, horizontal.length());
+}
 
+/// Applies camera mode changes from the Controller, snapping back onto the orbit when leaving free-fly.
+pub fn sync_camera_mode(
+    shm_res: Option<Res<SharedMemResource>>,
+    mut camera_query: MainCameraQuery,
+) {
+    let Some(shm_res) = shm_res else { return };
+    let shm = shm_res.0.get();
 
-    if is_animating || pending.0.abs() < 0.0001 {
+    let requested = shm.game_structure_control.camera_mode.load(Ordering::Relaxed);
+    let previous = shm.game_structure_game.camera_mode.swap(requested, Ordering::Relaxed);
+    let (requested, previous) = (CameraMode::from_u32(requested), CameraMode::from_u32(previous));
+    if requested == previous {
+        return;
+    }
+
+    info!("Camera mode set to {:?}", requested);
+    if requested == CameraMode::Orbit {
+        if let Some((mut transform, _)) = main_camera_mut(&mut camera_query) {
+            snap_to_orbit(&mut transform);
+        }
+    }
+}
+
+/// Free-fly keyboard controls, only active in `CameraMode::FreeFly`.
+/// WASD moves, Q/E goes down/up, arrow keys look around.
+pub fn camera_free_fly_keyboard(
+    input: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    shm_res: Option<Res<SharedMemResource>>,
+    mut camera_query: MainCameraQuery,
+) {
+    let Some(shm_res) = shm_res else { return };
+    let mode = shm_res.0.get().game_structure_game.camera_mode.load(Ordering::Relaxed);
+    if CameraMode::from_u32(mode) != CameraMode::FreeFly {
         return;
     }
-    apply_zoom(pending.0, &mut camera_query);
+    let Some((mut transform, _)) = main_camera_mut(&mut camera_query) else {
+        return;
+    };
+    let dt = time.delta_secs();
+
+    // Look around
+    let (mut yaw, mut pitch, _) = transform.rotation.to_euler(EulerRot::YXZ);
+    let axis = |neg: KeyCode, pos: KeyCode| {
+        input.pressed(pos) as i32 as f32 - input.pressed(neg) as i32 as f32
+    };
+    yaw -= axis(KeyCode::ArrowLeft, KeyCode::ArrowRight) * CAMERA_3D_FREE_FLY_LOOK_SPEED * dt;
+    pitch += axis(KeyCode::ArrowDown, KeyCode::ArrowUp) * CAMERA_3D_FREE_FLY_LOOK_SPEED * dt;
+    // Avoid flipping over the poles
+    pitch = pitch.clamp(-1.5, 1.5);
+    transform.rotation = Quat::from_euler(EulerRot::YXZ, yaw, pitch, 0.0);
+
+    // Move relative to the view direction
+    let forward = transform.forward().as_vec3();
+    let right = transform.right().as_vec3();
+    let movement = forward * axis(KeyCode::KeyS, KeyCode::KeyW)
+        + right * axis(KeyCode::KeyA, KeyCode::KeyD)
+        + Vec3::Y * axis(KeyCode::KeyQ, KeyCode::KeyE);
+    transform.translation += movement.normalize_or_zero() * CAMERA_3D_FREE_FLY_SPEED * dt;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn viewpoint_is_reported_as_camera_azimuth() {
+        let mut transform = Transform::default();
+        let pyramid_yaw = 0.3;
+        for azimuth in [0.0, std::f32::consts::FRAC_PI_2, 3.0, 5.5] {
+            place_on_orbit(&mut transform, pyramid_yaw + azimuth, CAMERA_3D_MIN_RADIUS);
+            assert!((camera_azimuth(&transform, pyramid_yaw) - azimuth).abs() < 1e-4);
+        }
+
+        // Out of range radius is clamped
+        place_on_orbit(&mut transform, 0.0, CAMERA_3D_MAX_RADIUS * 2.0);
+        assert!((transform.translation.xz().length() - CAMERA_3D_MAX_RADIUS).abs() < 1e-4);
+    }
+
+    #[test]
+    fn zoom_only_moves_the_main_3d_camera() {
+        let mut app = App::new();
+        app.add_systems(Update, |mut camera_query: MainCameraQuery| {
+            apply_zoom(1.0, ZoomMode::Dolly, &mut camera_query);
+        });
+
+        let start = Transform::from_xyz(0.0, CAMERA_3D_INITIAL_Y, CAMERA_3D_MIN_RADIUS);
+        let ui = app.world_mut().spawn((Camera2d, start)).id();
+        let main = app.world_mut().spawn((Camera3d::default(), start)).id();
+        // A second 3D camera rendered after the main one (e.g. a debug view)
+        let overlay = app
+            .world_mut()
+            .spawn((Camera3d::default(), Camera { order: 1, ..default() }, start))
+            .id();
+        app.update();
+
+        let translation = |entity| app.world().get::<Transform>(entity).unwrap().translation;
+        assert_eq!(translation(ui), start.translation);
+        assert_eq!(translation(overlay), start.translation);
+        assert!((translation(main).z - (CAMERA_3D_MIN_RADIUS + 1.0)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn fov_zoom_matches_dolly_magnification() {
+        let fov = std::f32::consts::FRAC_PI_4;
+        let distance = 15.0;
+        // Zooming out by 1 unit shrinks the pyramid by distance / (distance + 1), in both modes
+        let zoomed = zoomed_fov(fov, distance, 1.0);
+        let magnification = (fov / 2.0).tan() / (zoomed / 2.0).tan();
+        assert!((magnification - distance / (distance + 1.0)).abs() < 1e-4);
+
+        assert_eq!(zoomed_fov(fov, distance, 100.0), CAMERA_3D_MAX_FOV);
+        assert_eq!(zoomed_fov(fov, distance, -100.0), CAMERA_3D_MIN_FOV);
+    }
+
+    #[test]
+    fn recenter_restores_the_start_pose() {
+        let gs_ctrl = SharedGameStructure::new();
+        let mut transform = Transform::from_xyz(3.0, 1.0, -7.0);
+        let mut projection = Projection::Perspective(PerspectiveProjection { fov: CAMERA_3D_MIN_FOV, ..default() });
+        reset_to_start_pose(&mut transform, &mut projection, &gs_ctrl);
+
+        let start = Vec3::new(
+            f32::from_bits(gs_ctrl.camera_x.load(Ordering::Relaxed)),
+            f32::from_bits(gs_ctrl.camera_y.load(Ordering::Relaxed)),
+            f32::from_bits(gs_ctrl.camera_z.load(Ordering::Relaxed)),
+        );
+        assert_eq!(transform.translation, start);
+        assert!((transform.forward().as_vec3() - (-start).normalize()).length() < 1e-4);
+        let Projection::Perspective(perspective) = projection else { unreachable!() };
+        assert_eq!(perspective.fov, PerspectiveProjection::default().fov);
+    }
+
+    #[test]
+    fn held_rotation_does_not_depend_on_the_refresh_rate() {
+        let (rotate_speed, _) = camera_speeds(&SharedGameStructure::new());
+        for hz in [60u32, 120, 144] {
+            let mut app = App::new();
+            app.add_plugins(bevy::time::TimePlugin)
+                .insert_resource(bevy::time::TimeUpdateStrategy::ManualDuration(
+                    std::time::Duration::from_secs_f64(1.0 / hz as f64),
+                ))
+                .add_systems(
+                    Update,
+                    move |time: Res<Time>,
+                          mut rot_entities: Query<&mut Transform, (With<RotableComponent>, Without<Camera3d>)>| {
+                        apply_rotation(held_command_step(1.0, rotate_speed, time.delta_secs()), &mut rot_entities);
+                    },
+                );
+            let pyramid = app.world_mut().spawn((Transform::default(), RotableComponent)).id();
+            // The first update only starts the clock
+            for _ in 0..=hz / 2 {
+                app.update();
+            }
+
+            // Half a second of holding turns by half the speed, at every refresh rate
+            let yaw = pyramid_yaw([app.world().get::<Transform>(pyramid).unwrap()]);
+            assert!((yaw - rotate_speed / 2.0).abs() < 1e-3, "{} Hz: {} rad", hz, yaw);
+        }
+    }
+
+    #[test]
+    fn held_commands_do_not_jump_after_a_long_frame() {
+        let frame = 1.0 / REFRESH_RATE_HZ as f32;
+        assert_eq!(held_command_delta(frame / 2.0, false, false), frame / 2.0);
+        // A hitch, the wake up from the low-power mode, and a replay or deterministic timing
+        assert_eq!(held_command_delta(0.5, false, false), HELD_COMMAND_MAX_DELTA_FRAMES * frame);
+        assert_eq!(held_command_delta(0.1, false, true), frame);
+        assert_eq!(held_command_delta(frame / 2.0, true, false), frame);
+    }
 }
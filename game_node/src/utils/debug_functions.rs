@@ -1,12 +1,95 @@
 //! Debug functions for the game.
 use bevy::{prelude::*, window::*};
+use core::sync::atomic::Ordering;
+use shared::constants::{base_layout::is_valid_door_count, pyramid_constants::BASE_NR_SIDES};
+use shared::DoorOpenStyle;
+
+use crate::command_handler::{apply_input_lockout, read_shared_memory, PendingReset, SharedMemResource};
+use crate::utils::objects::{GameEntity, RoundStartTimestamp, SessionStats, UIEntity};
+use crate::utils::pyramid::round_entity_budget;
 
 pub struct DebugFunctionsPlugin;
 
 impl Plugin for DebugFunctionsPlugin {
     /// Builds the plugin by adding the `toggle_vsync` system to the app.
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, (toggle_vsync, visualize_lights));
+        app.add_systems(Startup, spawn_session_stats_overlay)
+            .add_systems(Update, (toggle_vsync, visualize_lights, update_session_stats_overlay))
+            // After the shared memory read, so the reset it requests is handled this frame
+            .add_systems(
+                PreUpdate,
+                cycle_target_door.after(read_shared_memory).before(apply_input_lockout),
+            )
+            // In PostUpdate, once the spawns and despawns of the reset have been applied
+            .add_systems(PostUpdate, check_entity_budget);
+    }
+}
+
+/// Cycles the target door with ',' (backward) and '.' (forward) and resets the round,
+/// to visually check every door without scripting the Controller.
+fn cycle_target_door(
+    input: Res<ButtonInput<KeyCode>>,
+    shm_res: Option<Res<SharedMemResource>>,
+    mut pending_reset: ResMut<PendingReset>,
+) {
+    let forward = if input.just_pressed(KeyCode::Period) {
+        true
+    } else if input.just_pressed(KeyCode::Comma) {
+        false
+    } else {
+        return;
+    };
+    let Some(shm_res) = shm_res else {
+        warn!("Cannot cycle the target door: shared memory not initialized");
+        return;
+    };
+
+    // Written to the control structure, which the reset copies into the game structure
+    let gs_ctrl = &shm_res.0.get().game_structure_control;
+    let mut door_count = gs_ctrl.base_nr_sides.load(Ordering::Relaxed) as usize;
+    if !is_valid_door_count(door_count) {
+        door_count = BASE_NR_SIDES;
+    }
+    let step = if forward { 1 } else { door_count - 1 };
+    let current = gs_ctrl.target_door.load(Ordering::Relaxed) as usize;
+    let target_door = (current % door_count + step) % door_count;
+    gs_ctrl.target_door.store(target_door as u32, Ordering::Relaxed);
+    pending_reset.0 = true;
+
+    info!("Target door set to {} (debug)", target_door);
+}
+
+/// Warns when a new round has more `GameEntity`/`UIEntity` than a round spawns,
+/// i.e. entities of previous rounds were not despawned on reset and are accumulating.
+fn check_entity_budget(
+    round_start: Res<RoundStartTimestamp>,
+    shm_res: Option<Res<SharedMemResource>>,
+    game_entities: Query<(), With<GameEntity>>,
+    ui_entities: Query<(), With<UIEntity>>,
+) {
+    // A new round sets the start timestamp in setup_round
+    if !round_start.is_changed() || round_start.0.is_none() {
+        return;
+    }
+    let Some(shm_res) = shm_res else { return };
+
+    let gs_game = &shm_res.0.get().game_structure_game;
+    let decorations_count = gs_game.decorations_count.each_ref().map(|c| c.load(Ordering::Relaxed));
+    let nr_sides = gs_game.base_nr_sides.load(Ordering::Relaxed) as usize;
+    let door_open_style = DoorOpenStyle::from_u32(gs_game.door_open_style.load(Ordering::Relaxed));
+    let game_budget = round_entity_budget(nr_sides, decorations_count, door_open_style);
+    let game_count = game_entities.iter().count();
+    if game_count > game_budget {
+        warn!(
+            "{} GameEntity after reset, expected at most {}: entities are leaking across rounds",
+            game_count, game_budget
+        );
+    }
+
+    // At most the score bar
+    let ui_count = ui_entities.iter().count();
+    if ui_count > 1 {
+        warn!("{} UIEntity after reset, expected at most 1: entities are leaking across rounds", ui_count);
     }
 }
 
@@ -25,6 +108,54 @@ fn toggle_vsync(input: Res<ButtonInput<KeyCode>>, mut window: Query<&mut Window>
     }
 }
 
+/// Text overlay of the session stats, toggled by `update_session_stats_overlay`.
+/// Not a `UIEntity`, so it survives the resets.
+#[derive(Component)]
+struct SessionStatsOverlay;
+
+fn spawn_session_stats_overlay(mut commands: Commands) {
+    commands.spawn((
+        Text::default(),
+        TextFont { font_size: 16.0, ..default() },
+        TextColor(Color::srgba(1.0, 1.0, 1.0, 0.8)),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(8.0),
+            left: Val::Px(8.0),
+            ..default()
+        },
+        Visibility::Hidden,
+        SessionStatsOverlay,
+    ));
+}
+
+/// Shows the session stats when the 'I' key is pressed, and keeps them up to date while shown.
+fn update_session_stats_overlay(
+    input: Res<ButtonInput<KeyCode>>,
+    session: Res<SessionStats>,
+    mut overlay: Query<(&mut Text, &mut Visibility), With<SessionStatsOverlay>>,
+) {
+    let Ok((mut text, mut visibility)) = overlay.single_mut() else {
+        return;
+    };
+    if input.just_pressed(KeyCode::KeyI) {
+        visibility.toggle_visible_hidden();
+        info!("Session stats overlay: {:?}", *visibility);
+    }
+    if *visibility == Visibility::Hidden || !(session.is_changed() || input.just_pressed(KeyCode::KeyI)) {
+        return;
+    }
+    text.0 = format!(
+        "Wins: {}  Aborted: {}  Checks: {}  Mean alignment: {:.3}  Win streak: {} (best {})",
+        session.wins,
+        session.aborted,
+        session.checks,
+        session.mean_alignment(),
+        session.win_streak,
+        session.best_win_streak
+    );
+}
+
 /// Visualizes lights when the 'L' key is pressed.
 fn visualize_lights(
     mut gizmos: Gizmos,
@@ -1,18 +1,47 @@
 //! Core game and UI functions.
 use bevy::prelude::*;
 
-use crate::command_handler::PendingCheckAlignment;
+use crate::command_handler::{PendingCheckAlignment, PendingForceWin, PendingReset};
 use crate::command_handler::SharedMemResource;
+use crate::state_emitter::FrameCounterResource;
+use crate::utils::asset_cache::SceneAssets;
+use crate::utils::setup::brightness_gain;
 use crate::utils::objects::{
-    BaseDoor, DoorWinEntities, GameEntity, HoleEmissive, HoleLight, ScoreBarFill,
-    ScoreBarUI, UIEntity,
+    BaseDoor, BaseFrame, BestDoorHint, DoorLeaf, DoorWinEntities, FixationBar, FixationCross, GameEntity,
+    HoleEmissive, HoleLight, RoundStartTimestamp, ScoreBarFill, ScoreBarSegment, ScoreBarUI, SessionStats,
+    UIEntity,
 };
 use core::sync::atomic::Ordering;
+use std::time::Duration;
+use shared::{
+    AttemptsPolicy, DoorOpenStyle, ScoreBarOrientation, SharedGameStructure, SharedMemory, TrialResult, TrialResultSummary,
+};
+use shared::constants::lighting_constants::PRACTICE_TARGET_LIGHT_INTENSITY;
+use shared::constants::pyramid_constants::{DOOR_SLIDE_PULL_RATIO, DOOR_SWING_ANGLE_DEG};
 use shared::constants::game_constants::{
-    SCORE_BAR_BORDER_THICKNESS, SCORE_BAR_HEIGHT, SCORE_BAR_TOP_OFFSET, SCORE_BAR_WIDTH_PERCENT,
-    UI_REFERENCE_HEIGHT,
+    ALIGNMENT_TIE_EPSILON, AUTO_RESET_DELAY_SECS, BEST_DOOR_HINT_EMISSIVE, FIXATION_THICKNESS_RATIO, SCORE_BAR_BORDER_THICKNESS,
+    SCORE_BAR_HEIGHT, SCORE_BAR_MAX_SEGMENTS, SCORE_BAR_TOP_OFFSET, SCORE_BAR_WIDTH_PERCENT, UI_REFERENCE_HEIGHT,
 };
 
+/// Dim cyan of the segments of a discrete score bar that are not lit
+const SCORE_BAR_UNLIT_SEGMENT_COLOR: Color = Color::srgba(0.2, 0.6, 1.0, 0.15);
+
+/// Keeps the more aligned of the best `(door_index, alignment)` so far and a candidate.
+/// Alignments within ALIGNMENT_TIE_EPSILON go to the lowest door index, so the result does not
+/// depend on the (unstable) query order.
+pub fn best_aligned_door(best: Option<(usize, f32)>, candidate: (usize, f32)) -> Option<(usize, f32)> {
+    let Some((best_index, best_alignment)) = best else {
+        return Some(candidate);
+    };
+    let (index, alignment) = candidate;
+    let better = if (alignment - best_alignment).abs() <= ALIGNMENT_TIE_EPSILON {
+        index < best_index
+    } else {
+        alignment > best_alignment
+    };
+    if better { Some(candidate) } else { best }
+}
+
 /// Helper to despawn ui entities given a mutable commands reference
 pub fn despawn_ui_helper(commands: &mut Commands, query: &Query<Entity, With<UIEntity>>) {
     for entity in query {
@@ -30,6 +59,7 @@ pub fn cleanup_game_entities(mut commands: Commands, query: Query<Entity, With<G
 /// Applies pending check alignment
 pub fn apply_pending_check_alignment(
     pending: Res<PendingCheckAlignment>,
+    force_win: Res<PendingForceWin>,
     shm_res: Option<Res<SharedMemResource>>,
     camera_query: Query<&Transform, With<Camera3d>>,
     door_query: Query<(Entity, &BaseDoor, &Transform)>,
@@ -37,6 +67,9 @@ pub fn apply_pending_check_alignment(
     time: Res<Time>,
     ui_query: Query<Entity, With<UIEntity>>,
     mut door_win_entities: ResMut<DoorWinEntities>,
+    round_start: Res<RoundStartTimestamp>,
+    mut session: ResMut<SessionStats>,
+    frame_counter: Res<FrameCounterResource>,
 ) {
     let Some(shm_res) = shm_res else { return };
     let shm = shm_res.0.get();
@@ -47,74 +80,297 @@ pub fn apply_pending_check_alignment(
         return;
     }
 
-    // Increment attempt counter
-    let attempts = gs_game.attempts.load(Ordering::Relaxed) + 1;
-    gs_game.attempts.store(attempts, Ordering::Relaxed);
-
-    let Ok(camera_transform) = camera_query.single() else {
-        return;
-    };
-
-    // Get local camera direction
-    let camera_forward = camera_transform.forward();
-
-    // Project camera forward to XZ plane
-    let camera_forward_xz = Vec3::new(camera_forward.x, 0.0, camera_forward.z).normalize();
+    // Debug force win: the camera and doors are skipped, as if the target door was perfectly aligned
+    let forced = force_win.0;
+    let winning_door_alignment = if forced {
+        warn!("Round won by the debug force_win command");
+        1.0
+    } else {
+        let Ok(camera_transform) = camera_query.single() else {
+            return;
+        };
 
-    let mut best_alignment = -1.0;
-    let mut _best_door_index = 0;
-    let mut winning_door_alignment = -1.0;
+        // Get local camera direction, the alignment is undefined when looking straight up or down
+        let camera_forward = camera_transform.forward().as_vec3();
+        if project_xz(camera_forward).is_none() {
+            warn!("Check alignment skipped: camera forward has no horizontal component");
+            return;
+        }
 
-    // Determine target door from SHM
-    let target_door_idx = gs_game.target_door.load(Ordering::Relaxed);
+        let mut best_door = None;
+        let mut winning_door_alignment = -1.0;
 
-    for (_, door, door_transform) in &door_query {
-        // Get door normal in world space
-        let door_normal_world = door_transform.rotation * door.normal;
+        // Determine target door from SHM
+        let target_door_idx = gs_game.target_door.load(Ordering::Relaxed);
 
-        // Project to XZ plane
-        let door_normal_xz = Vec3::new(door_normal_world.x, 0.0, door_normal_world.z).normalize();
+        for (_, door, door_transform) in &door_query {
+            // Get door normal in world space
+            let door_normal_world = door_transform.rotation * door.normal;
+            let Some(alignment) = door_alignment(camera_forward, door_normal_world) else {
+                continue;
+            };
 
-        // Calculate alignment (dot product)
-        let alignment = door_normal_xz.dot(camera_forward_xz);
+            // Most positive = door facing toward camera (from outside)
+            best_door = best_aligned_door(best_door, (door.door_index, alignment));
 
-        // Most positive = door facing toward camera (from outside)
-        if alignment > best_alignment {
-            best_alignment = alignment;
-            _best_door_index = door.door_index;
+            // Save the alignment for the target door
+            if door.door_index as u32 == target_door_idx {
+                winning_door_alignment = alignment;
+            }
         }
-
-        // Save the alignment for the target door
-        if door.door_index as u32 == target_door_idx {
-            winning_door_alignment = alignment;
+        if let Some((door_index, alignment)) = best_door {
+            debug!("Best aligned door: {} ({:.3})", door_index, alignment);
         }
-    }
+        winning_door_alignment
+    };
 
     // Store alignment for score bar animation AND SHM
     gs_game
         .current_alignment
         .store(winning_door_alignment.to_bits(), Ordering::Relaxed);
 
-    // Player wins
-    if winning_door_alignment > f32::from_bits(gs_game.cosine_alignment_threshold.load(Ordering::Relaxed)) {
+    // Practice checks are kept out of the real metrics
+    let (attempts, win_time) = if gs_game.practice_mode.load(Ordering::Relaxed) {
+        (&gs_game.practice_attempts, &gs_game.practice_win_time)
+    } else {
+        (&gs_game.attempts, &gs_game.win_time)
+    };
+
+    // A correct check extends the streak, a wrong one restarts it
+    let correct = forced || winning_door_alignment > f32::from_bits(gs_game.cosine_alignment_threshold.load(Ordering::Relaxed));
+    let streak = if correct { gs_game.correct_streak.load(Ordering::Relaxed) + 1 } else { 0 };
+    gs_game.correct_streak.store(streak, Ordering::Relaxed);
+
+    // Player wins once enough correct checks in a row were made (at once when forced)
+    let required = gs_game.consecutive_required.load(Ordering::Relaxed).max(1);
+    let won = correct && (forced || streak >= required);
+    let first_win = won && !round_won(gs_game);
+    if won {
         // Player wins! Set win time in SHM to trigger win state
-        gs_game.win_time.store(time.elapsed().as_secs_f32().to_bits(), Ordering::Relaxed);
+        win_time.store(time.elapsed().as_secs_f32().to_bits(), Ordering::Relaxed);
+    }
+
+    // Increment attempt counter according to the configured policy
+    let policy = AttemptsPolicy::from_u32(gs_game.attempts_increment_policy.load(Ordering::Relaxed));
+    if policy == AttemptsPolicy::AllChecks || !correct {
+        attempts.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // The round summary, once its attempts include this check
+    if first_win {
+        publish_trial_result(shm, TrialResult::Win, &round_start, time.elapsed());
+    }
+    if !gs_game.practice_mode.load(Ordering::Relaxed) {
+        session.checks += 1;
+        session.alignment_sum += winning_door_alignment;
+        if first_win {
+            session.wins += 1;
+            session.win_streak += 1;
+            session.best_win_streak = session.best_win_streak.max(session.win_streak);
+        }
     }
 
     // Every alignment check triggers the door animation on the winning light/emissive
+    latch_door_animation_timing(&shm.game_structure_control, gs_game);
     gs_game.is_animating.store(true, Ordering::Relaxed);
-    door_win_entities.animation_start_time = Some(time.elapsed());
+    door_win_entities.start_animation(time.elapsed(), frame_counter.0);
 
     // Clean old UI and spawn new (Score Bar)
     despawn_ui_helper(&mut commands, &ui_query);
-    spawn_score_bar(&mut commands);
+    spawn_score_bar(&mut commands, ScoreBarLayout::from_game_structure(gs_game));
 }
 
-/// Spawns the energy score bar at the top center of the screen
-pub fn spawn_score_bar(commands: &mut Commands) {
-    // Container for the score bar (centered at top)
-    commands
-        .spawn((
+/// Whether the current round was won (its practice win for a practice round).
+pub fn round_won(gs: &SharedGameStructure) -> bool {
+    let win_time = if gs.practice_mode.load(Ordering::Relaxed) {
+        &gs.practice_win_time
+    } else {
+        &gs.win_time
+    };
+    win_time.load(Ordering::Relaxed) != 0
+}
+
+/// Publishes the end-of-round summary of the current round, from its attempts and last check alignment.
+pub fn publish_trial_result(
+    shm: &SharedMemory,
+    result: TrialResult,
+    round_start: &RoundStartTimestamp,
+    now: std::time::Duration,
+) {
+    let gs_game = &shm.game_structure_game;
+    let attempts = if gs_game.practice_mode.load(Ordering::Relaxed) {
+        &gs_game.practice_attempts
+    } else {
+        &gs_game.attempts
+    };
+    let duration = now.saturating_sub(round_start.0.unwrap_or_default());
+    let summary = TrialResultSummary {
+        result_code: result as u32,
+        result_attempts: attempts.load(Ordering::Relaxed),
+        result_time_ms: duration.as_millis().min(u32::MAX as u128) as u32,
+        result_alignment: f32::from_bits(gs_game.current_alignment.load(Ordering::Relaxed)),
+    };
+    shm.trial_result.publish(&summary);
+    info!("Round ended: {:?} ({:?})", result, summary);
+}
+
+/// Projects a direction on the XZ plane and normalizes it.
+/// `None` for a (near) vertical direction, e.g. a camera looking straight up or down.
+pub fn project_xz(direction: Vec3) -> Option<Vec3> {
+    Vec3::new(direction.x, 0.0, direction.z).try_normalize()
+}
+
+/// Cosine between the camera forward and a door normal, both projected on the XZ plane.
+/// 1.0 when the camera looks straight along the normal, 0.0 when perpendicular, -1.0 when opposite.
+/// `None` when either direction is vertical, as the alignment is undefined (instead of NaN).
+pub fn door_alignment(camera_forward: Vec3, door_normal_world: Vec3) -> Option<f32> {
+    Some(project_xz(door_normal_world)?.dot(project_xz(camera_forward)?))
+}
+
+/// Requests a reset `auto_reset_delay_secs` after a won round's reveal animation is over, when
+/// `auto_reset_after_result` is set, so a session runs without the Controller sending the resets.
+/// Goes through the reset command path: the round index increments and the input lockout applies.
+pub fn auto_reset_after_result(
+    shm_res: Option<Res<SharedMemResource>>,
+    time: Res<Time>,
+    mut pending_reset: ResMut<PendingReset>,
+    mut waiting_since: Local<Option<Duration>>,
+) {
+    let Some(shm_res) = shm_res else { return };
+    let shm = shm_res.0.get();
+    let gs_game = &shm.game_structure_game;
+
+    // Live config, mirrored for the readback
+    let enabled = shm.game_structure_control.auto_reset_after_result.load(Ordering::Relaxed);
+    let delay_bits = shm.game_structure_control.auto_reset_delay_secs.load(Ordering::Relaxed);
+    gs_game.auto_reset_after_result.store(enabled, Ordering::Relaxed);
+    gs_game.auto_reset_delay_secs.store(delay_bits, Ordering::Relaxed);
+
+    // The wait starts when the reveal ends, the animation of a later check restarts it
+    let ended = round_won(gs_game) && !gs_game.is_animating.load(Ordering::Relaxed);
+    if !enabled || !ended || pending_reset.0 {
+        *waiting_since = None;
+        return;
+    }
+    let now = time.elapsed();
+    let since = *waiting_since.get_or_insert(now);
+    let delay = f32::from_bits(delay_bits);
+    let delay = if delay.is_finite() && delay >= 0.0 { delay } else { AUTO_RESET_DELAY_SECS };
+    if (now - since).as_secs_f32() >= delay {
+        *waiting_since = None;
+        pending_reset.0 = true;
+        info!("Auto reset after round {}", gs_game.round_index.load(Ordering::Relaxed));
+    }
+}
+
+/// Highlights the frame of the door the camera is best aligned with, when `show_best_door_hint` is set.
+/// The hint is hidden during the door animation, so it never mixes with the reveal of the target door.
+pub fn update_best_door_hint(
+    shm_res: Option<Res<SharedMemResource>>,
+    round_start: Res<RoundStartTimestamp>,
+    camera_query: Query<&Transform, With<Camera3d>>,
+    door_query: Query<(&BaseDoor, &Transform)>,
+    mut frame_query: Query<(&BaseFrame, &mut MeshMaterial3d<StandardMaterial>)>,
+    mut hint: ResMut<BestDoorHint>,
+    mut assets: SceneAssets,
+) {
+    let Some(shm_res) = shm_res else { return };
+    let shm = shm_res.0.get();
+
+    // Live config, mirrored for the readback
+    let enabled = shm.game_structure_control.show_best_door_hint.load(Ordering::Relaxed);
+    shm.game_structure_game.show_best_door_hint.store(enabled, Ordering::Relaxed);
+
+    // The frames of the previous round (and their materials) are gone
+    if round_start.is_changed() {
+        *hint = BestDoorHint::default();
+    }
+
+    let is_animating = shm.game_structure_game.is_animating.load(Ordering::Relaxed);
+    let best = match camera_query.single() {
+        Ok(camera_transform) if enabled && !is_animating => {
+            let camera_forward = camera_transform.forward().as_vec3();
+            door_query.iter().fold(None, |best, (door, door_transform)| {
+                match door_alignment(camera_forward, door_transform.rotation * door.normal) {
+                    Some(alignment) => best_aligned_door(best, (door.door_index, alignment)),
+                    None => best,
+                }
+            })
+        }
+        _ => None,
+    };
+    let best_index = best.map(|(door_index, _)| door_index);
+    if best_index == hint.door_index {
+        return;
+    }
+
+    // Restore the previous frame before highlighting the new one
+    if let Some(original) = hint.original_material.take() {
+        for (frame, mut material) in &mut frame_query {
+            if Some(frame.door_index) == hint.door_index {
+                material.0 = original.clone();
+            }
+        }
+    }
+    for (frame, mut material) in &mut frame_query {
+        if Some(frame.door_index) != best_index {
+            continue;
+        }
+        let hint_material = match &hint.hint_material {
+            Some(handle) => handle.clone(),
+            None => {
+                let mut highlighted = assets.materials.get(&material.0).cloned().unwrap_or_default();
+                let [r, g, b] = BEST_DOOR_HINT_EMISSIVE;
+                highlighted.emissive = LinearRgba::rgb(r, g, b);
+                let handle = assets.round_material(highlighted);
+                hint.hint_material = Some(handle.clone());
+                handle
+            }
+        };
+        hint.original_material = Some(std::mem::replace(&mut material.0, hint_material));
+    }
+    hint.door_index = best_index;
+}
+
+/// Score bar settings of the game structure.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ScoreBarLayout {
+    pub orientation: ScoreBarOrientation,
+    /// 0 for a continuous fill
+    pub segments: u32,
+}
+
+impl ScoreBarLayout {
+    pub fn from_game_structure(gs: &SharedGameStructure) -> Self {
+        Self {
+            orientation: ScoreBarOrientation::from_u32(gs.score_bar_orientation.load(Ordering::Relaxed)),
+            segments: gs.score_bar_segments.load(Ordering::Relaxed).min(SCORE_BAR_MAX_SEGMENTS),
+        }
+    }
+}
+
+/// Spawns the energy score bar, at the top center of the screen or on the right edge when vertical
+pub fn spawn_score_bar(commands: &mut Commands, layout: ScoreBarLayout) {
+    let orientation = layout.orientation;
+    let (container, bar) = match orientation {
+        // Centered vertically on the right edge, filling from the bottom
+        ScoreBarOrientation::Vertical => (
+            Node {
+                position_type: PositionType::Absolute,
+                height: Val::Percent(100.0),
+                right: Val::Px(SCORE_BAR_TOP_OFFSET),
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            Node {
+                width: Val::Px(SCORE_BAR_HEIGHT),
+                height: Val::Percent(SCORE_BAR_WIDTH_PERCENT),
+                flex_direction: FlexDirection::ColumnReverse,
+                ..default()
+            },
+        ),
+        // Centered at top, the fill grows from the left end or from the center
+        ScoreBarOrientation::LeftToRight | ScoreBarOrientation::CenterOut => (
             Node {
                 position_type: PositionType::Absolute,
                 width: Val::Percent(100.0),
@@ -122,38 +378,164 @@ pub fn spawn_score_bar(commands: &mut Commands) {
                 justify_content: JustifyContent::Center,
                 ..default()
             },
-            UIEntity,
-        ))
+            Node {
+                width: Val::Percent(SCORE_BAR_WIDTH_PERCENT),
+                height: Val::Px(SCORE_BAR_HEIGHT),
+                justify_content: if orientation == ScoreBarOrientation::CenterOut {
+                    JustifyContent::Center
+                } else {
+                    JustifyContent::FlexStart
+                },
+                ..default()
+            },
+        ),
+    };
+
+    commands
+        .spawn((container, UIEntity))
         .with_children(|parent| {
             // Outer border/background of the bar
             parent
                 .spawn((
                     Node {
-                        width: Val::Percent(SCORE_BAR_WIDTH_PERCENT),
-                        height: Val::Px(SCORE_BAR_HEIGHT),
                         border: UiRect::all(Val::Px(SCORE_BAR_BORDER_THICKNESS)),
                         padding: UiRect::all(Val::Px(2.0)),
-                        ..default()
+                        ..bar
                     },
                     BackgroundColor(Color::srgba(0.1, 0.1, 0.1, 0.5)), // Dark subtle background
                     ScoreBarUI,
                 ))
                 .with_children(|bar_parent| {
-                    // Inner fill bar (starts empty)
-                    bar_parent.spawn((
-                        Node {
-                            width: Val::Percent(0.0), // Starts empty
-                            height: Val::Percent(100.0),
-                            ..default()
-                        },
-                        BackgroundColor(Color::srgba(0.2, 0.6, 1.0, 0.3)), // Dim cyan glow when empty
-                        ScoreBarFill,
-                    ));
+                    if layout.segments == 0 {
+                        // Inner fill bar (starts empty)
+                        bar_parent.spawn((
+                            score_bar_fill_node(orientation, 0.0),
+                            BackgroundColor(Color::srgba(0.2, 0.6, 1.0, 0.3)), // Dim cyan glow when empty
+                            ScoreBarFill,
+                        ));
+                        return;
+                    }
+                    // Discrete bar: equal segments along the fill direction, all unlit
+                    for index in 0..layout.segments {
+                        bar_parent.spawn((
+                            Node {
+                                flex_grow: 1.0,
+                                flex_basis: Val::Px(0.0),
+                                margin: UiRect::all(Val::Px(1.0)),
+                                ..default()
+                            },
+                            BackgroundColor(SCORE_BAR_UNLIT_SEGMENT_COLOR),
+                            ScoreBarSegment(index),
+                        ));
+                    }
                 });
         });
 }
 
-/// Handles the light animation
+/// Whether segment `index` of a discrete bar is lit for a fill of `percent`: the lit count is rounded
+/// down, and `CenterOut` lights the segments symmetrically from the middle.
+pub fn score_bar_segment_lit(layout: ScoreBarLayout, index: u32, percent: f32) -> bool {
+    let lit = (percent / 100.0 * layout.segments as f32).floor() as u32;
+    match layout.orientation {
+        // Distance of the segment center to the bar center, in half segments
+        ScoreBarOrientation::CenterOut => (2 * index + 1).abs_diff(layout.segments) < lit,
+        ScoreBarOrientation::LeftToRight | ScoreBarOrientation::Vertical => index < lit,
+    }
+}
+
+/// Fill node of the score bar, `percent` of the bar along its orientation.
+pub fn score_bar_fill_node(orientation: ScoreBarOrientation, percent: f32) -> Node {
+    match orientation {
+        ScoreBarOrientation::Vertical => Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(percent),
+            ..default()
+        },
+        ScoreBarOrientation::LeftToRight | ScoreBarOrientation::CenterOut => Node {
+            width: Val::Percent(percent),
+            height: Val::Percent(100.0),
+            ..default()
+        },
+    }
+}
+
+/// Spawns the (hidden) fixation cross at screen center, shown by `sync_fixation_cross`.
+pub fn spawn_fixation_cross(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            // Above the 3D scene but below the score bar and the blank screen
+            GlobalZIndex(-1),
+            Visibility::Hidden,
+            FixationCross,
+        ))
+        .with_children(|parent| {
+            for horizontal in [true, false] {
+                parent.spawn((
+                    Node {
+                        position_type: PositionType::Absolute,
+                        ..default()
+                    },
+                    BackgroundColor(Color::WHITE),
+                    FixationBar { horizontal },
+                ));
+            }
+        });
+}
+
+/// Applies the fixation cross config from the Controller (visibility, size and color), without a reset
+pub fn sync_fixation_cross(
+    shm_res: Option<Res<SharedMemResource>>,
+    mut cross_query: Query<&mut Visibility, With<FixationCross>>,
+    mut bar_query: Query<(&mut Node, &mut BackgroundColor, &FixationBar)>,
+) {
+    let Some(shm_res) = shm_res else { return };
+    let shm = shm_res.0.get();
+    let gs_ctrl = &shm.game_structure_control;
+    let gs_game = &shm.game_structure_game;
+
+    let show = gs_ctrl.show_fixation.load(Ordering::Relaxed);
+    gs_game.show_fixation.store(show, Ordering::Relaxed);
+    let size_bits = gs_ctrl.fixation_size.load(Ordering::Relaxed);
+    gs_game.fixation_size.store(size_bits, Ordering::Relaxed);
+    let mut color = [0.0; 4];
+    for (i, channel) in color.iter_mut().enumerate() {
+        let bits = gs_ctrl.fixation_color[i].load(Ordering::Relaxed);
+        gs_game.fixation_color[i].store(bits, Ordering::Relaxed);
+        *channel = f32::from_bits(bits);
+    }
+
+    let requested = if show { Visibility::Visible } else { Visibility::Hidden };
+    for mut visibility in cross_query.iter_mut() {
+        visibility.set_if_neq(requested);
+    }
+    if !show {
+        return;
+    }
+
+    let size = f32::from_bits(size_bits).max(0.0);
+    let thickness = (size * FIXATION_THICKNESS_RATIO).max(1.0);
+    let color = Color::srgba(color[0], color[1], color[2], color[3]);
+    for (mut node, mut background, bar) in bar_query.iter_mut() {
+        let (width, height) = if bar.horizontal { (size, thickness) } else { (thickness, size) };
+        // Only write on change to keep change detection quiet
+        if node.width != Val::Px(width) || node.height != Val::Px(height) {
+            node.width = Val::Px(width);
+            node.height = Val::Px(height);
+        }
+        background.set_if_neq(BackgroundColor(color));
+    }
+}
+
+/// Handles the light animation, and opens the target door leaf with it
+#[allow(clippy::too_many_arguments)]
 pub fn handle_door_animation(
     mut door_win_entities: ResMut<DoorWinEntities>,
     shm_res: Option<Res<SharedMemResource>>,
@@ -165,6 +547,9 @@ pub fn handle_door_animation(
         (With<HoleEmissive>, Without<HoleLight>),
     >,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    mut door_query: Query<&mut BaseDoor>,
+    mut leaf_query: Query<(&DoorLeaf, &mut Transform)>,
+    frame_counter: Res<FrameCounterResource>,
 ) {
 
     let Some(shm_res) = shm_res else { return };
@@ -177,12 +562,12 @@ pub fn handle_door_animation(
         return;
     }
 
-    let Some(start_time) = door_win_entities.animation_start_time else {
+    let deterministic = gs_game.deterministic_timing.load(Ordering::Relaxed);
+    let Some(elapsed) = door_win_entities.animation_elapsed(time.elapsed(), frame_counter.0, deterministic) else {
         // No start time set — animation state is inconsistent, clear it
-        gs_game.is_animating.store(false, Ordering::Relaxed);
+        clear_animation_state(&mut door_win_entities, gs_game);
         return;
     };
-    let elapsed = (time.elapsed() - start_time).as_secs_f32();
 
     // Config values from SHM
     let fade_out_end = f32::from_bits(gs_game.door_anim_fade_out.load(Ordering::Relaxed));
@@ -194,16 +579,14 @@ pub fn handle_door_animation(
     // Get light entity from door_win_entities (winning_light = SpotLight/HoleLight)
     let Some(light_entity) = door_win_entities.winning_light else {
         // Entity was despawned (e.g. by reset)
-        door_win_entities.animation_start_time = None;
-        gs_game.is_animating.store(false, Ordering::Relaxed);
+        clear_animation_state(&mut door_win_entities, gs_game);
         return;
     };
 
     // Get light visibility and component
     let Ok((mut light_visibility, mut spotlight)) = light_query.get_mut(light_entity) else {
-        // Entity no longer valid 
-        door_win_entities.animation_start_time = None;
-        gs_game.is_animating.store(false, Ordering::Relaxed);
+        // Entity no longer valid
+        clear_animation_state(&mut door_win_entities, gs_game);
         return;
     };
 
@@ -222,6 +605,19 @@ pub fn handle_door_animation(
         0.0
     };
 
+    // The target door opens with the light and closes as it fades back in
+    let target_door = gs_game.target_door.load(Ordering::Relaxed) as usize;
+    let door_open_style = DoorOpenStyle::from_u32(gs_game.door_open_style.load(Ordering::Relaxed));
+    let is_open = intensity_factor > 0.0 && elapsed < stay_open_end;
+    for mut door in door_query.iter_mut().filter(|door| door.door_index == target_door) {
+        if door.is_open != is_open {
+            door.is_open = is_open;
+        }
+    }
+    for (leaf, mut transform) in leaf_query.iter_mut().filter(|(leaf, _)| leaf.door_index == target_door) {
+        transform.set_if_neq(door_leaf_transform(leaf, door_open_style, intensity_factor));
+    }
+
     // Max intensity values 
     let max_spotlight_intensity = f32::from_bits(gs_game.max_spotlight_intensity.load(Ordering::Relaxed));
 
@@ -231,7 +627,8 @@ pub fn handle_door_animation(
         *light_visibility = Visibility::Visible;
 
         let light_intensity = max_spotlight_intensity * intensity_factor;
-        spotlight.intensity = light_intensity;
+        // Never below the resting hint, which would flicker at the start and end of the reveal
+        spotlight.intensity = light_intensity.max(target_resting_intensity(gs_game)) * brightness_gain(gs_game);
 
         // Also update emissive material
         if let Some(emissive_entity) = door_win_entities.winning_emissive {
@@ -269,23 +666,117 @@ pub fn handle_door_animation(
             }
         }
 
-        // Clear animation timing state (winning entities persist for the round)
-        door_win_entities.animation_start_time = None;
-        gs_game.is_animating.store(false, Ordering::Relaxed);
+        clear_animation_state(&mut door_win_entities, gs_game);
+    }
+}
+
+/// Local transform of a door leaf `open` of the way (0 closed, 1 fully open) through its opening.
+pub fn door_leaf_transform(leaf: &DoorLeaf, style: DoorOpenStyle, open: f32) -> Transform {
+    let open = open.clamp(0.0, 1.0);
+    match style {
+        DoorOpenStyle::LightOnly => Transform::default(),
+        DoorOpenStyle::Slide => {
+            // Pulled out of the hole in the first half, then slid past the hinge side, clear of the hole
+            let pull = (open * 2.0).min(1.0) * leaf.radius * DOOR_SLIDE_PULL_RATIO;
+            let slide = (open * 2.0 - 1.0).max(0.0) * 2.0 * leaf.radius;
+            Transform::from_translation(leaf.outward * pull - leaf.right * slide)
+        }
+        DoorOpenStyle::Swing => {
+            // Around the hinge axis, turning the free edge outwards
+            let axis = leaf.right.cross(leaf.outward).normalize();
+            let rotation = Quat::from_axis_angle(axis, open * DOOR_SWING_ANGLE_DEG.to_radians());
+            Transform {
+                translation: leaf.hinge - rotation * leaf.hinge,
+                rotation,
+                scale: Vec3::ONE,
+            }
+        }
+    }
+}
+
+/// Resting intensity of the target door light for a round: `target_hint_intensity`, or at least
+/// PRACTICE_TARGET_LIGHT_INTENSITY in practice mode (0 keeps it dark until revealed).
+pub fn target_resting_intensity(gs_game: &SharedGameStructure) -> f32 {
+    let hint = f32::from_bits(gs_game.target_hint_intensity.load(Ordering::Relaxed));
+    let hint = if hint.is_finite() { hint.max(0.0) } else { 0.0 };
+    if gs_game.practice_mode.load(Ordering::Relaxed) {
+        hint.max(PRACTICE_TARGET_LIGHT_INTENSITY)
+    } else {
+        hint
+    }
+}
+
+/// Resting intensity modulated by the `hint_pulse_hz` / `hint_pulse_depth` breathing at `elapsed_secs`,
+/// clamped at 0. A non-positive or non-finite frequency or depth keeps the intensity steady.
+pub fn pulsed_intensity(gs_game: &SharedGameStructure, intensity: f32, elapsed_secs: f32) -> f32 {
+    let hz = f32::from_bits(gs_game.hint_pulse_hz.load(Ordering::Relaxed));
+    let depth = f32::from_bits(gs_game.hint_pulse_depth.load(Ordering::Relaxed));
+    if !(hz.is_finite() && hz > 0.0 && depth.is_finite() && depth > 0.0) {
+        return intensity;
+    }
+    (intensity + depth * (std::f32::consts::TAU * hz * elapsed_secs).sin()).max(0.0)
+}
+
+/// Keeps the target door light at its resting intensity outside of the door animation, from the first frame
+/// of the round (scheduled after the reset) and again once each animation ends, breathing if configured.
+pub fn update_target_resting_light(
+    door_win_entities: Res<DoorWinEntities>,
+    shm_res: Option<Res<SharedMemResource>>,
+    time: Res<Time>,
+    mut light_query: Query<(&mut Visibility, &mut SpotLight), With<HoleLight>>,
+) {
+    let Some(shm_res) = shm_res else { return };
+    let gs_game = &shm_res.0.get().game_structure_game;
+    let intensity = target_resting_intensity(gs_game);
+    // The reveal takes over the light while animating
+    if intensity <= 0.0 || gs_game.is_animating.load(Ordering::Relaxed) {
+        return;
+    }
+    let intensity = pulsed_intensity(gs_game, intensity, time.elapsed_secs()) * brightness_gain(gs_game);
+    let Some(light_entity) = door_win_entities.winning_light else {
+        return;
+    };
+    if let Ok((mut visibility, mut spotlight)) = light_query.get_mut(light_entity) {
+        visibility.set_if_neq(Visibility::Visible);
+        if spotlight.intensity != intensity {
+            spotlight.intensity = intensity;
+        }
+    }
+}
+
+/// Takes the phase durations of an animation starting now from the Controller, so a change applies to the
+/// next animation, also within the round. Live config, mirrored for the readback; a running animation keeps its timing.
+pub fn latch_door_animation_timing(gs_ctrl: &SharedGameStructure, gs_game: &SharedGameStructure) {
+    for (ctrl, game) in [
+        (&gs_ctrl.door_anim_fade_out, &gs_game.door_anim_fade_out),
+        (&gs_ctrl.door_anim_stay_open, &gs_game.door_anim_stay_open),
+        (&gs_ctrl.door_anim_fade_in, &gs_game.door_anim_fade_in),
+    ] {
+        game.store(ctrl.load(Ordering::Relaxed), Ordering::Relaxed);
     }
 }
 
+/// Stops the door animation, used when it finishes or its state became inconsistent
+/// (e.g. the winning entities were despawned by a reset mid-animation).
+/// Only the timing is cleared, the winning entities persist for the round.
+pub fn clear_animation_state(door_win_entities: &mut DoorWinEntities, gs_game: &SharedGameStructure) {
+    door_win_entities.animation_start_time = None;
+    gs_game.is_animating.store(false, Ordering::Relaxed);
+}
+
 
 /// Updates the score bar fill and color during the door animation
 pub fn update_score_bar_animation(
     door_win_entities: Res<DoorWinEntities>,
     shm_res: Option<Res<SharedMemResource>>,
     time: Res<Time>,
+    frame_counter: Res<FrameCounterResource>,
     mut fill_query: Query<(&mut Node, &mut BackgroundColor), With<ScoreBarFill>>,
+    mut segment_query: Query<(&ScoreBarSegment, &mut BackgroundColor), Without<ScoreBarFill>>,
 ) {
-    let Ok((mut node, mut bg_color)) = fill_query.single_mut() else {
+    if fill_query.is_empty() && segment_query.is_empty() {
         return;
-    };
+    }
     let Some(shm_res) = shm_res else { return };
     let shm = shm_res.0.get();
 
@@ -302,10 +793,11 @@ pub fn update_score_bar_animation(
     // Calculate the bar width — only show fill during animation, otherwise empty
     let current_width = if is_animating {
         // During animation: fill progressively based on animation progress
-        let Some(start_time) = door_win_entities.animation_start_time else {
+        // Same clock as the door animation
+        let deterministic = shm.game_structure_game.deterministic_timing.load(Ordering::Relaxed);
+        let Some(elapsed) = door_win_entities.animation_elapsed(time.elapsed(), frame_counter.0, deterministic) else {
             return;
         };
-        let elapsed = (time.elapsed() - start_time).as_secs_f32();
 
         let fade_out_end = f32::from_bits(
             shm.game_structure_game
@@ -332,8 +824,6 @@ pub fn update_score_bar_animation(
         0.0
     };
 
-    node.width = Val::Percent(current_width);
-
     // Color gradient based on alignment quality (cyan -> yellow -> white)
     let color = if alignment_normalized < 0.5 {
         let t = alignment_normalized * 2.0; // 0.0 to 1.0 for first half
@@ -353,7 +843,17 @@ pub fn update_score_bar_animation(
         )
     };
 
-    *bg_color = BackgroundColor(color);
+    let layout = ScoreBarLayout::from_game_structure(&shm.game_structure_game);
+    if let Ok((mut node, mut bg_color)) = fill_query.single_mut() {
+        let fill = score_bar_fill_node(layout.orientation, current_width);
+        node.width = fill.width;
+        node.height = fill.height;
+        *bg_color = BackgroundColor(color);
+    }
+    for (segment, mut bg_color) in segment_query.iter_mut() {
+        let lit = score_bar_segment_lit(layout, segment.0, current_width);
+        *bg_color = BackgroundColor(if lit { color } else { SCORE_BAR_UNLIT_SEGMENT_COLOR });
+    }
 }
 
 /// Updates UI scale based on window size for responsive design
@@ -371,3 +871,271 @@ pub fn update_ui_scale(mut ui_scale: ResMut<UiScale>, window_query: Query<&Windo
 
     ui_scale.0 = clamped_scale;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::constants::game_constants::REFRESH_RATE_HZ;
+    use shared::{create_shared_memory, remove_shared_memory};
+    use std::time::Duration;
+
+    #[test]
+    fn door_alignment_of_known_poses() {
+        let door_normal = Vec3::new(0.0, 0.0, -1.0);
+
+        let alignment = |camera_forward| door_alignment(camera_forward, door_normal).unwrap();
+
+        // Looking straight along the door normal
+        assert!((alignment(Vec3::new(0.0, 0.0, -1.0)) - 1.0).abs() < 1e-6);
+        // Perpendicular
+        assert!(alignment(Vec3::X).abs() < 1e-6);
+        // Opposite
+        assert!((alignment(Vec3::new(0.0, 0.0, 1.0)) + 1.0).abs() < 1e-6);
+        // The vertical component is ignored (camera looking slightly down at the door)
+        assert!((alignment(Vec3::new(0.0, -0.5, -1.0)) - 1.0).abs() < 1e-6);
+        // Undefined when looking straight down
+        assert_eq!(door_alignment(Vec3::NEG_Y, door_normal), None);
+    }
+
+    #[test]
+    fn equally_aligned_doors_pick_the_lowest_index() {
+        // Two doors symmetric about the camera forward, both at 45 degrees
+        let camera_forward = Vec3::new(0.0, 0.0, -1.0);
+        let doors = [
+            (2, Vec3::new(1.0, 0.0, -1.0).normalize()),
+            (0, Vec3::new(-1.0, 0.0, -1.0).normalize()),
+            (1, Vec3::new(0.0, 0.0, 1.0)),
+        ];
+        let best = |order: &[(usize, Vec3)]| {
+            order.iter().fold(None, |best, &(index, normal)| {
+                best_aligned_door(best, (index, door_alignment(camera_forward, normal).unwrap()))
+            })
+        };
+
+        let mut reversed = doors;
+        reversed.reverse();
+        assert_eq!(best(&doors).map(|(index, _)| index), Some(0));
+        assert_eq!(best(&reversed).map(|(index, _)| index), Some(0));
+    }
+
+    #[test]
+    fn top_down_camera_check_does_not_write_nan() {
+        let name = format!("monkey_test_top_down_check_{}", std::process::id());
+        let handle = create_shared_memory(&name).unwrap();
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .init_resource::<DoorWinEntities>()
+            .insert_resource(PendingCheckAlignment(true))
+            .init_resource::<PendingForceWin>()
+            .init_resource::<RoundStartTimestamp>()
+            .init_resource::<SessionStats>()
+            .insert_resource(SharedMemResource(handle.clone()))
+            .init_resource::<FrameCounterResource>()
+            .add_systems(Update, apply_pending_check_alignment);
+
+        // Camera straight above the pyramid, looking down
+        app.world_mut().spawn((
+            Camera3d::default(),
+            Transform::from_xyz(0.0, 10.0, 0.0).looking_at(Vec3::ZERO, Vec3::Z),
+        ));
+        app.world_mut().spawn((
+            BaseDoor {
+                door_index: 0,
+                normal: Vec3::Z,
+                is_open: false,
+            },
+            Transform::default(),
+        ));
+        app.update();
+
+        let gs_game = &handle.get().game_structure_game;
+        let alignment = f32::from_bits(gs_game.current_alignment.load(Ordering::Relaxed));
+        assert!(!alignment.is_nan());
+        assert!(!gs_game.is_animating.load(Ordering::Relaxed));
+        remove_shared_memory(&name).unwrap();
+    }
+
+    #[test]
+    fn force_win_bypasses_the_alignment() {
+        let name = format!("monkey_test_force_win_{}", std::process::id());
+        let handle = create_shared_memory(&name).unwrap();
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .init_resource::<DoorWinEntities>()
+            .insert_resource(PendingCheckAlignment(true))
+            .insert_resource(PendingForceWin(true))
+            .init_resource::<RoundStartTimestamp>()
+            .init_resource::<SessionStats>()
+            .insert_resource(SharedMemResource(handle.clone()))
+            .init_resource::<FrameCounterResource>()
+            .add_systems(Update, apply_pending_check_alignment);
+        app.world_mut().resource_mut::<Time>().advance_by(std::time::Duration::from_secs(1));
+
+        // Looking straight down, a regular check would be skipped
+        app.world_mut().spawn((
+            Camera3d::default(),
+            Transform::from_xyz(0.0, 10.0, 0.0).looking_at(Vec3::ZERO, Vec3::Z),
+        ));
+        app.update();
+
+        let gs_game = &handle.get().game_structure_game;
+        assert_eq!(f32::from_bits(gs_game.current_alignment.load(Ordering::Relaxed)), 1.0);
+        assert_eq!(f32::from_bits(gs_game.win_time.load(Ordering::Relaxed)), 1.0);
+        assert!(gs_game.is_animating.load(Ordering::Relaxed));
+
+        let result = handle.get().trial_result.take().unwrap();
+        assert_eq!(TrialResult::from_u32(result.result_code), TrialResult::Win);
+        assert_eq!((result.result_attempts, result.result_time_ms), (1, 1000));
+
+        let session = app.world().resource::<SessionStats>();
+        assert_eq!((session.wins, session.checks, session.mean_alignment()), (1, 1, 1.0));
+        assert_eq!((session.win_streak, session.best_win_streak), (1, 1));
+        remove_shared_memory(&name).unwrap();
+    }
+
+    #[test]
+    fn despawn_during_door_animation_clears_is_animating() {
+        let name = format!("monkey_test_door_animation_{}", std::process::id());
+        let handle = create_shared_memory(&name).unwrap();
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .init_resource::<Assets<StandardMaterial>>()
+            .insert_resource(SharedMemResource(handle.clone()))
+            .init_resource::<FrameCounterResource>()
+            .add_systems(Update, handle_door_animation);
+
+        // Active animation on a winning light
+        let light = app
+            .world_mut()
+            .spawn((Visibility::Hidden, SpotLight::default(), HoleLight))
+            .id();
+        app.insert_resource(DoorWinEntities {
+            winning_light: Some(light),
+            winning_emissive: None,
+            animation_start_time: Some(Duration::ZERO),
+            animation_start_frame: 0,
+        });
+        handle.get().game_structure_game.is_animating.store(true, Ordering::Relaxed);
+
+        // A reset despawns the round entities mid-animation
+        app.world_mut().despawn(light);
+        app.update();
+
+        assert!(!handle.get().game_structure_game.is_animating.load(Ordering::Relaxed));
+        assert!(app.world().resource::<DoorWinEntities>().animation_start_time.is_none());
+        remove_shared_memory(&name).unwrap();
+    }
+
+    #[test]
+    fn auto_reset_waits_for_the_reveal_and_the_delay() {
+        let name = format!("monkey_test_auto_reset_{}", std::process::id());
+        let handle = create_shared_memory(&name).unwrap();
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .init_resource::<PendingReset>()
+            .insert_resource(SharedMemResource(handle.clone()))
+            .add_systems(Update, auto_reset_after_result);
+        let shm = handle.get();
+        shm.game_structure_control.auto_reset_after_result.store(true, Ordering::Relaxed);
+        shm.game_structure_control.auto_reset_delay_secs.store(0.5f32.to_bits(), Ordering::Relaxed);
+        let advance = |app: &mut App, secs: f32| {
+            app.world_mut().resource_mut::<Time>().advance_by(Duration::from_secs_f32(secs));
+            app.update();
+            app.world().resource::<PendingReset>().0
+        };
+
+        // Not won yet, then won with the reveal playing
+        assert!(!advance(&mut app, 1.0));
+        shm.game_structure_game.win_time.store(1.0f32.to_bits(), Ordering::Relaxed);
+        shm.game_structure_game.is_animating.store(true, Ordering::Relaxed);
+        assert!(!advance(&mut app, 1.0));
+
+        // The delay counts from the end of the reveal
+        shm.game_structure_game.is_animating.store(false, Ordering::Relaxed);
+        assert!(!advance(&mut app, 1.0));
+        assert!(!advance(&mut app, 0.25));
+        assert!(advance(&mut app, 0.25));
+        assert!(shm.game_structure_game.auto_reset_after_result.load(Ordering::Relaxed));
+        remove_shared_memory(&name).unwrap();
+    }
+
+    #[test]
+    fn deterministic_animation_clock_counts_logic_frames() {
+        let mut door_win_entities = DoorWinEntities::default();
+        assert_eq!(door_win_entities.animation_elapsed(Duration::from_secs(1), 10, true), None);
+
+        door_win_entities.start_animation(Duration::from_secs(1), 10);
+        // Two wall-clock seconds but a single logic frame, e.g. after a stall
+        let now = Duration::from_secs(3);
+        assert_eq!(door_win_entities.animation_elapsed(now, 11, false), Some(2.0));
+        assert_eq!(door_win_entities.animation_elapsed(now, 11, true), Some(1.0 / REFRESH_RATE_HZ as f32));
+    }
+
+    #[test]
+    fn door_animation_timing_is_taken_when_an_animation_starts() {
+        let gs_ctrl = SharedGameStructure::new();
+        let gs_game = SharedGameStructure::new();
+        gs_ctrl.door_anim_stay_open.store(4.0f32.to_bits(), Ordering::Relaxed);
+        assert_ne!(f32::from_bits(gs_game.door_anim_stay_open.load(Ordering::Relaxed)), 4.0);
+
+        latch_door_animation_timing(&gs_ctrl, &gs_game);
+        assert_eq!(f32::from_bits(gs_game.door_anim_stay_open.load(Ordering::Relaxed)), 4.0);
+        assert_eq!(
+            gs_game.door_anim_fade_in.load(Ordering::Relaxed),
+            gs_ctrl.door_anim_fade_in.load(Ordering::Relaxed)
+        );
+    }
+
+    #[test]
+    fn vertical_score_bar_fills_its_height() {
+        let fill = score_bar_fill_node(ScoreBarOrientation::Vertical, 60.0);
+        assert_eq!((fill.width, fill.height), (Val::Percent(100.0), Val::Percent(60.0)));
+        for orientation in [ScoreBarOrientation::LeftToRight, ScoreBarOrientation::CenterOut] {
+            let fill = score_bar_fill_node(orientation, 60.0);
+            assert_eq!((fill.width, fill.height), (Val::Percent(60.0), Val::Percent(100.0)));
+        }
+    }
+
+    #[test]
+    fn segments_light_in_proportion_to_the_fill() {
+        let lit = |orientation, percent| {
+            let layout = ScoreBarLayout { orientation, segments: 5 };
+            (0..5).map(|index| score_bar_segment_lit(layout, index, percent)).collect::<Vec<_>>()
+        };
+        assert_eq!(lit(ScoreBarOrientation::LeftToRight, 59.0), [true, true, false, false, false]);
+        assert_eq!(lit(ScoreBarOrientation::LeftToRight, 100.0), [true; 5]);
+        assert_eq!(lit(ScoreBarOrientation::CenterOut, 19.0), [false; 5]);
+        assert_eq!(lit(ScoreBarOrientation::CenterOut, 60.0), [false, true, true, true, false]);
+    }
+
+    #[test]
+    fn door_leaves_open_out_of_the_hole() {
+        // Hole of radius 1 centered at (0, 1, 5), facing +Z
+        let leaf = DoorLeaf {
+            door_index: 0,
+            hinge: Vec3::new(-1.0, 1.0, 5.0),
+            outward: Vec3::Z,
+            right: Vec3::X,
+            radius: 1.0,
+        };
+        let center = leaf.hinge + leaf.right * leaf.radius;
+
+        // Closed at rest whatever the style, and LightOnly never moves
+        for style in [DoorOpenStyle::LightOnly, DoorOpenStyle::Slide, DoorOpenStyle::Swing] {
+            assert_eq!(door_leaf_transform(&leaf, style, 0.0), Transform::default());
+        }
+        assert_eq!(door_leaf_transform(&leaf, DoorOpenStyle::LightOnly, 1.0), Transform::default());
+
+        // The swing keeps the hinge and turns the leaf outwards
+        let swing = door_leaf_transform(&leaf, DoorOpenStyle::Swing, 1.0);
+        assert!(swing.transform_point(leaf.hinge).distance(leaf.hinge) < 1e-5);
+        assert!((swing.transform_point(center) - center).dot(leaf.outward) > 0.9);
+        let half = door_leaf_transform(&leaf, DoorOpenStyle::Swing, 0.5);
+        assert!((half.transform_point(center) - center).dot(leaf.outward) > 0.0);
+
+        // The slide ends in front of the frame, clear of the hole
+        let slide = door_leaf_transform(&leaf, DoorOpenStyle::Slide, 1.0).transform_point(center);
+        assert!((slide - center).dot(leaf.outward) > 0.0);
+        assert!((slide - center).dot(leaf.right).abs() >= 2.0 * leaf.radius - 1e-5);
+    }
+}
@@ -1,6 +1,7 @@
 //! Macros for the game, such as for cross-platform logging.
 
-/// A macro for cross-platform logging (native and web platforms)
+/// A macro for cross-platform logging (native and web platforms).
+/// On native it is an `info!` of the game, so `MONKEY_LOG_LEVEL` can silence it.
 #[macro_export]
 macro_rules! log {
     ($($t:tt)*) => {{
@@ -8,6 +9,6 @@ macro_rules! log {
         web_sys::console::log_1(&format!($($t)*).into());
 
         #[cfg(not(target_arch = "wasm32"))]
-        println!($($t)*);
+        ::bevy::log::info!($($t)*);
     }};
 }
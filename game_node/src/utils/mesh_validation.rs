@@ -0,0 +1,63 @@
+//! Validation helpers for the hand-built procedural meshes.
+use bevy::mesh::PrimitiveTopology;
+use bevy::prelude::*;
+
+/// Checks that all vertex attributes of a mesh have the same length and that every index is in range.
+/// Only active in debug builds, where it panics with the mesh name instead of failing deep inside Bevy.
+pub fn debug_assert_mesh_valid(mesh: &Mesh, name: &str) {
+    if !cfg!(debug_assertions) {
+        return;
+    }
+
+    // All attributes (positions, normals, uvs, ...) must describe the same number of vertices
+    let mut vertex_count: Option<(&str, usize)> = None;
+    for (attribute, values) in mesh.attributes() {
+        match vertex_count {
+            None => vertex_count = Some((attribute.name, values.len())),
+            Some((first_name, first_len)) => assert_eq!(
+                values.len(),
+                first_len,
+                "Mesh '{}': attribute {} has {} vertices but {} has {}",
+                name,
+                attribute.name,
+                values.len(),
+                first_name,
+                first_len
+            ),
+        }
+    }
+    let vertex_count = vertex_count.map_or(0, |(_, len)| len);
+    let is_triangle_list = mesh.primitive_topology() == PrimitiveTopology::TriangleList;
+
+    match mesh.indices() {
+        Some(indices) => {
+            if let Some(max_index) = indices.iter().max() {
+                assert!(
+                    max_index < vertex_count,
+                    "Mesh '{}': index {} out of range for {} vertices",
+                    name,
+                    max_index,
+                    vertex_count
+                );
+            }
+            if is_triangle_list {
+                assert!(
+                    indices.len().is_multiple_of(3),
+                    "Mesh '{}': {} indices is not a multiple of 3 for a triangle list",
+                    name,
+                    indices.len()
+                );
+            }
+        }
+        None => {
+            if is_triangle_list {
+                assert!(
+                    vertex_count.is_multiple_of(3),
+                    "Mesh '{}': {} vertices is not a multiple of 3 for a non-indexed triangle list",
+                    name,
+                    vertex_count
+                );
+            }
+        }
+    }
+}
@@ -1,15 +1,24 @@
 //! This file defines the various objects, resources, and components used in the game.
 use bevy::prelude::*;
+use shared::constants::game_constants::REFRESH_RATE_HZ;
+use shared::BaseTexture;
 use std::time::Duration;
 
 
-/// Shapes for decorations on the pyramid faces
-#[derive(Clone, Copy, Debug, PartialEq)]
+/// Shape and color of the decorations of one face triangle
+pub type DecorationStyle = (DecorationShape, Color);
+
+/// Style of the decorations of one face triangle and the number of decorations placed on it
+pub type PlacedDecorations = (DecorationStyle, u32);
+
+/// Shapes for decorations on the pyramid faces (the value is reported in `decoration_shapes`)
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum DecorationShape {
-    Circle,
-    Square,
-    Star,
-    Triangle,
+    Circle = 0,
+    Square = 1,
+    Star = 2,
+    Triangle = 3,
 }
 
 /// Single decoration on a pyramid face with barycentric coordinates relative to the triangle vertices (top, corner1, corner2)
@@ -17,6 +26,8 @@ pub enum DecorationShape {
 pub struct Decoration {
     pub barycentric: Vec3,
     pub size: f32,
+    /// In-plane rotation in radians (0 unless the decoration jitter is enabled)
+    pub rotation: f32,
 }
 
 /// Set of decorations for a pyramid face, which all share same shape and color
@@ -27,6 +38,15 @@ pub struct DecorationSet {
     pub decorations: Vec<Decoration>,
 }
 
+/// Frame currently highlighted by the best door hint, with the material to restore on it
+#[derive(Resource, Default)]
+pub struct BestDoorHint {
+    pub door_index: Option<usize>,
+    pub original_material: Option<Handle<StandardMaterial>>,
+    // Shared by the frames of the round (they all use the same base material)
+    pub hint_material: Option<Handle<StandardMaterial>>,
+}
+
 /// The current winning doors and animation state
 #[derive(Resource, Default)]
 pub struct DoorWinEntities {
@@ -37,12 +57,124 @@ pub struct DoorWinEntities {
     
     // Animation timing
     pub animation_start_time: Option<Duration>,
+    /// Logic frame (`FrameCounterResource`) of `animation_start_time`
+    pub animation_start_frame: u64,
+}
+
+impl DoorWinEntities {
+    /// Starts the door animation at the current time and logic frame.
+    pub fn start_animation(&mut self, now: Duration, frame_number: u64) {
+        self.animation_start_time = Some(now);
+        self.animation_start_frame = frame_number;
+    }
+
+    /// Seconds since the door animation started, `None` when it is not running. With `deterministic`
+    /// they are counted in logic frames, so the animation advances one tick per frame at any frame rate.
+    pub fn animation_elapsed(&self, now: Duration, frame_number: u64, deterministic: bool) -> Option<f32> {
+        let start_time = self.animation_start_time?;
+        Some(if deterministic {
+            frame_number.saturating_sub(self.animation_start_frame) as f32 / REFRESH_RATE_HZ as f32
+        } else {
+            (now - start_time).as_secs_f32()
+        })
+    }
+}
+
+/// Pyramid face metadata, kept to regenerate its decorations without respawning the pyramid
+/// and for the per-face queries (hints, visibility counts, face selection).
+/// Corners and normal are in world space at spawn, the face `Transform` carries the rotation since.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct PyramidFace {
+    pub index: usize,
+    pub color: Color,
+    pub top_left: Vec3,
+    pub bottom_left: Vec3,
+    pub bottom_right: Vec3,
+    pub top_right: Vec3,
+    // Points into the pyramid whatever the winding of the corners (see `inward_face_normal`)
+    pub normal: Vec3,
+}
+
+impl PyramidFace {
+    /// Center of the face in world space, for the given face transform
+    pub fn world_center(&self, transform: &Transform) -> Vec3 {
+        let corners = self.top_left + self.bottom_left + self.bottom_right + self.top_right;
+        transform.transform_point(corners / 4.0)
+    }
+
+    /// Normal pointing out of the pyramid, towards the camera orbit, the side the decorations sit on
+    pub fn outward_normal(&self) -> Vec3 {
+        -self.normal
+    }
+
+    /// Outward normal of the face in world space, for the given face transform
+    pub fn world_normal(&self, transform: &Transform) -> Vec3 {
+        transform.rotation * self.outward_normal()
+    }
+}
+
+/// Decoration spawned as a child of a pyramid face
+#[derive(Component)]
+pub struct FaceDecoration;
+
+/// Fixation cross overlay (persists across resets, unlike `UIEntity`)
+#[derive(Component)]
+pub struct FixationCross;
+
+/// One of the two bars of the fixation cross
+#[derive(Component)]
+pub struct FixationBar {
+    pub horizontal: bool,
+}
+
+/// Procedural textures for the wooden base, generated once in `setup_environment`
+#[derive(Resource)]
+pub struct BaseTextures {
+    pub wood_grain: Handle<Image>,
+    pub checker: Handle<Image>,
+}
+
+impl BaseTextures {
+    /// Texture handle for the requested base texture (`None` keeps the flat color)
+    pub fn get(&self, texture: BaseTexture) -> Option<Handle<Image>> {
+        match texture {
+            BaseTexture::SolidColor => None,
+            BaseTexture::WoodGrain => Some(self.wood_grain.clone()),
+            BaseTexture::Checker => Some(self.checker.clone()),
+        }
+    }
 }
 
 /// Resource to track the start time of the current round
 #[derive(Resource, Default)]
 pub struct RoundStartTimestamp(pub Option<Duration>);
 
+/// Aggregates over the rounds of the session, kept across resets unlike the game structure.
+/// Practice rounds are left out.
+#[derive(Resource, Default, Debug)]
+pub struct SessionStats {
+    pub wins: u32,
+    /// Rounds left by a reset before being won
+    pub aborted: u32,
+    pub checks: u32,
+    /// Sum of the target door alignment of every check
+    pub alignment_sum: f32,
+    /// Rounds won in a row, restarted by an aborted round
+    pub win_streak: u32,
+    pub best_win_streak: u32,
+}
+
+impl SessionStats {
+    /// Mean target door alignment of the checks, 0.0 before the first one.
+    pub fn mean_alignment(&self) -> f32 {
+        if self.checks == 0 {
+            0.0
+        } else {
+            self.alignment_sum / self.checks as f32
+        }
+    }
+}
+
 /// Pyramid component
 #[derive(Component)]
 pub struct Pyramid;
@@ -71,12 +203,25 @@ pub struct UIEntity;
 #[derive(Component)]
 pub struct PersistentCamera;
 
+/// A component that marks the curved backdrop behind the pyramid
+#[derive(Component)]
+pub struct Backdrop;
+
 /// Component to mark the base frame (wooden panel with hole)
 #[derive(Component)]
 pub struct BaseFrame {
     pub door_index: usize,
+    /// Center of the hole in the frame's local space (the mesh vertices are in the unrotated world space)
+    pub center: Vec3,
 }
 
+/// Component to mark the top lid of the base
+#[derive(Component)]
+pub struct BaseLid;
+
+/// Query filter for the top-level entities of the wooden base (the lights and glows are children of the frames)
+pub type BaseEntityFilter = Or<(With<BaseFrame>, With<BaseDoor>, With<BaseLid>)>;
+
 /// Component to mark the base door (pentagon that covers the hole)
 #[derive(Component)]
 pub struct BaseDoor {
@@ -85,9 +230,27 @@ pub struct BaseDoor {
     pub is_open: bool,
 }
 
+/// Panel closing a base hole, spawned as a child of its `BaseDoor` unless the `DoorOpenStyle` is LightOnly.
+/// Vectors are in the door's local space, the world space of the unrotated base like the frame mesh.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct DoorLeaf {
+    pub door_index: usize,
+    /// Middle of the left edge of the hole, the swing pivot
+    pub hinge: Vec3,
+    /// Out of the base, towards the camera orbit
+    pub outward: Vec3,
+    /// Along the hole, from the hinge towards the opposite edge
+    pub right: Vec3,
+    /// Radius of the pentagonal hole
+    pub radius: f32,
+}
+
 // Component of the UI bar showing the score with lights
 #[derive(Component)]
 pub struct ScoreBarUI;
 // Component marking the fill bar inside the ScoreBarUI
 #[derive(Component)]
 pub struct ScoreBarFill;
+// Component of one segment of a discrete ScoreBarUI, with its index along the fill direction
+#[derive(Component)]
+pub struct ScoreBarSegment(pub u32);
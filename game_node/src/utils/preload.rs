@@ -0,0 +1,54 @@
+//! Preloading of the shared scene assets, so the first round does not build them while it is revealed.
+//!
+//! Fills the `AssetCache` at startup with the decoration meshes of every shape at the default sizes and
+//! with the materials of the default configuration. The frame, lid and pyramid meshes are built in world
+//! space from the round's orientation and size, they cannot be prepared before the round is known.
+use bevy::prelude::*;
+use shared::constants::pyramid_constants::{
+    BASE_COLOR, BASE_TEXTURE, DECORATION_STAR_INNER_RATIO, DECORATION_STAR_POINTS, PYRAMID_COLORS,
+    PYRAMID_DECORATIONS_SIZE,
+};
+use shared::BaseTexture;
+
+use crate::utils::asset_cache::{MaterialKind, SceneAssets};
+use crate::utils::objects::{BaseTextures, DecorationShape};
+use crate::utils::setup::setup_environment;
+
+pub struct PreloadPlugin;
+
+impl Plugin for PreloadPlugin {
+    fn build(&self, app: &mut App) {
+        // After setup_environment, which creates the base textures
+        app.add_systems(Startup, preload_scene_assets.after(setup_environment));
+    }
+}
+
+/// Builds the cached assets a round with the default configuration uses.
+/// Cached entries unused by the first round are evicted at the following reset.
+fn preload_scene_assets(mut assets: SceneAssets, base_textures: Option<Res<BaseTextures>>) {
+    for shape in [
+        DecorationShape::Circle,
+        DecorationShape::Square,
+        DecorationShape::Star,
+        DecorationShape::Triangle,
+    ] {
+        for size in PYRAMID_DECORATIONS_SIZE {
+            assets.decoration_mesh(shape, size, DECORATION_STAR_POINTS, DECORATION_STAR_INNER_RATIO);
+        }
+    }
+
+    let base_color = Color::srgba(BASE_COLOR[0], BASE_COLOR[1], BASE_COLOR[2], BASE_COLOR[3]);
+    let base_texture = base_textures.and_then(|textures| textures.get(BaseTexture::from_u32(BASE_TEXTURE)));
+    assets.material(MaterialKind::BaseFrame, base_color, base_texture.clone());
+    assets.material(MaterialKind::BaseLid, base_color, base_texture);
+    assets.material(MaterialKind::PyramidTop, Color::WHITE, None);
+    for [r, g, b, a] in PYRAMID_COLORS {
+        assets.material(MaterialKind::PyramidFace, Color::srgba(r, g, b, a), None);
+    }
+
+    debug!(
+        "Preloaded scene assets: {} meshes, {} materials",
+        assets.meshes.len(),
+        assets.materials.len()
+    );
+}
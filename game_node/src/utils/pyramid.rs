@@ -1,16 +1,48 @@
 //! Logic for spawning the pyramid base with interactive doors.
 
 use crate::utils::objects::{
-    BaseDoor, BaseFrame, Decoration, DecorationSet, DecorationShape, GameEntity, HoleEmissive,
-    HoleLight, Pyramid, RotableComponent,
+    BaseDoor, BaseFrame, BaseLid, Decoration, DoorLeaf, DecorationSet, DecorationShape, DecorationStyle, FaceDecoration,
+    GameEntity, HoleEmissive, HoleLight, PlacedDecorations, Pyramid, PyramidFace, RotableComponent,
 };
+use crate::utils::asset_cache::{MaterialKind, SceneAssets};
+use crate::utils::mesh_validation::debug_assert_mesh_valid;
 use bevy::prelude::*;
 use shared::constants::{object_constants::GROUND_Y, pyramid_constants::*};
+use shared::constants::decoration_layout::effective_edge_margin;
+use shared::{DecorationPlacement, DoorOpenStyle};
 
 use rand::{Rng, RngCore};
 use rand::SeedableRng;
 use rand_chacha::ChaCha8Rng;
 
+// ChaCha streams of the face seed, one per decoration property, so changing one parameter does not
+// shift the draws of the others (e.g. a different count keeps the shapes and colors of every face).
+// The placement uses the default stream 0.
+/// Stream of the decoration jitter
+const DECORATION_JITTER_STREAM: u64 = 1;
+/// Stream of the shape of each face triangle
+const DECORATION_SHAPE_STREAM: u64 = 2;
+/// Stream of the color of each face triangle
+const DECORATION_COLOR_STREAM: u64 = 3;
+/// Stream of the random start yaw of the round
+const START_YAW_STREAM: u64 = 4;
+
+/// RNG of one decoration property, from the face seed and the property stream.
+fn decoration_rng(seed: u64, stream: u64) -> ChaCha8Rng {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    rng.set_stream(stream);
+    rng
+}
+
+/// Random start yaw in [0, TAU) radians, from the first face seed and the round index:
+/// every round of a session starts from another viewpoint, and a replayed session from the same ones.
+pub fn random_start_yaw(seed: u64, round_index: u32) -> f32 {
+    // Spread consecutive round indices over the whole seed space
+    let round_seed = u64::from(round_index).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    let mut rng = decoration_rng(seed ^ round_seed, START_YAW_STREAM);
+    rng.random_range(0.0..std::f32::consts::TAU)
+}
+
 /// Creates a pentagon mesh for the hole emissive effect
 fn create_pentagon_mesh(
     center: Vec3,
@@ -63,26 +95,34 @@ fn create_pentagon_mesh(
     mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals_vec);
     mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
     mesh.insert_indices(bevy::mesh::Indices::U32(indices));
+    debug_assert_mesh_valid(&mesh, "pentagon");
 
     mesh
 }
 
 /// Spawns the wooden base with holes for the pyramid.
 /// Returns `(Option<Entity>, Option<Entity>)` = (winning_light, winning_emissive) for the target door.
+/// Also called alone by the rebuild base command, with the current rotation of the pyramid.
+#[allow(clippy::too_many_arguments)]
 pub fn spawn_pyramid_base(
     commands: &mut Commands,
-    meshes: &mut ResMut<Assets<Mesh>>,
-    materials: &mut ResMut<Assets<StandardMaterial>>,
+    assets: &mut SceneAssets,
     p_start_orientation_rad: f32, // Replaced GameState
+    nr_sides: usize,              // Number of sides (and doors), a valid door count
     target_door: usize,           // Target door index for winning door entities
+    depth_offset: f32,            // Anti z-fighting inset of the emissive pentagon
+    base_texture: Option<Handle<Image>>, // Optional texture for the frames and lid
+    rotation: Quat,               // Rotation of the rotable entities (identity at round start)
+    door_open_style: DoorOpenStyle, // LightOnly leaves the holes open, the others close them with a door leaf
 ) -> (Option<Entity>, Option<Entity>) {
     let base_radius = BASE_RADIUS;
-    let angle_increment = std::f32::consts::TAU / BASE_NR_SIDES as f32;
+    let base_color = Color::srgba(BASE_COLOR[0], BASE_COLOR[1], BASE_COLOR[2], BASE_COLOR[3]);
+    let angle_increment = std::f32::consts::TAU / nr_sides as f32;
 
     let mut winning_light: Option<Entity> = None;
     let mut winning_emissive: Option<Entity> = None;
 
-    for i in 0..BASE_NR_SIDES {
+    for i in 0..nr_sides {
         let angle1 =
             i as f32 * angle_increment + p_start_orientation_rad + std::f32::consts::PI / 2.0;
         let angle2 =
@@ -118,7 +158,7 @@ pub fn spawn_pyramid_base(
         let light_pos = center;
 
         // Create emissive pentagon mesh - offset center slightly inward to prevent z-fighting
-        let pentagon_center_inset = center + normal * 0.01; // Slightly inward from frame surface
+        let pentagon_center_inset = center + normal * depth_offset; // Slightly inward from frame surface
         let pentagon_mesh = create_pentagon_mesh(
             pentagon_center_inset,
             pentagon_radius,
@@ -132,15 +172,10 @@ pub fn spawn_pyramid_base(
 
         let frame_id = commands
             .spawn((
-                Mesh3d(meshes.add(frame_mesh)),
-                MeshMaterial3d(materials.add(StandardMaterial {
-                    base_color: Color::srgba( BASE_COLOR[0], BASE_COLOR[1], BASE_COLOR[2], BASE_COLOR[3]), 
-                    cull_mode: None,
-                    double_sided: true,
-                    ..default()
-                })),
-                Transform::default(), // Frame sits at (0,0,0) or world origin
-                BaseFrame { door_index: i },
+                Mesh3d(assets.round_mesh(frame_mesh)),
+                MeshMaterial3d(assets.material(MaterialKind::BaseFrame, base_color, base_texture.clone())),
+                Transform::from_rotation(rotation), // Frame sits at (0,0,0) or world origin
+                BaseFrame { door_index: i, center },
                 GameEntity,
                 RotableComponent,
             ))
@@ -148,8 +183,9 @@ pub fn spawn_pyramid_base(
 
         // Spawn emissive pentagon glow as child of frame
         let emissive_id = commands.spawn((
-            Mesh3d(meshes.add(pentagon_mesh)),
-            MeshMaterial3d(materials.add(StandardMaterial {
+            Mesh3d(assets.round_mesh(pentagon_mesh)),
+            // Not shared, the door animation modifies the emission of the winning door
+            MeshMaterial3d(assets.round_material(StandardMaterial {
                 emissive: LinearRgba::new(0.0, 0.0, 0.0, 1.0), // Start with no emission
                 cull_mode: None,
                 ..default()
@@ -186,8 +222,8 @@ pub fn spawn_pyramid_base(
         }
 
         // Spawn the door entity
-        commands.spawn((
-            Transform::default(),
+        let door_id = commands.spawn((
+            Transform::from_rotation(rotation),
             BaseDoor {
                 door_index: i,
                 normal: -normal,
@@ -195,24 +231,41 @@ pub fn spawn_pyramid_base(
             },
             GameEntity,
             RotableComponent,
-        ));
+            // Propagated to the door leaf
+            Visibility::default(),
+        )).id();
+
+        // Door leaf in the hole, in front of the glow, opened by the reveal animation
+        if door_open_style != DoorOpenStyle::LightOnly {
+            let leaf_mesh = create_pentagon_mesh(center, pentagon_radius, local_right, local_up, -normal);
+            commands.spawn((
+                Mesh3d(assets.round_mesh(leaf_mesh)),
+                MeshMaterial3d(assets.material(MaterialKind::BaseFrame, base_color, base_texture.clone())),
+                Transform::default(), // Closed, the mesh vertices are in the door's local space
+                DoorLeaf {
+                    door_index: i,
+                    hinge: center - local_right * pentagon_radius,
+                    outward: -normal,
+                    right: local_right,
+                    radius: pentagon_radius,
+                },
+                GameEntity,
+                ChildOf(door_id),
+            ));
+        }
     }
 
     // Spawn the top lid of the base
     let top_y = GROUND_Y + BASE_HEIGHT;
 
     // Create a polygon mesh matching the base's shape
-    let top_lid_mesh = create_top_lid_mesh(base_radius, BASE_NR_SIDES, p_start_orientation_rad);
+    let top_lid_mesh = create_top_lid_mesh(base_radius, nr_sides, p_start_orientation_rad);
 
     commands.spawn((
-        Mesh3d(meshes.add(top_lid_mesh)),
-        MeshMaterial3d(materials.add(StandardMaterial {
-            base_color: Color::srgba( BASE_COLOR[0], BASE_COLOR[1], BASE_COLOR[2], BASE_COLOR[3]), 
-            cull_mode: None,
-            double_sided: false,
-            ..default()
-        })),
-        Transform::from_xyz(0.0, top_y, 0.0),
+        Mesh3d(assets.round_mesh(top_lid_mesh)),
+        MeshMaterial3d(assets.material(MaterialKind::BaseLid, base_color, base_texture)),
+        Transform::from_xyz(0.0, top_y, 0.0).with_rotation(rotation),
+        BaseLid,
         RotableComponent,
         GameEntity,
     ));
@@ -259,6 +312,7 @@ fn create_top_lid_mesh(radius: f32, sides: usize, start_orientation: f32) -> Mes
     mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
     mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
     mesh.insert_indices(bevy::mesh::Indices::U32(indices));
+    debug_assert_mesh_valid(&mesh, "top_lid");
 
     mesh
 }
@@ -330,6 +384,17 @@ fn create_frame_with_hole(
         normals.push(normal.to_array());
     }
 
+    // Planar UVs in the local (right, up) frame: the rectangle maps to [0,1], the hole keeps its relative position
+    let uvs: Vec<[f32; 2]> = positions
+        .iter()
+        .map(|position| {
+            let offset = Vec3::from_array(*position) - bottom_left;
+            let u = offset.dot(local_right) / width;
+            let v = 1.0 - offset.dot(local_up) / height;
+            [u, v]
+        })
+        .collect();
+
     // Create triangles connecting the outer rectangle to the inner pentagon
     let mut indices = Vec::new();
 
@@ -350,17 +415,19 @@ fn create_frame_with_hole(
 
     mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
     mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
     mesh.insert_indices(bevy::mesh::Indices::U32(indices));
+    debug_assert_mesh_valid(&mesh, "frame_with_hole");
 
     (mesh, normal, local_right, local_up, center, pentagon_radius)
 }
 
 /// Spawns a triangular prism.
-/// Returns `(Option<Entity>, Option<Entity>)` = (winning_light, winning_emissive) for the target door.
+/// Returns `(Option<Entity>, Option<Entity>, placed)` = (winning_light, winning_emissive) for the target door
+/// and the decoration style and placed count of each face triangle.
 pub fn spawn_pyramid(
     commands: &mut Commands,
-    meshes: &mut ResMut<Assets<Mesh>>,
-    materials: &mut ResMut<Assets<StandardMaterial>>,
+    assets: &mut SceneAssets,
     decoration_seeds: [u64; 3],
     p_radius: f32,
     p_height: f32,
@@ -368,39 +435,36 @@ pub fn spawn_pyramid(
     p_colors: [Color; 3],
     decoration_counts: [u32; 3],
     decoration_sizes: [f32; 3],
+    decoration_emissive: [f32; 3],
+    star_points: u32,
+    star_inner_ratio: f32,
+    depth_offset: f32,
+    decoration_jitter: bool,
+    placement: DecorationPlacement,
+    edge_margin_factor: f32,
+    base_texture: Option<Handle<Image>>,
+    nr_sides: usize,
     target_door: usize,
-) -> (Option<Entity>, Option<Entity>) {
+    door_open_style: DoorOpenStyle,
+    start_rotation: Quat,
+) -> (Option<Entity>, Option<Entity>, [[PlacedDecorations; 2]; 3]) {
     let height_y = p_height;
 
-    // Build the symmetric triangular vertices for the BASE.
-    let mut base_corners: [Vec3; 3] = [Vec3::ZERO; 3];
-    // Build the symmetric triangular vertices for the TOP.
-    let mut top_corners: [Vec3; 3] = [Vec3::ZERO; 3];
-
-    let mut prev_xz = Vec2::new(
-        p_radius * p_orientation_rad.cos(),
-        p_radius * p_orientation_rad.sin(),
-    );
-
-    // Set first corners
-    base_corners[0] = Vec3::new(prev_xz.x, GROUND_Y + BASE_HEIGHT, prev_xz.y);
-    top_corners[0] = Vec3::new(prev_xz.x, height_y, prev_xz.y);
-
-    // Compute constants for rotation
-    let pyramid_angle_increment_cos: f32 = PYRAMID_ANGLE_INCREMENT_RAD.cos();
-    let pyramid_angle_increment_sin: f32 = PYRAMID_ANGLE_INCREMENT_RAD.sin();
-
-    for i in 1..3 {
-        // Rotate
-        let x = prev_xz.x * pyramid_angle_increment_cos - prev_xz.y * pyramid_angle_increment_sin;
-        let z = prev_xz.y * pyramid_angle_increment_cos + prev_xz.x * pyramid_angle_increment_sin;
-        prev_xz = Vec2::new(x, z);
-
-        // Save vertices
-        base_corners[i] = Vec3::new(prev_xz.x, GROUND_Y + BASE_HEIGHT, prev_xz.y);
-        top_corners[i] = Vec3::new(prev_xz.x, height_y, prev_xz.y);
+    // The orbit and alignment math assume the pyramid is centered on the origin
+    let mut corners_xz = pyramid_corners_xz(p_radius, p_orientation_rad);
+    let centroid = corners_centroid(corners_xz);
+    if centroid.length() > PYRAMID_CENTER_EPSILON {
+        warn!(
+            "Pyramid centroid {:?} is off the origin (radius={}, orientation={}), recentering it",
+            centroid, p_radius, p_orientation_rad
+        );
+        corners_xz = corners_xz.map(|corner| corner - centroid);
     }
 
+    // Build the symmetric triangular vertices for the BASE and the TOP.
+    let base_corners = corners_xz.map(|corner| Vec3::new(corner.x, GROUND_Y + BASE_HEIGHT, corner.y));
+    let top_corners = corners_xz.map(|corner| Vec3::new(corner.x, height_y, corner.y));
+
     // Spawn Top Cap
 
     // Create mesh for the top triangle
@@ -421,62 +485,19 @@ pub fn spawn_pyramid(
         Mesh::ATTRIBUTE_UV_0,
         vec![[0.5, 0.0], [0.0, 1.0], [1.0, 1.0]],
     );
+    debug_assert_mesh_valid(&top_mesh, "pyramid_top");
 
     commands.spawn((
-        Mesh3d(meshes.add(top_mesh)),
-        MeshMaterial3d(materials.add(StandardMaterial {
-            base_color: Color::WHITE,
-            cull_mode: None,
-            double_sided: true,
-            ..default()
-        })),
-        Transform::default(),
+        Mesh3d(assets.round_mesh(top_mesh)),
+        MeshMaterial3d(assets.material(MaterialKind::PyramidTop, Color::WHITE, None)),
+        Transform::from_rotation(start_rotation),
         Pyramid,
         RotableComponent,
         GameEntity,
     ));
 
-    // Generate Decoration Sets
-
-    let mut dec_sets: Vec<Option<DecorationSet>> = Vec::new();
-
-    // Indices for the loop below to generate sets for Face 0 and Face 1
-    // We treat the rectangle as two triangles:
-    // Tri A: (TopLeft, BaseLeft, BaseRight)
-    // Tri B: (TopLeft, BaseRight, TopRight)
-    for i in 0..3 {
-        // Create a fresh RNG from the per-face seed so identical seeds produce identical aesthetics
-        let mut face_rng = ChaCha8Rng::seed_from_u64(decoration_seeds[i]);
-        // Generate for first 2 faces
-        let next = (i + 1) % 3;
-
-        let tl = top_corners[i];
-        let tr = top_corners[next];
-        let bl = base_corners[i];
-        let br = base_corners[next];
-
-        // Set A (Bottom-Left Triangle)
-        dec_sets.push(Some(generate_decoration_set(
-            &mut face_rng,
-            tl,
-            bl,
-            br,
-            decoration_counts[i],
-            decoration_sizes[i],
-        )));
-
-        // Set B (Top-Right Triangle)
-        dec_sets.push(Some(generate_decoration_set(
-            &mut face_rng,
-            tl,
-            br,
-            tr,
-            decoration_counts[i],
-            decoration_sizes[i],
-        )));
-    }
-
     // Spawn the pyramid faces
+    let mut placed_decorations = [[((DecorationShape::Circle, Color::WHITE), 0); 2]; 3];
     for i in 0..3 {
         let next = (i + 1) % 3;
 
@@ -503,9 +524,7 @@ pub fn spawn_pyramid(
         let indices = vec![0, 1, 2, 0, 2, 3];
 
         // Calculate Normal (same for the whole flat face)
-        let v1 = bl - tl;
-        let v2 = tr - tl;
-        let normal = v1.cross(v2).normalize();
+        let normal = inward_face_normal(tl, bl, tr);
         let normals = vec![normal.to_array(); 4];
 
         mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
@@ -520,70 +539,245 @@ pub fn spawn_pyramid(
             ],
         );
         mesh.insert_indices(bevy::mesh::Indices::U32(indices));
-
+        debug_assert_mesh_valid(&mesh, "pyramid_face");
+
+        let face = PyramidFace {
+            index: i,
+            color: p_colors[i],
+            top_left: tl,
+            bottom_left: bl,
+            bottom_right: br,
+            top_right: tr,
+            normal,
+        };
         let face_entity = commands
             .spawn((
-                Mesh3d(meshes.add(mesh)),
-                MeshMaterial3d(materials.add(StandardMaterial {
-                    base_color: p_colors[i],
-                    cull_mode: None,
-                    double_sided: false,
-                    ..default()
-                })),
-                Transform::default(),
+                Mesh3d(assets.round_mesh(mesh)),
+                MeshMaterial3d(assets.material(MaterialKind::PyramidFace, p_colors[i], None)),
+                Transform::from_rotation(start_rotation),
                 Pyramid,
+                face,
                 RotableComponent,
                 GameEntity,
             ))
             .id();
 
-        // Apply Set A to the first virtual triangle (TL, BL, BR)
-        if let Some(ref set_a) = dec_sets[i * 2] {
-            spawn_decorations_from_set(
-                commands,
-                meshes,
-                materials,
-                face_entity,
-                set_a,
-                tl,
-                bl,
-                br,
-                normal,
-            );
-        }
-
-        // Apply Set B to the second virtual triangle (TL, BR, TR)
-        if let Some(ref set_b) = dec_sets[i * 2 + 1] {
-            spawn_decorations_from_set(
-                commands,
-                meshes,
-                materials,
-                face_entity,
-                set_b,
-                tl,
-                br,
-                tr,
-                normal,
-            );
-        }
+        placed_decorations[i] = spawn_face_decorations(
+            commands,
+            assets,
+            face_entity,
+            &face,
+            &FaceDecorationParams {
+                seed: decoration_seeds[i],
+                count: decoration_counts[i],
+                size: decoration_sizes[i],
+                emissive: decoration_emissive[i],
+                star_points,
+                star_inner_ratio,
+                depth_offset,
+                jitter: decoration_jitter,
+                placement,
+                edge_margin_factor,
+            },
+        );
     }
 
     // Spawn the base and capture winning door entities
-    let (winning_light, winning_emissive) = spawn_pyramid_base(commands, meshes, materials, p_orientation_rad, target_door);
+    let (winning_light, winning_emissive) = spawn_pyramid_base(
+        commands,
+        assets,
+        p_orientation_rad,
+        nr_sides,
+        target_door,
+        depth_offset,
+        base_texture,
+        start_rotation,
+        door_open_style,
+    );
     // Max intensity not vital here or pass it in
 
-    (winning_light, winning_emissive)
+    (winning_light, winning_emissive, placed_decorations)
+}
+
+/// XZ corners of the pyramid, the first at `p_orientation_rad` on the circle of radius `p_radius`
+/// and each next one rotated by PYRAMID_ANGLE_INCREMENT_RAD.
+pub fn pyramid_corners_xz(p_radius: f32, p_orientation_rad: f32) -> [Vec2; 3] {
+    let mut corners = [Vec2::ZERO; 3];
+    corners[0] = Vec2::new(
+        p_radius * p_orientation_rad.cos(),
+        p_radius * p_orientation_rad.sin(),
+    );
+
+    // Compute constants for rotation
+    let pyramid_angle_increment_cos: f32 = PYRAMID_ANGLE_INCREMENT_RAD.cos();
+    let pyramid_angle_increment_sin: f32 = PYRAMID_ANGLE_INCREMENT_RAD.sin();
+
+    for i in 1..3 {
+        let prev = corners[i - 1];
+        corners[i] = Vec2::new(
+            prev.x * pyramid_angle_increment_cos - prev.y * pyramid_angle_increment_sin,
+            prev.y * pyramid_angle_increment_cos + prev.x * pyramid_angle_increment_sin,
+        );
+    }
+    corners
+}
+
+/// Centroid of the pyramid corners, the origin for a centered pyramid
+pub fn corners_centroid(corners: [Vec2; 3]) -> Vec2 {
+    corners.iter().sum::<Vec2>() / 3.0
+}
+
+/// Unit normal of a side face, pointing into the pyramid whatever the winding of its corners:
+/// the pyramid is centered on the Y axis, so a normal pointing away from it is flipped.
+pub fn inward_face_normal(top_left: Vec3, bottom_left: Vec3, top_right: Vec3) -> Vec3 {
+    let normal = (bottom_left - top_left).cross(top_right - top_left).normalize();
+    // Middle of the face in the XZ plane, the side corners are vertically aligned
+    let center_xz = Vec3::new(bottom_left.x + top_right.x, 0.0, bottom_left.z + top_right.z);
+    if normal.dot(center_xz) > 0.0 { -normal } else { normal }
+}
+
+/// Upper bound of the `GameEntity`s spawned for one round: a frame, hole glow, hole light and door per side
+/// (and a door leaf unless `door_open_style` is `LightOnly`), the lid, the pyramid top and its faces, and at
+/// most `count` decorations per face triangle.
+pub fn round_entity_budget(nr_sides: usize, decorations_count: [u32; 3], door_open_style: DoorOpenStyle) -> usize {
+    let decorations: usize = decorations_count.iter().map(|&count| 2 * count as usize).sum();
+    let per_side = if door_open_style == DoorOpenStyle::LightOnly { 4 } else { 5 };
+    per_side * nr_sides + 1 + 1 + 3 + decorations
+}
+
+/// Decoration settings of one pyramid face.
+#[derive(Clone, Copy, Debug)]
+pub struct FaceDecorationParams {
+    pub seed: u64,
+    pub count: u32,
+    pub size: f32,
+    pub emissive: f32,
+    pub star_points: u32,
+    pub star_inner_ratio: f32,
+    pub depth_offset: f32,
+    pub jitter: bool,
+    pub placement: DecorationPlacement,
+    pub edge_margin_factor: f32,
+}
+
+/// Generates and spawns the decorations of one pyramid face from its seed, returning the shape and color
+/// picked for each of its two triangles and the number of decorations placed on it.
+/// Used at round start and by the re-roll command, which regenerates decorations without touching the geometry.
+pub fn spawn_face_decorations(
+    commands: &mut Commands,
+    assets: &mut SceneAssets,
+    face_entity: Entity,
+    face: &PyramidFace,
+    params: &FaceDecorationParams,
+) -> [PlacedDecorations; 2] {
+    // Generate both sets before spawning, so the RNG sequence does not depend on spawning
+    let sets = generate_face_decoration_sets(
+        face,
+        params.seed,
+        params.count,
+        params.size,
+        params.jitter,
+        params.placement,
+        params.edge_margin_factor,
+    );
+
+    for (set, (top, corner1, corner2)) in sets.iter().zip(face_triangles(face)) {
+        spawn_decorations_from_set(
+            commands,
+            assets,
+            face_entity,
+            set,
+            top,
+            corner1,
+            corner2,
+            face.outward_normal(),
+            params.star_points,
+            params.star_inner_ratio,
+            params.depth_offset,
+            params.emissive,
+        );
+    }
+
+    sets.map(|set| ((set.shape, set.color), set.decorations.len() as u32))
+}
+
+/// The two triangles of a face rectangle:
+/// Tri A: (TopLeft, BaseLeft, BaseRight)
+/// Tri B: (TopLeft, BaseRight, TopRight)
+fn face_triangles(face: &PyramidFace) -> [(Vec3, Vec3, Vec3); 2] {
+    [
+        (face.top_left, face.bottom_left, face.bottom_right),
+        (face.top_left, face.bottom_right, face.top_right),
+    ]
+}
+
+/// Generates the decoration sets of the two triangles of a face from its seed.
+/// Fresh RNGs are created from the seed so identical seeds produce identical aesthetics.
+fn generate_face_decoration_sets(
+    face: &PyramidFace,
+    seed: u64,
+    count: u32,
+    size: f32,
+    jitter: bool,
+    placement: DecorationPlacement,
+    edge_margin_factor: f32,
+) -> [DecorationSet; 2] {
+    let mut placement_rng = decoration_rng(seed, 0);
+    let mut shape_rng = decoration_rng(seed, DECORATION_SHAPE_STREAM);
+    let mut color_rng = decoration_rng(seed, DECORATION_COLOR_STREAM);
+    let mut sets = face_triangles(face).map(|(top, corner1, corner2)| {
+        // Same shape and color for all decorations of a triangle
+        let shape = match shape_rng.next_u64() % 4 {
+            0 => DecorationShape::Circle,
+            1 => DecorationShape::Square,
+            2 => DecorationShape::Star,
+            _ => DecorationShape::Triangle,
+        };
+        let color = Color::srgb(
+            color_rng.random_range(0.2..0.22),
+            color_rng.random_range(0.2..0.22),
+            color_rng.random_range(0.2..0.22),
+        );
+        let edge_margin = size * effective_edge_margin(edge_margin_factor, shape_extent(shape));
+        match placement {
+            DecorationPlacement::Poisson => generate_decoration_set(
+                &mut placement_rng,
+                (shape, color),
+                top,
+                corner1,
+                corner2,
+                count,
+                size,
+                edge_margin,
+            ),
+            DecorationPlacement::Grid => {
+                generate_grid_decoration_set((shape, color), top, corner1, corner2, count, size, edge_margin)
+            }
+        }
+    });
+
+    // Enabling the jitter keeps the layout and only turns the decorations
+    if jitter {
+        let mut jitter_rng = decoration_rng(seed, DECORATION_JITTER_STREAM);
+        for decoration in sets.iter_mut().flat_map(|set| set.decorations.iter_mut()) {
+            decoration.rotation = jitter_rng.random_range(0.0..std::f32::consts::TAU);
+        }
+    }
+    sets
 }
 
 /// Generates a decoration set for a pyramid face using Poisson-like sampling.
 /// Decorations are stored using barycentric coordinates relative to the triangle vertices.
+#[allow(clippy::too_many_arguments)]
 fn generate_decoration_set(
     rng: &mut ChaCha8Rng,
+    (shape, color): DecorationStyle,
     top: Vec3,
     corner1: Vec3,
     corner2: Vec3,
     count: u32,
-    size: f32, // New Arg
+    size: f32,
+    edge_margin: f32,
 ) -> DecorationSet {
     // Determine the number of decorations to generate.
     let decoration_count = count as usize;
@@ -601,21 +795,6 @@ fn generate_decoration_set(
     let mut successful_placements = 0;
     let mut total_attempts = 0;
 
-    // Choose a random shape type, which will be the same for all decorations on this face.
-    let shape = match rng.next_u64() % 4 {
-        0 => DecorationShape::Circle,
-        1 => DecorationShape::Square,
-        2 => DecorationShape::Star,
-        _ => DecorationShape::Triangle,
-    };
-
-    // Choose a random vibrant color, which will be the same for all decorations on this face.
-    let color = Color::srgb(
-        rng.random_range(0.2..0.22),
-        rng.random_range(0.2..0.22),
-        rng.random_range(0.2..0.22),
-    );
-
     while successful_placements < decoration_count
         && (total_attempts as usize) < (decoration_count as usize) * MAX_PLACEMENT_ATTEMPTS
     {
@@ -623,35 +802,18 @@ fn generate_decoration_set(
 
         // Generate a random position using barycentric coordinates to ensure the point is inside the triangle.
         let (world_position, is_valid) =
-            sample_point_in_triangle(rng, top, corner1, corner2, size, &decorations_world);
+            sample_point_in_triangle(rng, top, corner1, corner2, size, edge_margin, &decorations_world);
 
         // Skip this attempt if the position overlaps with existing decorations or is too close to the edges.
         if !is_valid {
             continue;
         }
 
-        // Convert world position to barycentric coordinates
-        // world_position = w0*top + w1*corner1 + w2*corner2
-        // where w0 + w1 + w2 = 1
-        let v0 = corner1 - top;
-        let v1 = corner2 - top;
-        let v2 = world_position - top;
-
-        let d00 = v0.dot(v0);
-        let d01 = v0.dot(v1);
-        let d11 = v1.dot(v1);
-        let d20 = v2.dot(v0);
-        let d21 = v2.dot(v1);
-
-        let denom = d00 * d11 - d01 * d01;
-        let w1 = (d11 * d20 - d01 * d21) / denom;
-        let w2 = (d00 * d21 - d01 * d20) / denom;
-        let w0 = 1.0 - w1 - w2;
-
         // Store this decoration with barycentric coordinates
         decorations.push(Decoration {
-            barycentric: Vec3::new(w0, w1, w2),
+            barycentric: barycentric_coordinates(world_position, top, corner1, corner2),
             size,
+            rotation: 0.0,
         });
         decorations_world.push((world_position, size));
         successful_placements += 1;
@@ -664,18 +826,129 @@ fn generate_decoration_set(
     }
 }
 
+/// Generates a decoration set on a regular triangular lattice, with the spacing of the Poisson sampling and
+/// its `edge_margin`. Deterministic: it places the requested count whenever the lattice has room for it, else every
+/// lattice point. The lattice is shifted to the offset fitting the most points in the triangle, and when it
+/// has more points than requested, evenly spread ones are kept.
+fn generate_grid_decoration_set(
+    (shape, color): DecorationStyle,
+    top: Vec3,
+    corner1: Vec3,
+    corner2: Vec3,
+    count: u32,
+    size: f32,
+    edge_margin: f32,
+) -> DecorationSet {
+    // Bounds the lattice for tiny decorations on a large face
+    const MAX_GRID_POINTS: f32 = 250_000.0;
+    // Lattice offsets tried along each axis, as fractions of the spacing and the row height
+    const GRID_OFFSET_STEPS: u32 = 4;
+
+    let spacing = size * DECORATION_SPACING;
+    let row_height = spacing * 3.0_f32.sqrt() / 2.0;
+
+    // Rows parallel to the corner1-corner2 edge, stacked towards the top
+    let axis_x = (corner2 - corner1).normalize_or_zero();
+    let axis_y = (top - corner1 - axis_x * (top - corner1).dot(axis_x)).normalize_or_zero();
+    let x_range = [corner1, corner2, top].map(|v| (v - corner1).dot(axis_x));
+    let x_min = x_range.iter().copied().fold(f32::INFINITY, f32::min);
+    let x_max = x_range.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let y_max = (top - corner1).dot(axis_y);
+
+    let rows = (y_max / row_height).floor() + 1.0;
+    let columns = ((x_max - x_min) / spacing).floor() + 2.0;
+    let lattice_fits = spacing > 0.0 && y_max > 0.0 && rows * columns <= MAX_GRID_POINTS;
+    if count > 0 && !lattice_fits {
+        warn!("No decoration grid for size {} on this face triangle", size);
+    }
+
+    // Lattice points inside the triangle, away from its edges, for an offset of the lattice origin
+    let lattice_at = |offset_x: f32, offset_y: f32| {
+        let mut lattice: Vec<Vec3> = Vec::new();
+        for row in 0..=rows as u32 {
+            // Every other row is shifted by half a spacing, so each point has six neighbors at `spacing`
+            let shift = if row % 2 == 1 { spacing / 2.0 } else { 0.0 };
+            let y = offset_y + row as f32 * row_height;
+            for column in 0..=columns as u32 {
+                let x = x_min + offset_x + shift + column as f32 * spacing;
+                let position = corner1 + axis_x * x + axis_y * y;
+                let barycentric = barycentric_coordinates(position, top, corner1, corner2);
+                if barycentric.min_element() >= 0.0
+                    && point_to_line_segment_distance(position, top, corner1) >= edge_margin
+                    && point_to_line_segment_distance(position, corner1, corner2) >= edge_margin
+                    && point_to_line_segment_distance(position, corner2, top) >= edge_margin
+                {
+                    lattice.push(position);
+                }
+            }
+        }
+        lattice
+    };
+
+    let mut lattice: Vec<Vec3> = Vec::new();
+    if count > 0 && lattice_fits {
+        for step_x in 0..GRID_OFFSET_STEPS {
+            for step_y in 0..GRID_OFFSET_STEPS {
+                let fraction = |step: u32| step as f32 / GRID_OFFSET_STEPS as f32;
+                let candidate = lattice_at(fraction(step_x) * spacing, fraction(step_y) * row_height);
+                // The first offset wins ties, so the layout stays deterministic
+                if candidate.len() > lattice.len() {
+                    lattice = candidate;
+                }
+            }
+        }
+    }
+
+    let placed = (count as usize).min(lattice.len());
+    let decorations = (0..placed)
+        .map(|i| Decoration {
+            barycentric: barycentric_coordinates(lattice[i * lattice.len() / placed], top, corner1, corner2),
+            size,
+            rotation: 0.0,
+        })
+        .collect();
+
+    DecorationSet {
+        shape,
+        color,
+        decorations,
+    }
+}
+
+/// Barycentric coordinates `(w0, w1, w2)` of `point` in the triangle, with point = w0*top + w1*corner1 + w2*corner2
+/// and w0 + w1 + w2 = 1.
+fn barycentric_coordinates(point: Vec3, top: Vec3, corner1: Vec3, corner2: Vec3) -> Vec3 {
+    let v0 = corner1 - top;
+    let v1 = corner2 - top;
+    let v2 = point - top;
+
+    let d00 = v0.dot(v0);
+    let d01 = v0.dot(v1);
+    let d11 = v1.dot(v1);
+    let d20 = v2.dot(v0);
+    let d21 = v2.dot(v1);
+
+    let denom = d00 * d11 - d01 * d01;
+    let w1 = (d11 * d20 - d01 * d21) / denom;
+    let w2 = (d00 * d21 - d01 * d20) / denom;
+    Vec3::new(1.0 - w1 - w2, w1, w2)
+}
+
 /// Spawns decorations from a decoration set onto a face
 /// Reconstructs world positions from barycentric coordinates relative to the given triangle vertices
 fn spawn_decorations_from_set(
     commands: &mut Commands,
-    meshes: &mut ResMut<Assets<Mesh>>,
-    materials: &mut ResMut<Assets<StandardMaterial>>,
+    assets: &mut SceneAssets,
     parent_face: Entity,
     decoration_set: &DecorationSet,
     top: Vec3,
     corner1: Vec3,
     corner2: Vec3,
-    face_normal: Vec3,
+    outward_normal: Vec3,
+    star_points: u32,
+    star_inner_ratio: f32,
+    depth_offset: f32,
+    emissive: f32,
 ) {
     for decoration in &decoration_set.decorations {
         // Reconstruct world position from barycentric coordinates
@@ -683,36 +956,47 @@ fn spawn_decorations_from_set(
             + decoration.barycentric.y * corner1
             + decoration.barycentric.z * corner2;
 
-        let mesh = create_decoration_mesh(decoration_set.shape, decoration.size);
-
-        // Calculate the rotation to align the decoration with the face plane
-        let base_rotation = Quat::from_rotation_x(std::f32::consts::FRAC_PI_2);
-        let normal_rotation = Quat::from_rotation_arc(Vec3::Y, face_normal);
-        let final_rotation = normal_rotation * base_rotation;
+        // Shared by every decoration of the same shape and size
+        let mesh = assets.decoration_mesh(
+            decoration_set.shape,
+            decoration.size,
+            star_points,
+            star_inner_ratio,
+        );
 
-        // Offset slightly away from face surface to prevent z-fighting
-        let offset_position = position - face_normal * 0.01;
+        let transform = decoration_transform(position, outward_normal, decoration.rotation, depth_offset);
 
         // Spawn the decoration as a child of the face
         commands.entity(parent_face).with_children(|parent| {
             parent.spawn((
-                Mesh3d(meshes.add(mesh)),
-                MeshMaterial3d(materials.add(StandardMaterial {
-                    base_color: decoration_set.color,
-                    reflectance: 0.0,
-                    ..default()
-                })),
-                Transform {
-                    translation: offset_position,
-                    rotation: final_rotation,
-                    scale: Vec3::ONE,
-                },
+                Mesh3d(mesh),
+                MeshMaterial3d(assets.material(
+                    MaterialKind::Decoration { emissive: emissive.to_bits() },
+                    decoration_set.color,
+                    None,
+                )),
+                transform,
+                FaceDecoration,
                 GameEntity,
             ));
         });
     }
 }
 
+/// Transform of a decoration at `position` on a face: in the face plane with its front towards `outward_normal`,
+/// and lifted `depth_offset` off the surface on that side to prevent z-fighting.
+fn decoration_transform(position: Vec3, outward_normal: Vec3, rotation: f32, depth_offset: f32) -> Transform {
+    // The decoration meshes face +Z: turn it to -Y, then -Y to the outward normal.
+    // The jitter turns them around their own normal
+    let base_rotation = Quat::from_rotation_x(std::f32::consts::FRAC_PI_2);
+    let normal_rotation = Quat::from_rotation_arc(Vec3::NEG_Y, outward_normal);
+    Transform {
+        translation: position + outward_normal * depth_offset,
+        rotation: normal_rotation * base_rotation * Quat::from_rotation_z(rotation),
+        scale: Vec3::ONE,
+    }
+}
+
 /// Samples a random point inside a triangle using barycentric coordinates, with collision checking against existing decorations
 fn sample_point_in_triangle(
     rng: &mut ChaCha8Rng,
@@ -720,6 +1004,7 @@ fn sample_point_in_triangle(
     v1: Vec3,
     v2: Vec3,
     size: f32,
+    edge_margin: f32,
     existing_decorations: &[(Vec3, f32)],
 ) -> (Vec3, bool) {
     // Generate random barycentric coordinates using the square root method for a uniform distribution
@@ -734,9 +1019,6 @@ fn sample_point_in_triangle(
     // Calculate the 3D position of the point
     let position = v0 * w0 + v1 * w1 + v2 * w2;
 
-    // Set a minimum distance from the edges, proportional to the decoration's size
-    let edge_margin = size * 1.5;
-
     // Check if the point is too close to the triangle's edges.
     let dist_to_edge_01 = point_to_line_segment_distance(position, v0, v1);
     let dist_to_edge_12 = point_to_line_segment_distance(position, v1, v2);
@@ -750,13 +1032,12 @@ fn sample_point_in_triangle(
     }
 
     // Check for overlap with existing decorations (Poisson disk constraint)
-    let min_spacing = size * 2.0; // The minimum distance between decoration centers
-
     for (existing_pos, existing_size) in existing_decorations {
         let distance = position.distance(*existing_pos);
-        let required_distance = (size + existing_size) * 1.2; // Add 20% extra spacing.
+        // DECORATION_SPACING decoration sizes apart (all the decorations of a set share their size)
+        let required_distance = (size + existing_size) / 2.0 * DECORATION_SPACING;
 
-        if distance < required_distance.max(min_spacing) {
+        if distance < required_distance {
             return (position, false);
         }
     }
@@ -781,18 +1062,33 @@ fn point_to_line_segment_distance(point: Vec3, line_start: Vec3, line_end: Vec3)
     point.distance(projection)
 }
 
-/// Creates a mesh for a decoration shape
-fn create_decoration_mesh(shape: DecorationShape, size: f32) -> Mesh {
+/// Distance from the center of a decoration mesh to its farthest vertex, in decoration sizes
+fn shape_extent(shape: DecorationShape) -> f32 {
+    match shape {
+        DecorationShape::Circle | DecorationShape::Star => 1.0,
+        DecorationShape::Square => std::f32::consts::SQRT_2,
+        // Apex at two thirds of the sqrt(3) height
+        DecorationShape::Triangle => 2.0 / 3.0_f32.sqrt(),
+    }
+}
+
+/// Creates a mesh for a decoration shape, centered on the origin
+pub(crate) fn create_decoration_mesh(
+    shape: DecorationShape,
+    size: f32,
+    star_points: u32,
+    star_inner_ratio: f32,
+) -> Mesh {
     match shape {
         DecorationShape::Circle => Circle::new(size).mesh().resolution(16).build(),
         DecorationShape::Square => Rectangle::new(size * 2.0, size * 2.0).mesh().build(),
-        DecorationShape::Star => create_star_mesh(size, 5),
+        DecorationShape::Star => create_star_mesh(size, star_points as usize, star_inner_ratio),
         DecorationShape::Triangle => create_triangle_mesh(size),
     }
 }
 
-/// Creates a star-shaped mesh
-fn create_star_mesh(size: f32, points: usize) -> Mesh {
+/// Creates a star-shaped mesh with `points` outer tips and inner vertices at `size * inner_ratio`
+fn create_star_mesh(size: f32, points: usize, inner_ratio: f32) -> Mesh {
     let mut mesh = Mesh::new(
         bevy::mesh::PrimitiveTopology::TriangleList,
         Default::default(),
@@ -812,7 +1108,7 @@ fn create_star_mesh(size: f32, points: usize) -> Mesh {
     let angle_step = std::f32::consts::TAU / (points * 2) as f32;
     for i in 0..(points * 2) {
         let angle = i as f32 * angle_step;
-        let radius = if i % 2 == 0 { size } else { size * 0.4 };
+        let radius = if i % 2 == 0 { size } else { size * inner_ratio };
         let x = angle.cos() * radius;
         let y = angle.sin() * radius;
 
@@ -831,6 +1127,7 @@ fn create_star_mesh(size: f32, points: usize) -> Mesh {
     mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
     mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
     mesh.insert_indices(bevy::mesh::Indices::U32(indices));
+    debug_assert_mesh_valid(&mesh, "star");
 
     mesh
 }
@@ -855,6 +1152,185 @@ fn create_triangle_mesh(size: f32) -> Mesh {
     mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
     mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
     mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    debug_assert_mesh_valid(&mesh, "triangle");
 
     mesh
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pyramid_stays_centered_on_the_origin() {
+        for radius in [0.1, 0.5, 1.0, PYRAMID_BASE_RADIUS, 5.0, 20.0] {
+            for step in 0..64 {
+                let orientation = step as f32 / 64.0 * std::f32::consts::TAU - std::f32::consts::PI;
+                let centroid = corners_centroid(pyramid_corners_xz(radius, orientation));
+                assert!(
+                    centroid.length() <= PYRAMID_CENTER_EPSILON,
+                    "centroid {:?} for radius {} and orientation {}",
+                    centroid,
+                    radius,
+                    orientation
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn decorations_sit_in_front_of_every_face() {
+        use shared::constants::camera_3d_constants::{CAMERA_3D_INITIAL_Y, CAMERA_3D_MIN_RADIUS};
+        let depth_offset = DECORATION_DEPTH_OFFSET;
+
+        for orientation in [0.0, 0.7, -2.0, std::f32::consts::PI] {
+            let corners_xz = pyramid_corners_xz(PYRAMID_BASE_RADIUS, orientation);
+            // The reversed corners flip the winding of every face
+            let mut reversed = corners_xz;
+            reversed.reverse();
+            for corners_xz in [corners_xz, reversed] {
+                for i in 0..3 {
+                    let next = (i + 1) % 3;
+                    let tl = Vec3::new(corners_xz[i].x, PYRAMID_HEIGHT, corners_xz[i].y);
+                    let tr = Vec3::new(corners_xz[next].x, PYRAMID_HEIGHT, corners_xz[next].y);
+                    let bl = Vec3::new(corners_xz[i].x, GROUND_Y + BASE_HEIGHT, corners_xz[i].y);
+                    let outward = -inward_face_normal(tl, bl, tr);
+
+                    // On the face, seen by the camera orbiting in front of it
+                    let position = (tl + bl + tr + Vec3::new(tr.x, bl.y, tr.z)) / 4.0;
+                    let camera = Vec3::new(position.x, 0.0, position.z).normalize() * CAMERA_3D_MIN_RADIUS
+                        + Vec3::Y * CAMERA_3D_INITIAL_Y;
+
+                    let transform = decoration_transform(position, outward, 1.0, depth_offset);
+                    let context = format!("face {} at orientation {}", i, orientation);
+                    assert!(
+                        transform.translation.distance(camera) < position.distance(camera),
+                        "decoration behind the {}",
+                        context
+                    );
+                    assert!((transform.translation.distance(position) - depth_offset).abs() < 1e-5, "{}", context);
+                    // The mesh front (+Z) faces the camera
+                    let front = transform.rotation * Vec3::Z;
+                    assert!(front.dot(outward) > 0.999, "{}", context);
+                    assert!(front.dot(camera - transform.translation) > 0.0, "{}", context);
+                }
+            }
+        }
+    }
+
+    /// 2x2 face in the XY plane
+    fn test_face() -> PyramidFace {
+        PyramidFace {
+            index: 0,
+            color: Color::WHITE,
+            top_left: Vec3::new(-1.0, 2.0, 0.0),
+            bottom_left: Vec3::new(-1.0, 0.0, 0.0),
+            bottom_right: Vec3::new(1.0, 0.0, 0.0),
+            top_right: Vec3::new(1.0, 2.0, 0.0),
+            normal: Vec3::Z,
+        }
+    }
+
+    #[test]
+    fn decoration_properties_are_seeded_independently() {
+        let face = test_face();
+        let styles = |sets: &[DecorationSet; 2]| sets.clone().map(|set| (set.shape, set.color));
+        let positions = |sets: &[DecorationSet; 2]| {
+            sets.iter().flat_map(|set| set.decorations.iter().map(|d| d.barycentric)).collect::<Vec<_>>()
+        };
+
+        let reference = generate_face_decoration_sets(&face, 42, 4, 0.05, false, DecorationPlacement::Poisson, DECORATION_EDGE_MARGIN);
+        // Reproducible from the seed
+        let again = generate_face_decoration_sets(&face, 42, 4, 0.05, false, DecorationPlacement::Poisson, DECORATION_EDGE_MARGIN);
+        assert_eq!(styles(&again), styles(&reference));
+        assert_eq!(positions(&again), positions(&reference));
+
+        // A different count keeps the shapes and colors, and the layout starts the same
+        let more = generate_face_decoration_sets(&face, 42, 8, 0.05, false, DecorationPlacement::Poisson, DECORATION_EDGE_MARGIN);
+        assert_eq!(styles(&more), styles(&reference));
+        assert_eq!(more[0].decorations[0].barycentric, reference[0].decorations[0].barycentric);
+
+        // The jitter only turns the decorations
+        let jittered = generate_face_decoration_sets(&face, 42, 4, 0.05, true, DecorationPlacement::Poisson, DECORATION_EDGE_MARGIN);
+        assert_eq!(styles(&jittered), styles(&reference));
+        assert_eq!(positions(&jittered), positions(&reference));
+    }
+
+    #[test]
+    fn random_start_yaw_is_reproducible_and_varies_per_round() {
+        let yaws: Vec<f32> = (1..=8).map(|round| random_start_yaw(42, round)).collect();
+        assert!(yaws.iter().all(|yaw| (0.0..std::f32::consts::TAU).contains(yaw)));
+        assert_eq!(yaws[0], random_start_yaw(42, 1));
+        assert_ne!(yaws[0], yaws[1]);
+        assert_ne!(yaws[0], random_start_yaw(43, 1));
+    }
+
+    #[test]
+    fn grid_placement_fits_dense_requests() {
+        let face = test_face();
+        let (size, count) = (0.05, 100);
+        let grid = generate_face_decoration_sets(&face, 7, count, size, false, DecorationPlacement::Grid, DECORATION_EDGE_MARGIN);
+        let poisson = generate_face_decoration_sets(&face, 7, count, size, false, DecorationPlacement::Poisson, DECORATION_EDGE_MARGIN);
+        assert!(poisson.iter().any(|set| set.decorations.len() < count as usize));
+
+        // Same style as the Poisson sets, and the same layout for any seed
+        let other_seed = generate_face_decoration_sets(&face, 8, count, size, false, DecorationPlacement::Grid, DECORATION_EDGE_MARGIN);
+        for ((set, poisson_set), other_set) in grid.iter().zip(&poisson).zip(&other_seed) {
+            assert_eq!((set.shape, set.color), (poisson_set.shape, poisson_set.color));
+            assert_eq!(set.decorations.len(), count as usize);
+            let positions = |set: &DecorationSet| set.decorations.iter().map(|d| d.barycentric).collect::<Vec<_>>();
+            assert_eq!(positions(set), positions(other_set));
+        }
+
+        // Inside the triangles and never overlapping
+        for (set, (top, corner1, corner2)) in grid.iter().zip(face_triangles(&face)) {
+            let world: Vec<Vec3> = set
+                .decorations
+                .iter()
+                .inspect(|d| assert!(d.barycentric.min_element() >= 0.0))
+                .map(|d| d.barycentric.x * top + d.barycentric.y * corner1 + d.barycentric.z * corner2)
+                .collect();
+            for (i, a) in world.iter().enumerate() {
+                for b in &world[i + 1..] {
+                    assert!(a.distance(*b) >= size * 2.4 - 1e-4);
+                }
+            }
+        }
+
+        // More than fits places every lattice point
+        let full = generate_face_decoration_sets(&face, 7, 10_000, size, false, DecorationPlacement::Grid, DECORATION_EDGE_MARGIN);
+        assert!(full.iter().all(|set| set.decorations.len() > count as usize && set.decorations.len() < 10_000));
+    }
+
+    #[test]
+    fn edge_margin_lets_decorations_approach_the_edges_without_crossing_them() {
+        let face = test_face();
+        let size = 0.1;
+        let sets = |margin: f32, placement| generate_face_decoration_sets(&face, 3, 10_000, size, false, placement, margin);
+        let placed = |sets: &[DecorationSet; 2]| sets.iter().map(|set| set.decorations.len()).sum::<usize>();
+
+        for placement in [DecorationPlacement::Poisson, DecorationPlacement::Grid] {
+            let default = sets(DECORATION_EDGE_MARGIN, placement);
+            let closest = sets(0.0, placement);
+            assert!(placed(&closest) > placed(&default), "{:?}", placement);
+
+            // A margin below the shape's extent is clamped, so every mesh stays inside its triangle
+            for (set, (top, corner1, corner2)) in closest.iter().zip(face_triangles(&face)) {
+                let reach = size * shape_extent(set.shape);
+                for d in &set.decorations {
+                    let position = d.barycentric.x * top + d.barycentric.y * corner1 + d.barycentric.z * corner2;
+                    for (a, b) in [(top, corner1), (corner1, corner2), (corner2, top)] {
+                        assert!(point_to_line_segment_distance(position, a, b) >= reach - 1e-4, "{:?}", placement);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn entity_budget_counts_the_door_leaves() {
+        let light_only = round_entity_budget(5, [2, 0, 1], DoorOpenStyle::LightOnly);
+        assert_eq!(round_entity_budget(5, [2, 0, 1], DoorOpenStyle::Slide), light_only + 5);
+        assert_eq!(round_entity_budget(5, [2, 0, 1], DoorOpenStyle::Swing), light_only + 5);
+    }
+}
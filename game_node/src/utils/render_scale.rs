@@ -0,0 +1,142 @@
+//! Reduced resolution rendering of the 3D scene, for weak GPUs.
+//!
+//! Below a scale of 1.0 the persistent camera renders into an image smaller than the window, which a
+//! second camera draws as a full-window UI image behind every other node. That camera also draws the
+//! UI, so the score bar and the overlays keep the window resolution while the scene is upscaled.
+use bevy::camera::{ImageRenderTarget, RenderTarget};
+use bevy::image::BevyDefault;
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureFormat};
+use bevy::window::PrimaryWindow;
+
+use crate::utils::objects::PersistentCamera;
+
+/// Renders the 3D scene at `scale` times the window resolution (1.0 keeps the window resolution).
+pub struct RenderScalePlugin {
+    pub scale: f32,
+}
+
+/// Resolution scale of the 3D scene.
+#[derive(Resource)]
+pub struct RenderScale(pub f32);
+
+/// Image the 3D scene is rendered into, when it is scaled.
+#[derive(Resource)]
+pub struct SceneRenderTarget(pub Handle<Image>);
+
+/// Camera drawing the upscaled scene image and the UI to the window.
+#[derive(Component)]
+pub struct UpscaleCamera;
+
+impl Plugin for RenderScalePlugin {
+    fn build(&self, app: &mut App) {
+        if self.scale >= 1.0 {
+            return;
+        }
+        // After Startup, which spawns the persistent camera
+        app.insert_resource(RenderScale(self.scale))
+            .add_systems(PostStartup, attach_scaled_render_target)
+            .add_systems(Update, resize_scaled_render_target);
+    }
+}
+
+/// Size of the scene image for a window of `window_size` physical pixels, at least one pixel wide.
+pub fn scaled_target_size(window_size: UVec2, scale: f32) -> UVec2 {
+    let scaled = |pixels: u32| ((pixels as f32 * scale).round() as u32).max(1);
+    UVec2::new(scaled(window_size.x), scaled(window_size.y))
+}
+
+/// Points the persistent camera at a scaled image and spawns the camera upscaling it to the window.
+fn attach_scaled_render_target(
+    scale: Res<RenderScale>,
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    camera_query: Query<Entity, With<PersistentCamera>>,
+) {
+    let size = scaled_target_size(window.physical_size(), scale.0);
+    let image = images.add(Image::new_target_texture(
+        size.x,
+        size.y,
+        TextureFormat::bevy_default(),
+        None,
+    ));
+
+    for camera in &camera_query {
+        commands
+            .entity(camera)
+            .insert(RenderTarget::Image(ImageRenderTarget::from(image.clone())));
+    }
+
+    commands.spawn((
+        Camera2d,
+        // Drawn after the scene is rendered into the image
+        Camera {
+            order: 1,
+            ..default()
+        },
+        IsDefaultUiCamera,
+        UpscaleCamera,
+    ));
+    // Not a UIEntity: the image stays across resets, like the persistent camera
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            ..default()
+        },
+        ImageNode::new(image.clone()),
+        // Behind the fixation cross, the score bar and the blank screen
+        GlobalZIndex(i32::MIN),
+    ));
+
+    info!("Rendering the 3D scene at {}x{} ({}x the window resolution)", size.x, size.y, scale.0);
+    commands.insert_resource(SceneRenderTarget(image));
+}
+
+/// Follows the window size, the scene image would otherwise be stretched after a resize.
+fn resize_scaled_render_target(
+    scale: Res<RenderScale>,
+    target: Option<Res<SceneRenderTarget>>,
+    mut images: ResMut<Assets<Image>>,
+    window: Single<&Window, (With<PrimaryWindow>, Changed<Window>)>,
+) {
+    let Some(target) = target else { return };
+    let size = scaled_target_size(window.physical_size(), scale.0);
+    // Only touch the asset on a new size, a modified image is uploaded again
+    let Some(current) = images.get(&target.0).map(|image| image.size()) else { return };
+    if current == size {
+        return;
+    }
+    if let Some(image) = images.get_mut(&target.0) {
+        image.resize(Extent3d {
+            width: size.x,
+            height: size.y,
+            ..default()
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::window_config::clamp_render_scale;
+    use shared::constants::render_constants::MIN_RENDER_SCALE;
+
+    #[test]
+    fn scaled_target_follows_the_window() {
+        assert_eq!(scaled_target_size(UVec2::new(1920, 1080), 1.0), UVec2::new(1920, 1080));
+        assert_eq!(scaled_target_size(UVec2::new(1920, 1080), 0.5), UVec2::new(960, 540));
+        assert_eq!(scaled_target_size(UVec2::new(1, 3), 0.1), UVec2::new(1, 1));
+    }
+
+    #[test]
+    fn render_scale_is_clamped_to_a_downscale() {
+        assert_eq!(clamp_render_scale(0.75), Some(0.75));
+        assert_eq!(clamp_render_scale(2.0), Some(1.0));
+        assert_eq!(clamp_render_scale(0.01), Some(MIN_RENDER_SCALE));
+        assert_eq!(clamp_render_scale(0.0), None);
+        assert_eq!(clamp_render_scale(f32::NAN), None);
+    }
+}
@@ -0,0 +1,55 @@
+//! Per-round logging of the effective game configuration.
+//!
+//! When the `MONKEY_ROUND_LOG` environment variable is set, one JSON line is appended to that
+//! file at the start of every round (practice rounds excepted), so experiments can be reproduced offline.
+//! Native only: the browser build has no file system.
+use crate::command_handler::SharedMemResource;
+use crate::utils::objects::RoundStartTimestamp;
+use bevy::prelude::*;
+use shared::constants::game_constants::ROUND_LOG_ENV_VAR;
+use shared::SharedGameStructure;
+use std::io::Write;
+use std::sync::atomic::Ordering;
+
+/// Appends the configuration of the round that just started to the JSONL log file.
+pub fn log_round_config(
+    round_start: Res<RoundStartTimestamp>,
+    shm_res: Option<Res<SharedMemResource>>,
+) {
+    // A new round sets the start timestamp in setup_round
+    if !round_start.is_changed() || round_start.0.is_none() {
+        return;
+    }
+    let Some(shm_res) = shm_res else { return };
+    let Ok(path) = std::env::var(ROUND_LOG_ENV_VAR) else {
+        return;
+    };
+    let gs_game = &shm_res.0.get().game_structure_game;
+    // Practice rounds are not part of the experiment
+    if gs_game.practice_mode.load(Ordering::Relaxed) {
+        debug!("Practice round, not logged to {}", path);
+        return;
+    }
+
+    let line = round_config_json(gs_game).to_string();
+    let result = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| writeln!(file, "{}", line));
+    match result {
+        Ok(()) => debug!("Round config appended to {}", path),
+        Err(e) => warn!("Failed to write round config to {}: {}", path, e),
+    }
+}
+
+/// Serializes the effective (game side) configuration of the current round.
+fn round_config_json(gs: &SharedGameStructure) -> serde_json::Value {
+    let unix_time = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0.0, |d| d.as_secs_f64());
+
+    let mut value = serde_json::to_value(gs.snapshot()).unwrap_or_default();
+    value["unix_time"] = unix_time.into();
+    value
+}
@@ -3,15 +3,28 @@ use bevy::prelude::*;
 
 use bevy::asset::RenderAssetUsages;
 use bevy::mesh::Indices;
-use bevy::render::render_resource::PrimitiveTopology;
+use bevy::render::render_resource::{Extent3d, PrimitiveTopology, TextureDimension, TextureFormat};
 
 use crate::log;
+use crate::utils::asset_cache::SceneAssets;
+use crate::utils::camera::reset_to_start_pose;
+use crate::utils::mesh_validation::debug_assert_mesh_valid;
 use crate::utils::objects::*;
-use crate::utils::pyramid::spawn_pyramid;
+use crate::utils::pyramid::{random_start_yaw, spawn_pyramid};
 use shared::constants::{
-    lighting_constants::{GLOBAL_AMBIENT_LIGHT_INTENSITY, SPOTLIGHT_LIGHT_INTENSITY},
-    object_constants::GROUND_Y,
+    base_layout::is_valid_door_count,
+    decoration_layout::{decoration_count_for_density, pyramid_face_area},
+    lighting_constants::{
+        GLOBAL_AMBIENT_LIGHT_COLOR, GLOBAL_AMBIENT_LIGHT_INTENSITY, SPOTLIGHT_LIGHT_INTENSITY,
+    },
+    object_constants::{GROUND_Y, SHOW_BACKDROP},
+    pyramid_constants::{
+        BASE_HEIGHT, BASE_NR_SIDES, BASE_NR_SIDES_MAX, BASE_TEXTURE_SIZE, COLOR_PALETTE, DECORATION_STAR_INNER_RATIO,
+        DECORATION_STAR_POINTS_MAX, DECORATION_STAR_POINTS_MIN, PALETTE_INDEX_NONE, PYRAMID_BASE_RADIUS,
+        PYRAMID_HEIGHT, PYRAMID_TARGET_DOOR_INDEX,
+    },
 };
+use shared::{BaseTexture, ColorSpace, DecorationPlacement, DoorOpenStyle, SharedGameStructure};
 
 use crate::command_handler::SharedMemResource;
 use core::sync::atomic::Ordering;
@@ -22,7 +35,14 @@ pub fn setup_environment(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
 ) {
+    // Procedural base textures, selected per round by the `base_texture` config
+    commands.insert_resource(BaseTextures {
+        wood_grain: images.add(create_wood_grain_image(BASE_TEXTURE_SIZE)),
+        checker: images.add(create_checker_image(BASE_TEXTURE_SIZE)),
+    });
+
     // Ground Plane
     commands.spawn((
         Mesh3d(meshes.add(Plane3d::default().mesh().size(50.0, 50.0))),
@@ -46,6 +66,8 @@ pub fn setup_environment(
             ..default()
         })),
         Transform::from_xyz(0.0, GROUND_Y, 0.0),
+        if SHOW_BACKDROP { Visibility::Visible } else { Visibility::Hidden },
+        Backdrop,
     ));
 
     // Main Spotlight
@@ -63,7 +85,11 @@ pub fn setup_environment(
 
     // Ambient Light
     commands.insert_resource(GlobalAmbientLight {
-        color: Color::WHITE,
+        color: Color::srgb(
+            GLOBAL_AMBIENT_LIGHT_COLOR[0],
+            GLOBAL_AMBIENT_LIGHT_COLOR[1],
+            GLOBAL_AMBIENT_LIGHT_COLOR[2],
+        ),
         brightness: GLOBAL_AMBIENT_LIGHT_INTENSITY, // Default start value
         affects_lightmapped_meshes: true,
     });
@@ -75,15 +101,15 @@ pub fn setup_environment(
 /// This spawns the pyramid and resets the camera. All spawned entities are marked with GameEntity.
 pub fn setup_round(
     mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-    mut camera_query: Query<&mut Transform, With<PersistentCamera>>,
+    mut assets: SceneAssets,
+    mut camera_query: Query<(&mut Transform, &mut Projection), With<PersistentCamera>>,
     mut spotlight_query: Query<&mut SpotLight, (Without<HoleLight>, Without<GameEntity>)>,
     ambient_light: Option<ResMut<GlobalAmbientLight>>,
     shm_res: Option<Res<SharedMemResource>>,
     mut round_start: ResMut<crate::utils::objects::RoundStartTimestamp>,
     time: Res<Time>,
     mut door_win_entities: ResMut<DoorWinEntities>,
+    base_textures: Option<Res<BaseTextures>>,
 ) {
     // Read shared memory
     let Some(shm_res) = shm_res else {
@@ -100,7 +126,13 @@ pub fn setup_round(
     let gs_ctrl = &shm.game_structure_control;
     // Reset all fields of game structure
     let gs_game = &shm.game_structure_game;
+    let round_index = gs_game.round_index.load(Ordering::Relaxed) + 1;
+    let heartbeat = gs_game.heartbeat.load(Ordering::Relaxed);
+    // Read before the copy: a config written meanwhile has a later epoch and still reads as pending
+    let config_epoch = gs_ctrl.config_epoch.load(Ordering::Acquire);
     gs_game.reset_all_fields(gs_ctrl);
+    gs_game.round_index.store(round_index, Ordering::Relaxed);
+    gs_game.heartbeat.store(heartbeat, Ordering::Relaxed);
 
     // Update all the game resoruces based on the new configuration
     let mut decoration_seeds = [0u64; 3];
@@ -108,8 +140,10 @@ pub fn setup_round(
         decoration_seeds[i] = gs_game.decoration_seeds[i].load(Ordering::Relaxed);
     }
 
-    let main_intensity = f32::from_bits(gs_game.main_spotlight_intensity.load(Ordering::Relaxed));
-    let ambient_intensity = f32::from_bits(gs_game.ambient_brightness.load(Ordering::Relaxed));
+    // The calibration gain scales every light and the face colors
+    let gain = brightness_gain(gs_game);
+    let main_intensity = f32::from_bits(gs_game.main_spotlight_intensity.load(Ordering::Relaxed)) * gain;
+    let ambient_intensity = f32::from_bits(gs_game.ambient_brightness.load(Ordering::Relaxed)) * gain;
     // Update Lights
     for mut spot in spotlight_query.iter_mut() {
         spot.intensity = main_intensity;
@@ -117,33 +151,58 @@ pub fn setup_round(
 
     if let Some(mut ambient) = ambient_light {
         ambient.brightness = ambient_intensity;
+        ambient.color = ambient_color(gs_game);
     }
 
-    // Reset the persistent camera position
-    if let Ok(mut camera_transform) = camera_query.single_mut() {
-        *camera_transform = Transform::from_xyz(
-            f32::from_bits(gs_ctrl.camera_x.load(Ordering::Relaxed)),
-            f32::from_bits(gs_ctrl.camera_y.load(Ordering::Relaxed)),
-            f32::from_bits(gs_ctrl.camera_z.load(Ordering::Relaxed)),
-        )
-        .looking_at(Vec3::ZERO, Vec3::Y);
+    // Reset the persistent camera position, and the field of view a FovZoom may have changed
+    if let Ok((mut camera_transform, mut projection)) = camera_query.single_mut() {
+        reset_to_start_pose(&mut camera_transform, &mut projection, gs_ctrl);
     }
 
     gs_game.win_time.store(0, Ordering::Relaxed);
+    gs_game.attempts.store(gs_game.attempts_base.load(Ordering::Relaxed), Ordering::Relaxed);
+    gs_game.practice_win_time.store(0, Ordering::Relaxed);
+    gs_game.practice_attempts.store(gs_game.attempts_base.load(Ordering::Relaxed), Ordering::Relaxed);
+    gs_game.correct_streak.store(0, Ordering::Relaxed);
 
-    let radius = f32::from_bits(gs_game.base_radius.load(Ordering::Relaxed));
-    let height = f32::from_bits(gs_game.height.load(Ordering::Relaxed));
+    let (radius, height) = pyramid_dimensions(gs_game);
     let orient = f32::from_bits(gs_game.start_orient.load(Ordering::Relaxed));
+    // The whole scene is turned, like the rotation commands do, so start_orient keeps its meaning
+    let start_yaw = if gs_game.random_start_orientation.load(Ordering::Relaxed) {
+        random_start_yaw(decoration_seeds[0], round_index)
+    } else {
+        0.0
+    };
+    gs_game.start_yaw.store(start_yaw.to_bits(), Ordering::Relaxed);
+
+    // A palette entry replaces the explicit colors, stored back so the readback reflects reality
+    let palette_index = gs_game.palette_index.load(Ordering::Relaxed);
+    if palette_index != PALETTE_INDEX_NONE {
+        match COLOR_PALETTE.get(palette_index as usize) {
+            Some(palette) => {
+                for (i, channel) in palette.iter().flatten().enumerate() {
+                    gs_game.colors[i].store(channel.to_bits(), Ordering::Relaxed);
+                }
+            }
+            None => warn!(
+                "palette_index={} out of range (0..{}), using the explicit colors",
+                palette_index,
+                COLOR_PALETTE.len()
+            ),
+        }
+    }
 
+    let color_space = ColorSpace::from_u32(gs_game.color_space.load(Ordering::Relaxed));
     let mut colors = [Color::WHITE; 3];
     for i in 0..3 {
         let r = f32::from_bits(gs_game.colors[i * 4 + 0].load(Ordering::Relaxed));
         let g = f32::from_bits(gs_game.colors[i * 4 + 1].load(Ordering::Relaxed));
         let b = f32::from_bits(gs_game.colors[i * 4 + 2].load(Ordering::Relaxed));
         let a = f32::from_bits(gs_game.colors[i * 4 + 3].load(Ordering::Relaxed));
-        colors[i] = Color::srgba(r, g, b, a);
+        colors[i] = scale_brightness(face_color(color_space, [r, g, b, a]), gain);
     }
 
+    resolve_decoration_counts(gs_game);
     let mut decoration_counts = [0; 3];
     for i in 0..3 {
         decoration_counts[i] = gs_game.decorations_count[i].load(Ordering::Relaxed);
@@ -154,14 +213,19 @@ pub fn setup_round(
         decoration_sizes[i] = f32::from_bits(gs_game.decorations_size[i].load(Ordering::Relaxed));
     }
 
-    // Read target door from shared memory
-    let target_door = gs_game.target_door.load(Ordering::Relaxed) as usize;
+    let decoration_emissive = std::array::from_fn(|i| decoration_emissive(gs_game, i));
+    let (star_points, star_inner_ratio, depth_offset) = decoration_style(gs_game);
+
+    // Base texture (SolidColor keeps the flat wood color)
+    let base_texture = BaseTexture::from_u32(gs_game.base_texture.load(Ordering::Relaxed));
+    let base_texture = base_textures.and_then(|textures| textures.get(base_texture));
+
+    let (nr_sides, target_door) = door_layout(gs_game);
     
     // Spawn the pyramid and capture winning door entities
-    let (winning_light, winning_emissive) = spawn_pyramid(
+    let (winning_light, winning_emissive, placed_decorations) = spawn_pyramid(
         &mut commands,
-        &mut meshes,
-        &mut materials,
+        &mut assets,
         decoration_seeds,
         radius,
         height,
@@ -169,19 +233,174 @@ pub fn setup_round(
         colors,
         decoration_counts,
         decoration_sizes,
+        decoration_emissive,
+        star_points,
+        star_inner_ratio,
+        depth_offset,
+        gs_game.decoration_jitter_enabled.load(Ordering::Relaxed),
+        DecorationPlacement::from_u32(gs_game.placement_mode.load(Ordering::Relaxed)),
+        f32::from_bits(gs_game.decoration_edge_margin_factor.load(Ordering::Relaxed)),
+        base_texture,
+        nr_sides,
         target_door,
+        DoorOpenStyle::from_u32(gs_game.door_open_style.load(Ordering::Relaxed)),
+        Quat::from_rotation_y(start_yaw),
     );
 
+    for (face_index, placed) in placed_decorations.into_iter().enumerate() {
+        store_placed_decorations(gs_game, face_index, placed);
+    }
+
     // Populate DoorWinEntities with the target door's entities and reset timer
     door_win_entities.winning_light = winning_light;
     door_win_entities.winning_emissive = winning_emissive;
     door_win_entities.animation_start_time = None;
 
+    // Stays flat across rounds unless assets leak
+    debug!(
+        "Assets after reset: {} meshes, {} materials",
+        assets.meshes.len(),
+        assets.materials.len()
+    );
+
+    gs_game.config_applied_epoch.store(config_epoch, Ordering::Release);
     log!("🎮 Round Started! target_door={}, winning_light={:?}, winning_emissive={:?}", target_door, winning_light, winning_emissive);
 }
 
 
 
+/// Pyramid radius and height of a game structure. Values giving a degenerate pyramid (non-finite, radius not
+/// positive, top not above the wooden base) are replaced by the defaults, stored back for the readback.
+pub fn pyramid_dimensions(gs: &SharedGameStructure) -> (f32, f32) {
+    let mut radius = f32::from_bits(gs.base_radius.load(Ordering::Relaxed));
+    let mut height = f32::from_bits(gs.height.load(Ordering::Relaxed));
+
+    if !(radius.is_finite() && radius > 0.0) {
+        warn!("base_radius={} is not positive, falling back to {}", radius, PYRAMID_BASE_RADIUS);
+        radius = PYRAMID_BASE_RADIUS;
+        gs.base_radius.store(radius.to_bits(), Ordering::Relaxed);
+    }
+    let base_top = GROUND_Y + BASE_HEIGHT;
+    if !(height.is_finite() && height > base_top) {
+        warn!(
+            "height={} is not above the base top ({}), falling back to {}",
+            height, base_top, PYRAMID_HEIGHT
+        );
+        height = PYRAMID_HEIGHT;
+        gs.height.store(height.to_bits(), Ordering::Relaxed);
+    }
+    (radius, height)
+}
+
+/// Number of doors and target door of a game structure. An invalid door count falls back to BASE_NR_SIDES,
+/// then a target door out of range (which would make the round unwinnable) to PYRAMID_TARGET_DOOR_INDEX,
+/// both stored back for the readback.
+pub fn door_layout(gs: &SharedGameStructure) -> (usize, usize) {
+    let mut nr_sides = gs.base_nr_sides.load(Ordering::Relaxed) as usize;
+    if !is_valid_door_count(nr_sides) {
+        warn!(
+            "base_nr_sides={} is not a multiple of 3 in 3..={}, falling back to {}",
+            nr_sides, BASE_NR_SIDES_MAX, BASE_NR_SIDES
+        );
+        nr_sides = BASE_NR_SIDES;
+        gs.base_nr_sides.store(nr_sides as u32, Ordering::Relaxed);
+    }
+    let mut target_door = gs.target_door.load(Ordering::Relaxed) as usize;
+    if target_door >= nr_sides {
+        warn!(
+            "target_door={} out of range (doors 0..{}), falling back to {}",
+            target_door, nr_sides, PYRAMID_TARGET_DOOR_INDEX
+        );
+        target_door = PYRAMID_TARGET_DOOR_INDEX;
+        gs.target_door.store(target_door as u32, Ordering::Relaxed);
+    }
+    (nr_sides, target_door)
+}
+
+/// In density mode (`decoration_density` > 0 for a face) replaces the decoration count of the face by the one
+/// giving that density on the current face area, so the readback reports the spawned count.
+pub fn resolve_decoration_counts(gs: &SharedGameStructure) {
+    let (radius, height) = pyramid_dimensions(gs);
+    let face_area = pyramid_face_area(radius, height);
+    for i in 0..3 {
+        let density = f32::from_bits(gs.decoration_density[i].load(Ordering::Relaxed));
+        if density > 0.0 {
+            let count = decoration_count_for_density(density, face_area);
+            gs.decorations_count[i].store(count, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Stores the shape, color and placed count of the decorations rendered on a face, one per face triangle
+pub fn store_placed_decorations(
+    gs: &SharedGameStructure,
+    face_index: usize,
+    placed: [PlacedDecorations; 2],
+) {
+    for (half, ((shape, color), count)) in placed.into_iter().enumerate() {
+        let slot = face_index * 2 + half;
+        gs.decoration_shapes[slot].store(shape as u32, Ordering::Relaxed);
+        gs.decorations_placed[slot].store(count, Ordering::Relaxed);
+        for (channel, value) in color.to_srgba().to_f32_array().into_iter().enumerate() {
+            gs.decoration_colors[slot * 4 + channel].store(value.to_bits(), Ordering::Relaxed);
+        }
+    }
+}
+
+/// Emissive strength of the decorations of a face relative to their color, 0 for invalid values.
+pub fn decoration_emissive(gs: &SharedGameStructure, face_index: usize) -> f32 {
+    let emissive = f32::from_bits(gs.decoration_emissive[face_index].load(Ordering::Relaxed));
+    if emissive.is_finite() && emissive >= 0.0 { emissive } else { 0.0 }
+}
+
+/// Reads the decoration style from a game structure: `(star_points, star_inner_ratio, depth_offset)`
+pub fn decoration_style(gs: &SharedGameStructure) -> (u32, f32, f32) {
+    // Star decorations complexity, clamped to a sane range
+    let star_points = gs
+        .star_points
+        .load(Ordering::Relaxed)
+        .clamp(DECORATION_STAR_POINTS_MIN, DECORATION_STAR_POINTS_MAX);
+    // Clamping keeps a NaN, which would reach the star mesh and its cache key
+    let star_inner_ratio = Some(f32::from_bits(gs.star_inner_ratio.load(Ordering::Relaxed)))
+        .filter(|ratio| ratio.is_finite())
+        .unwrap_or(DECORATION_STAR_INNER_RATIO)
+        .clamp(0.05, 0.95);
+
+    // Anti z-fighting offset shared by decorations and the door glow
+    let depth_offset = f32::from_bits(gs.decoration_depth_offset.load(Ordering::Relaxed)).max(0.0);
+
+    (star_points, star_inner_ratio, depth_offset)
+}
+
+/// Face color from the stored channels, gamma-encoded or linear light depending on `color_space`.
+pub fn face_color(color_space: ColorSpace, [r, g, b, a]: [f32; 4]) -> Color {
+    match color_space {
+        ColorSpace::Srgb => Color::srgba(r, g, b, a),
+        ColorSpace::Linear => Color::linear_rgba(r, g, b, a),
+    }
+}
+
+/// Calibration gain of a game structure, 1.0 when not a finite non-negative value.
+pub fn brightness_gain(gs: &SharedGameStructure) -> f32 {
+    let gain = f32::from_bits(gs.brightness_gain.load(Ordering::Relaxed));
+    if gain.is_finite() && gain >= 0.0 { gain } else { 1.0 }
+}
+
+/// Scales the RGB of a color in linear light, so the emitted luminance scales by `gain`.
+pub fn scale_brightness(color: Color, gain: f32) -> Color {
+    let linear = color.to_linear();
+    Color::linear_rgba(linear.red * gain, linear.green * gain, linear.blue * gain, linear.alpha)
+}
+
+/// Reads the ambient light color (sRGB) from a game structure
+pub fn ambient_color(gs: &SharedGameStructure) -> Color {
+    Color::srgb(
+        f32::from_bits(gs.ambient_color[0].load(Ordering::Relaxed)),
+        f32::from_bits(gs.ambient_color[1].load(Ordering::Relaxed)),
+        f32::from_bits(gs.ambient_color[2].load(Ordering::Relaxed)),
+    )
+}
+
 fn create_extended_semicircle_mesh(
     radius: f32,
     height: f32,
@@ -242,5 +461,129 @@ fn create_extended_semicircle_mesh(
     mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
     mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
     mesh.insert_indices(Indices::U32(indices));
+    debug_assert_mesh_valid(&mesh, "extended_semicircle");
     mesh
 }
+
+/// Creates a grayscale wood grain texture, tinted by the base color of the material
+fn create_wood_grain_image(size: u32) -> Image {
+    let mut data = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let u = x as f32 / size as f32;
+            let v = y as f32 / size as f32;
+            // Wavy stripes along the panel with a slower warp to mimic growth rings
+            let warp = 4.0 * (v * 6.0).sin() + 2.0 * (v * 17.0 + u * 3.0).sin();
+            let grain = 0.5 + 0.5 * (u * 40.0 + warp).sin();
+            let value = ((0.7 + 0.3 * grain) * 255.0) as u8;
+            data.extend_from_slice(&[value, value, value, 255]);
+        }
+    }
+
+    Image::new(
+        Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+}
+
+/// Creates a grayscale 8x8 checker texture, tinted by the base color of the material
+fn create_checker_image(size: u32) -> Image {
+    let cell = (size / 8).max(1);
+    let mut data = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let value = if (x / cell + y / cell).is_multiple_of(2) { 255 } else { 150 };
+            data.extend_from_slice(&[value, value, value, 255]);
+        }
+    }
+
+    Image::new(
+        Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::asset_cache::AssetCache;
+    use bevy::mesh::VertexAttributeValues;
+    use shared::{create_shared_memory, remove_shared_memory};
+
+    #[test]
+    fn degenerate_dimensions_fall_back_to_a_valid_pyramid() {
+        let name = format!("monkey_test_degenerate_dimensions_{}", std::process::id());
+        let handle = create_shared_memory(&name).unwrap();
+        let gs_ctrl = &handle.get().game_structure_control;
+        gs_ctrl.base_radius.store(0.0f32.to_bits(), Ordering::Relaxed);
+        gs_ctrl.height.store((-1.0f32).to_bits(), Ordering::Relaxed);
+
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .init_resource::<Assets<Mesh>>()
+            .init_resource::<Assets<StandardMaterial>>()
+            .init_resource::<AssetCache>()
+            .init_resource::<RoundStartTimestamp>()
+            .init_resource::<DoorWinEntities>()
+            .insert_resource(SharedMemResource(handle.clone()))
+            .add_systems(Update, setup_round);
+        app.update();
+
+        let gs_game = &handle.get().game_structure_game;
+        assert_eq!(f32::from_bits(gs_game.base_radius.load(Ordering::Relaxed)), PYRAMID_BASE_RADIUS);
+        assert_eq!(f32::from_bits(gs_game.height.load(Ordering::Relaxed)), PYRAMID_HEIGHT);
+
+        let meshes = app.world().resource::<Assets<Mesh>>();
+        assert!(!meshes.is_empty());
+        for (_, mesh) in meshes.iter() {
+            if let Some(VertexAttributeValues::Float32x3(normals)) = mesh.attribute(Mesh::ATTRIBUTE_NORMAL) {
+                assert!(normals.iter().flatten().all(|value| value.is_finite()));
+            }
+        }
+        remove_shared_memory(&name).unwrap();
+    }
+
+    #[test]
+    fn linear_face_colors_are_not_gamma_decoded() {
+        let rgba = [0.5, 0.5, 0.5, 1.0];
+        assert_eq!(face_color(ColorSpace::Linear, rgba).to_linear().red, 0.5);
+        // Mid grey in sRGB is about a fifth of the white luminance
+        let srgb = face_color(ColorSpace::Srgb, rgba).to_linear().red;
+        assert!((srgb - 0.214).abs() < 1e-3);
+    }
+
+    #[test]
+    fn brightness_gain_scales_the_luminance() {
+        let grey = Color::srgb(0.5, 0.5, 0.5);
+        let doubled = scale_brightness(grey, 2.0).to_linear();
+        assert!((doubled.red - 2.0 * grey.to_linear().red).abs() < 1e-6);
+        assert_eq!(doubled.alpha, 1.0);
+
+        let gs = SharedGameStructure::new();
+        gs.brightness_gain.store(f32::NAN.to_bits(), Ordering::Relaxed);
+        assert_eq!(brightness_gain(&gs), 1.0);
+    }
+
+    #[test]
+    fn non_finite_star_ratio_falls_back_to_the_default() {
+        let gs = SharedGameStructure::new();
+        gs.star_inner_ratio.store(f32::NAN.to_bits(), Ordering::Relaxed);
+        assert_eq!(decoration_style(&gs).1, DECORATION_STAR_INNER_RATIO);
+        gs.star_inner_ratio.store(2.0f32.to_bits(), Ordering::Relaxed);
+        assert_eq!(decoration_style(&gs).1, 0.95);
+    }
+}
@@ -1,20 +1,43 @@
 //! Game logic wrapped up using the various plugins.
 //!
 use crate::command_handler::SharedMemResource;
-use crate::command_handler::{PendingAnimation, PendingBlankScreen, PendingReset, RenderingPaused};
-use crate::state_emitter::FrameCounterResource;
-use crate::utils::camera::{apply_pending_rotation, apply_pending_zoom};
+use crate::command_handler::{
+    apply_input_lockout, read_shared_memory, shm_available, PendingAnimation, PendingBlankScreen, PendingBlankScreenState, PendingRebuildBase,
+    PendingReroll, PendingReleaseCursor, PendingReset, RenderingPaused,
+};
+use crate::state_emitter::{FrameCounterResource, FrameTimingResource};
+use crate::utils::asset_cache::{AssetCache, SceneAssets};
+use crate::utils::camera::{
+    apply_pending_recenter, apply_pending_rotation, apply_pending_viewpoint, apply_pending_zoom,
+    camera_free_fly_keyboard, pyramid_yaw, sync_camera_mode,
+};
 use crate::utils::game_functions::{
-    apply_pending_check_alignment, handle_door_animation, spawn_score_bar,
-    update_score_bar_animation, update_ui_scale,
+    apply_pending_check_alignment, auto_reset_after_result, handle_door_animation, latch_door_animation_timing,
+    publish_trial_result, round_won,
+    spawn_fixation_cross, spawn_score_bar, sync_fixation_cross, update_best_door_hint, update_score_bar_animation,
+    update_target_resting_light, update_ui_scale, ScoreBarLayout,
 };
 use crate::utils::objects::{
-    DoorWinEntities, GameEntity, PersistentCamera, RoundStartTimestamp,
-    UIEntity,
+    Backdrop, BaseEntityFilter, BaseTextures, BestDoorHint, DoorWinEntities, FaceDecoration, GameEntity,
+    PersistentCamera, PyramidFace, RoundStartTimestamp, SessionStats, UIEntity,
+};
+use crate::utils::pyramid::{spawn_face_decorations, spawn_pyramid_base, FaceDecorationParams};
+use crate::utils::setup::{
+    ambient_color, brightness_gain, decoration_emissive, decoration_style, door_layout, resolve_decoration_counts,
+    setup_environment, store_placed_decorations,
 };
-use crate::utils::setup::setup_environment;
+use bevy::core_pipeline::tonemapping::Tonemapping;
+use bevy::post_process::bloom::Bloom;
 use bevy::prelude::*;
+use bevy::render::view::Hdr;
+use bevy::window::{CursorGrabMode, CursorOptions, PresentMode, PrimaryWindow};
 use crate::utils::setup::setup_round;
+use shared::constants::render_constants::{
+    BLOOM_ENABLED, FOG_COLOR, FOG_ENABLED, FOG_END, FOG_START, TONEMAPPING,
+};
+use shared::{
+    BaseTexture, DecorationPlacement, DoorOpenStyle, PresentModeCommand, SharedGameStructure, TrialResult,
+};
 use core::sync::atomic::Ordering;
 
 // Plugin for managing all the game systems.config
@@ -24,17 +47,70 @@ impl Plugin for SystemsLogicPlugin {
     /// Builds the plugin by adding the systems to the app.
     fn build(&self, app: &mut App) {
         app.init_resource::<BlankScreenState>()
+            .init_resource::<AssetCache>()
+            .init_resource::<BestDoorHint>()
+            .init_resource::<SessionStats>()
             // Spawn persistent camera and static environment once at startup
-            .add_systems(Startup, (spawn_persistent_camera, setup_environment))
+            .add_systems(
+                Startup,
+                (spawn_persistent_camera, setup_environment, spawn_fixation_cross),
+            )
             // Global UI responsiveness system (runs every frame)
             .add_systems(Update, update_ui_scale)
             // Command driven
             .add_systems(
                 Update,
-                (handle_reset_command, handle_animation_door_command),
+                (
+                    handle_reset_command,
+                    // Before a reset of the same frame, which then despawns the re-rolled decorations
+                    handle_reroll_command.before(handle_reset_command),
+                    // Likewise, and before the animation command so it reveals the rebuilt target door
+                    handle_rebuild_base_command
+                        .before(handle_reset_command)
+                        .before(handle_animation_door_command),
+                    handle_animation_door_command,
+                )
+                    .run_if(shm_available),
+            )
+            // Target door hint light, on the new round's entities and once the animation is over
+            .add_systems(
+                Update,
+                update_target_resting_light
+                    .after(handle_reset_command)
+                    .after(handle_door_animation)
+                    .run_if(shm_available),
             )
             // Rendering control systems (run any time)
-            .add_systems(Update, (apply_blank_screen, handle_rendering_pause))
+            .add_systems(
+                Update,
+                (apply_blank_screen, handle_rendering_pause, apply_release_cursor, apply_present_mode),
+            )
+            // Live config applied without waiting for a reset
+            .add_systems(
+                Update,
+                (
+                    sync_ambient_light,
+                    sync_camera_post_processing,
+                    sync_camera_fog,
+                    sync_backdrop_visibility,
+                    sync_fixation_cross,
+                ),
+            )
+            // Camera control scheme (orbit for the task, free-fly for inspection)
+            .add_systems(
+                Update,
+                (sync_camera_mode, camera_free_fly_keyboard)
+                    .chain()
+                    .run_if(is_not_paused),
+            )
+            // Like a reset command: after the shared memory read, before the lockout the reset starts
+            .add_systems(
+                PreUpdate,
+                auto_reset_after_result
+                    .after(read_shared_memory)
+                    .before(apply_input_lockout)
+                    .run_if(shm_available),
+            )
             // Input and Logic Systems
             .add_systems(
                 Update,
@@ -44,14 +120,25 @@ impl Plugin for SystemsLogicPlugin {
                     (
                         apply_pending_rotation,
                         apply_pending_zoom,
+                        apply_pending_recenter,
+                        apply_pending_viewpoint,
                         apply_pending_check_alignment,
                         handle_door_animation,
                         update_score_bar_animation,
+                        update_best_door_hint,
                     )
-                        .run_if(is_not_paused),
+                        .run_if(is_not_paused)
+                        .run_if(shm_available),
 
                 ).chain(),
             );
+
+        // Per-round config log, written once setup_round has filled the game structure
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_systems(
+            Update,
+            crate::utils::round_log::log_round_config.after(handle_reset_command),
+        );
     }
 }
 
@@ -62,6 +149,25 @@ fn is_not_paused(rendering_paused: Res<RenderingPaused>) -> bool {
 /// This camera persists across resets to avoid artifacts.
 fn spawn_persistent_camera(mut commands: Commands, shm_res: Option<Res<SharedMemResource>>) {
     // Get initial of camera
+    let (bloom_enabled, tonemapping, fog) = if let Some(ref shm_res) = shm_res {
+        let shm = shm_res.0.get();
+        (
+            shm.game_structure_game.bloom_enabled.load(Ordering::Relaxed),
+            shm.game_structure_game.tonemapping.load(Ordering::Relaxed),
+            distance_fog(&shm.game_structure_game),
+        )
+    } else {
+        let fog = FOG_ENABLED.then(|| DistanceFog {
+            color: Color::srgba(FOG_COLOR[0], FOG_COLOR[1], FOG_COLOR[2], FOG_COLOR[3]),
+            falloff: FogFalloff::Linear {
+                start: FOG_START,
+                end: FOG_END,
+            },
+            ..default()
+        });
+        (BLOOM_ENABLED, TONEMAPPING, fog)
+    };
+
     let (camera_3d_initial_x, camera_3d_initial_y, camera_3d_initial_z) = if let Some(ref shm_res) = shm_res {
         let shm = shm_res.0.get();
         (
@@ -74,7 +180,7 @@ fn spawn_persistent_camera(mut commands: Commands, shm_res: Option<Res<SharedMem
     };
 
 
-    commands.spawn((
+    let mut camera = commands.spawn((
         Camera3d::default(),
         Transform::from_xyz(
             camera_3d_initial_x,
@@ -82,8 +188,130 @@ fn spawn_persistent_camera(mut commands: Commands, shm_res: Option<Res<SharedMem
             camera_3d_initial_z,
         )
         .looking_at(Vec3::ZERO, Vec3::Y),
+        tonemapping_from_u32(tonemapping),
         PersistentCamera,
     ));
+    if bloom_enabled {
+        camera.insert(Bloom::NATURAL);
+    }
+    if let Some(fog) = fog {
+        camera.insert(fog);
+    }
+}
+
+/// Builds the distance fog described by a game structure (`None` when fog is disabled)
+fn distance_fog(gs: &SharedGameStructure) -> Option<DistanceFog> {
+    if !gs.fog_enabled.load(Ordering::Relaxed) {
+        return None;
+    }
+    let channel = |i: usize| f32::from_bits(gs.fog_color[i].load(Ordering::Relaxed));
+    let start = f32::from_bits(gs.fog_start.load(Ordering::Relaxed)).max(0.0);
+    let end = f32::from_bits(gs.fog_end.load(Ordering::Relaxed)).max(start + 0.01);
+
+    Some(DistanceFog {
+        color: Color::srgba(channel(0), channel(1), channel(2), channel(3)),
+        falloff: FogFalloff::Linear { start, end },
+        ..default()
+    })
+}
+
+/// Applies fog changes from the Controller to the persistent camera, without a reset.
+/// `applied` holds the fog fields last applied to the camera: the game structure cannot tell, as a
+/// reset copies the control fields into it.
+fn sync_camera_fog(
+    mut commands: Commands,
+    shm_res: Option<Res<SharedMemResource>>,
+    camera_query: Query<Entity, With<PersistentCamera>>,
+    mut applied: Local<Option<(bool, [u32; 6])>>,
+) {
+    let Some(shm_res) = shm_res else { return };
+    let shm = shm_res.0.get();
+    let gs_ctrl = &shm.game_structure_control;
+    let gs_game = &shm.game_structure_game;
+
+    // Live config, mirrored for the readback
+    let enabled = gs_ctrl.fog_enabled.load(Ordering::Relaxed);
+    gs_game.fog_enabled.store(enabled, Ordering::Relaxed);
+    let mut bits = [0u32; 6];
+    for ((ctrl, game), bits) in gs_ctrl
+        .fog_color
+        .iter()
+        .chain([&gs_ctrl.fog_start, &gs_ctrl.fog_end])
+        .zip(gs_game.fog_color.iter().chain([&gs_game.fog_start, &gs_game.fog_end]))
+        .zip(bits.iter_mut())
+    {
+        *bits = ctrl.load(Ordering::Relaxed);
+        game.store(*bits, Ordering::Relaxed);
+    }
+
+    // Only rebuild the fog when one of its fields changed since it was last applied
+    if *applied == Some((enabled, bits)) {
+        return;
+    }
+    let Ok(camera) = camera_query.single() else {
+        return;
+    };
+    *applied = Some((enabled, bits));
+    match distance_fog(gs_game) {
+        Some(fog) => {
+            info!("Distance fog updated: {:?}", fog.falloff);
+            commands.entity(camera).insert(fog);
+        }
+        None => {
+            info!("Distance fog disabled");
+            commands.entity(camera).remove::<DistanceFog>();
+        }
+    }
+}
+
+/// Maps the shared memory tonemapping index to Bevy's operator (unknown values use Bevy's default)
+fn tonemapping_from_u32(value: u32) -> Tonemapping {
+    match value {
+        0 => Tonemapping::None,
+        1 => Tonemapping::Reinhard,
+        2 => Tonemapping::ReinhardLuminance,
+        3 => Tonemapping::AcesFitted,
+        4 => Tonemapping::AgX,
+        5 => Tonemapping::SomewhatBoringDisplayTransform,
+        7 => Tonemapping::BlenderFilmic,
+        _ => Tonemapping::TonyMcMapface,
+    }
+}
+
+/// Applies bloom and tonemapping changes from the Controller to the persistent camera, without a reset
+fn sync_camera_post_processing(
+    mut commands: Commands,
+    shm_res: Option<Res<SharedMemResource>>,
+    mut camera_query: Query<(Entity, Has<Bloom>, &mut Tonemapping), With<PersistentCamera>>,
+) {
+    let Some(shm_res) = shm_res else { return };
+    let shm = shm_res.0.get();
+    let gs_ctrl = &shm.game_structure_control;
+    let gs_game = &shm.game_structure_game;
+
+    let bloom_enabled = gs_ctrl.bloom_enabled.load(Ordering::Relaxed);
+    let tonemapping_index = gs_ctrl.tonemapping.load(Ordering::Relaxed);
+    gs_game.bloom_enabled.store(bloom_enabled, Ordering::Relaxed);
+    gs_game.tonemapping.store(tonemapping_index, Ordering::Relaxed);
+
+    let Ok((camera, has_bloom, mut tonemapping)) = camera_query.single_mut() else {
+        return;
+    };
+
+    // Bloom requires HDR, drop both when disabled to restore the plain LDR pipeline
+    if bloom_enabled && !has_bloom {
+        commands.entity(camera).insert(Bloom::NATURAL);
+        info!("Bloom enabled");
+    } else if !bloom_enabled && has_bloom {
+        commands.entity(camera).remove::<(Bloom, Hdr)>();
+        info!("Bloom disabled");
+    }
+
+    let requested = tonemapping_from_u32(tonemapping_index);
+    if *tonemapping != requested {
+        *tonemapping = requested;
+        info!("Tonemapping set to {:?}", requested);
+    }
 }
 
 /// Resource tracking blank screen state
@@ -119,11 +347,11 @@ fn spawn_blank_overlay(commands: &mut Commands) {
 fn handle_reset_command(
     mut pending_reset: ResMut<PendingReset>,
     mut commands: Commands,
-    meshes: ResMut<Assets<Mesh>>,
-    materials: ResMut<Assets<StandardMaterial>>,
+    mut assets: SceneAssets,
     time: Res<Time>,
     mut frame_counter: ResMut<FrameCounterResource>,
-    camera_query: Query<&mut Transform, With<PersistentCamera>>,
+    mut frame_timing: ResMut<FrameTimingResource>,
+    camera_query: Query<(&mut Transform, &mut Projection), With<PersistentCamera>>,
     game_entities: Query<Entity, With<GameEntity>>,
     ambient_light: Option<ResMut<GlobalAmbientLight>>,
     shm_res: Option<Res<SharedMemResource>>,
@@ -131,6 +359,8 @@ fn handle_reset_command(
     spotlight_query: Query<&mut SpotLight, (Without<crate::utils::objects::HoleLight>, Without<GameEntity>)>,
     round_start: ResMut<RoundStartTimestamp>,
     mut door_win_entities: ResMut<DoorWinEntities>,
+    base_textures: Option<Res<BaseTextures>>,
+    mut session: ResMut<SessionStats>,
 ) {
     
     if !pending_reset.0 {
@@ -141,6 +371,7 @@ fn handle_reset_command(
 
     // Reset commands received
     frame_counter.0 = 0;
+    *frame_timing = FrameTimingResource::default();
 
     // Clear animation state to avoid stale entity references after despawn
     door_win_entities.animation_start_time = None;
@@ -149,16 +380,31 @@ fn handle_reset_command(
 
     // Clear is_animating flag in SHM
     if let Some(ref shm_res) = shm_res {
-        shm_res.0.get().game_structure_game.is_animating.store(false, Ordering::Relaxed);
+        let shm = shm_res.0.get();
+        shm.game_structure_game.is_animating.store(false, Ordering::Relaxed);
+
+        // A round left before being won is reported as aborted, a won one was reported at the win
+        if round_start.0.is_some() && !round_won(&shm.game_structure_game) {
+            publish_trial_result(shm, TrialResult::Aborted, &round_start, time.elapsed());
+            if !shm.game_structure_game.practice_mode.load(Ordering::Relaxed) {
+                session.aborted += 1;
+                session.win_streak = 0;
+            }
+        }
     }
 
     despawn_all_game_and_ui(commands.reborrow(), game_entities, ui_entities);
+    assets.begin_round();
+
+    // setup_round copies the Controller's layout into the game structure
+    let score_bar = shm_res.as_ref().map_or_else(ScoreBarLayout::default, |shm_res| {
+        ScoreBarLayout::from_game_structure(&shm_res.0.get().game_structure_control)
+    });
 
     // Reset shared memory game structure to default values for new round
     setup_round(
         commands.reborrow(),
-        meshes,
-        materials,
+        assets,
         camera_query,
         spotlight_query,
         ambient_light,
@@ -166,12 +412,140 @@ fn handle_reset_command(
         round_start,
         time,
         door_win_entities,
+        base_textures,
     );
 
-    spawn_score_bar(&mut commands);
+    spawn_score_bar(&mut commands, score_bar);
+
+}
+
+
+/// Re-rolls the decorations from the Controller's decoration seeds (and counts/sizes/densities/placement mode),
+/// keeping the pyramid geometry, the base and the camera untouched.
+fn handle_reroll_command(
+    pending_reroll: Res<PendingReroll>,
+    mut commands: Commands,
+    mut assets: SceneAssets,
+    shm_res: Option<Res<SharedMemResource>>,
+    face_query: Query<(Entity, &PyramidFace)>,
+    decoration_query: Query<Entity, With<FaceDecoration>>,
+) {
+    if !pending_reroll.0 {
+        return;
+    }
+    let Some(shm_res) = shm_res else { return };
+    let shm = shm_res.0.get();
+    let gs_ctrl = &shm.game_structure_control;
+    let gs_game = &shm.game_structure_game;
+
+    // Only the decoration layout fields are taken from the Controller
+    for i in 0..3 {
+        gs_game.decoration_seeds[i].store(gs_ctrl.decoration_seeds[i].load(Ordering::Relaxed), Ordering::Relaxed);
+        gs_game.decorations_count[i].store(gs_ctrl.decorations_count[i].load(Ordering::Relaxed), Ordering::Relaxed);
+        gs_game.decorations_size[i].store(gs_ctrl.decorations_size[i].load(Ordering::Relaxed), Ordering::Relaxed);
+        gs_game.decoration_density[i].store(gs_ctrl.decoration_density[i].load(Ordering::Relaxed), Ordering::Relaxed);
+    }
+    gs_game.placement_mode.store(gs_ctrl.placement_mode.load(Ordering::Relaxed), Ordering::Relaxed);
+    gs_game
+        .decoration_edge_margin_factor
+        .store(gs_ctrl.decoration_edge_margin_factor.load(Ordering::Relaxed), Ordering::Relaxed);
+    resolve_decoration_counts(gs_game);
+    let placement = DecorationPlacement::from_u32(gs_game.placement_mode.load(Ordering::Relaxed));
+    let (star_points, star_inner_ratio, depth_offset) = decoration_style(gs_game);
+
+    for entity in decoration_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    for (face_entity, face) in face_query.iter() {
+        let placed = spawn_face_decorations(
+            &mut commands,
+            &mut assets,
+            face_entity,
+            face,
+            &FaceDecorationParams {
+                seed: gs_game.decoration_seeds[face.index].load(Ordering::Relaxed),
+                count: gs_game.decorations_count[face.index].load(Ordering::Relaxed),
+                size: f32::from_bits(gs_game.decorations_size[face.index].load(Ordering::Relaxed)),
+                emissive: decoration_emissive(gs_game, face.index),
+                star_points,
+                star_inner_ratio,
+                depth_offset,
+                jitter: gs_game.decoration_jitter_enabled.load(Ordering::Relaxed),
+                placement,
+                edge_margin_factor: f32::from_bits(gs_game.decoration_edge_margin_factor.load(Ordering::Relaxed)),
+            },
+        );
+        store_placed_decorations(gs_game, face.index, placed);
+    }
 
+    info!(
+        "Decorations re-rolled with seeds {:?}",
+        gs_game.decoration_seeds.each_ref().map(|seed| seed.load(Ordering::Relaxed))
+    );
 }
 
+/// Rebuilds only the wooden base from the Controller's door count and target door, keeping the pyramid,
+/// its decorations and the camera. The target door is validated against the new count (see `door_layout`).
+/// The previous base's meshes are released with the round's at the next reset.
+#[allow(clippy::too_many_arguments)]
+fn handle_rebuild_base_command(
+    pending_rebuild: Res<PendingRebuildBase>,
+    mut commands: Commands,
+    mut assets: SceneAssets,
+    shm_res: Option<Res<SharedMemResource>>,
+    base_query: Query<(Entity, &Transform), BaseEntityFilter>,
+    mut door_win_entities: ResMut<DoorWinEntities>,
+    mut hint: ResMut<BestDoorHint>,
+    base_textures: Option<Res<BaseTextures>>,
+) {
+    if !pending_rebuild.0 {
+        return;
+    }
+    let Some(shm_res) = shm_res else { return };
+    let shm = shm_res.0.get();
+    let gs_ctrl = &shm.game_structure_control;
+    let gs_game = &shm.game_structure_game;
+
+    // The animation drives the lights of the current target door
+    if gs_game.is_animating.load(Ordering::Relaxed) {
+        info!("Rebuild base command ignored: door animation running");
+        return;
+    }
+
+    // Only the door layout fields are taken from the Controller
+    gs_game.base_nr_sides.store(gs_ctrl.base_nr_sides.load(Ordering::Relaxed), Ordering::Relaxed);
+    gs_game.target_door.store(gs_ctrl.target_door.load(Ordering::Relaxed), Ordering::Relaxed);
+    let (nr_sides, target_door) = door_layout(gs_game);
+
+    // The new base keeps the rotation the subject gave to the pyramid
+    let rotation = Quat::from_rotation_y(pyramid_yaw(base_query.iter().map(|(_, transform)| transform)));
+    for (entity, _) in base_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let orient = f32::from_bits(gs_game.start_orient.load(Ordering::Relaxed));
+    let (_, _, depth_offset) = decoration_style(gs_game);
+    let base_texture = BaseTexture::from_u32(gs_game.base_texture.load(Ordering::Relaxed));
+    let base_texture = base_textures.and_then(|textures| textures.get(base_texture));
+    let (winning_light, winning_emissive) = spawn_pyramid_base(
+        &mut commands,
+        &mut assets,
+        orient,
+        nr_sides,
+        target_door,
+        depth_offset,
+        base_texture,
+        rotation,
+        DoorOpenStyle::from_u32(gs_game.door_open_style.load(Ordering::Relaxed)),
+    );
+    door_win_entities.winning_light = winning_light;
+    door_win_entities.winning_emissive = winning_emissive;
+    door_win_entities.animation_start_time = None;
+    // The highlighted frame is gone
+    *hint = BestDoorHint::default();
+
+    info!("Base rebuilt with {} doors, target_door={}", nr_sides, target_door);
+}
 
 /// System to handle animation door command
 fn handle_animation_door_command(
@@ -179,6 +553,7 @@ fn handle_animation_door_command(
     mut door_win_entities: ResMut<DoorWinEntities>,
     shm_res: Option<Res<SharedMemResource>>,
     time: Res<Time>,
+    frame_counter: Res<FrameCounterResource>,
 ) {
     if !pending_anim.0 {
         return;
@@ -204,34 +579,90 @@ fn handle_animation_door_command(
 
     // Only start animation if we found at least one entity
     info!("Starting door animation from pre-populated entities: light={:?}, emissive={:?}", found_light, found_emissive);
-    door_win_entities.animation_start_time = Some(time.elapsed());
+    latch_door_animation_timing(&shm.game_structure_control, &shm.game_structure_game);
+    door_win_entities.start_animation(time.elapsed(), frame_counter.0);
     shm.game_structure_game
         .is_animating
         .store(true, Ordering::Relaxed);
 }
 
-/// System to apply blank screen command - spawns/despawns a black fullscreen overlay
+/// System to apply blank screen commands - spawns/despawns a black fullscreen overlay
 fn apply_blank_screen(
     mut commands: Commands,
     pending_blank: Res<PendingBlankScreen>,
+    pending_blank_state: Res<PendingBlankScreenState>,
     mut blank_state: ResMut<BlankScreenState>,
     overlay_query: Query<Entity, With<BlankScreenOverlay>>,
 ) {
-    if pending_blank.0 {
-        // Toggle blank screen state
-        blank_state.is_active = !blank_state.is_active;
-
-        if blank_state.is_active {
-            // Spawn black fullscreen overlay
-            spawn_blank_overlay(&mut commands);
-            info!("Blank screen activated");
-        } else {
-            // Despawn the overlay
-            for entity in overlay_query.iter() {
-                commands.entity(entity).despawn();
-            }
-            info!("Blank screen deactivated");
+    // Absolute state (blank_on/blank_off) takes precedence over the legacy toggle
+    let target = match pending_blank_state.0 {
+        Some(target) => target,
+        None if pending_blank.0 => {
+            info!("Blank screen toggled (prefer blank_on/blank_off for a deterministic state)");
+            !blank_state.is_active
+        }
+        None => return,
+    };
+    if target == blank_state.is_active {
+        return;
+    }
+    blank_state.is_active = target;
+
+    if blank_state.is_active {
+        // Spawn black fullscreen overlay
+        spawn_blank_overlay(&mut commands);
+        info!("Blank screen activated");
+    } else {
+        // Despawn the overlay
+        for entity in overlay_query.iter() {
+            commands.entity(entity).despawn();
         }
+        info!("Blank screen deactivated");
+    }
+}
+
+/// Frees and shows the cursor on the `release_cursor` command (the startup grab is not restored)
+fn apply_release_cursor(
+    pending_release: Res<PendingReleaseCursor>,
+    mut cursor_query: Query<&mut CursorOptions>,
+) {
+    if !pending_release.0 {
+        return;
+    }
+    for mut cursor in cursor_query.iter_mut() {
+        cursor.grab_mode = CursorGrabMode::None;
+        cursor.visible = true;
+    }
+    info!("Cursor released");
+}
+
+/// Applies the Controller's present mode to the primary window when it requests a new one,
+/// so a mode set meanwhile with the debug vsync key is kept until then.
+fn apply_present_mode(
+    shm_res: Option<Res<SharedMemResource>>,
+    mut window: Single<&mut Window, With<PrimaryWindow>>,
+    mut last_request: Local<u32>,
+) {
+    let Some(shm_res) = shm_res else { return };
+    let request = shm_res.0.get().commands.present_mode.load(Ordering::Relaxed);
+    if request == *last_request {
+        return;
+    }
+    *last_request = request;
+
+    let present_mode = match PresentModeCommand::from_u32(request) {
+        PresentModeCommand::Unchanged => return,
+        PresentModeCommand::AutoVsync => PresentMode::AutoVsync,
+        PresentModeCommand::AutoNoVsync => PresentMode::AutoNoVsync,
+        PresentModeCommand::Immediate => PresentMode::Immediate,
+    };
+    window.present_mode = present_mode;
+
+    // The browser paces the canvas with requestAnimationFrame, whatever the present mode
+    if cfg!(target_arch = "wasm32") {
+        warn!("Present mode set to {:?}, the browser may ignore it and keep vsync", present_mode);
+    } else {
+        info!("Present mode set to {:?}", present_mode);
     }
 }
 
@@ -255,6 +686,62 @@ fn handle_rendering_pause(
     }
 }
 
+/// Applies ambient light color/brightness changes from the Controller immediately, without a reset
+fn sync_ambient_light(
+    shm_res: Option<Res<SharedMemResource>>,
+    ambient_light: Option<ResMut<GlobalAmbientLight>>,
+) {
+    let Some(shm_res) = shm_res else { return };
+    let Some(mut ambient) = ambient_light else { return };
+    let shm = shm_res.0.get();
+    let gs_ctrl = &shm.game_structure_control;
+    let gs_game = &shm.game_structure_game;
+
+    let mut changed = false;
+    let brightness_bits = gs_ctrl.ambient_brightness.load(Ordering::Relaxed);
+    if brightness_bits != gs_game.ambient_brightness.load(Ordering::Relaxed) {
+        gs_game.ambient_brightness.store(brightness_bits, Ordering::Relaxed);
+        changed = true;
+    }
+    for i in 0..3 {
+        let channel_bits = gs_ctrl.ambient_color[i].load(Ordering::Relaxed);
+        if channel_bits != gs_game.ambient_color[i].load(Ordering::Relaxed) {
+            gs_game.ambient_color[i].store(channel_bits, Ordering::Relaxed);
+            changed = true;
+        }
+    }
+
+    if changed {
+        ambient.brightness = f32::from_bits(brightness_bits) * brightness_gain(gs_game);
+        ambient.color = ambient_color(gs_game);
+        info!("Ambient light updated: brightness={}, color={:?}", ambient.brightness, ambient.color);
+    }
+}
+
+/// Shows or hides the curved backdrop as configured by the Controller.
+/// The backdrop is spawned at Startup before shared memory attaches, so the config is applied here.
+fn sync_backdrop_visibility(
+    shm_res: Option<Res<SharedMemResource>>,
+    mut backdrop_query: Query<&mut Visibility, With<Backdrop>>,
+) {
+    let Some(shm_res) = shm_res else { return };
+    let shm = shm_res.0.get();
+    let show_backdrop = shm.game_structure_control.show_backdrop.load(Ordering::Relaxed);
+    shm.game_structure_game
+        .show_backdrop
+        .store(show_backdrop, Ordering::Relaxed);
+
+    let requested = if show_backdrop {
+        Visibility::Visible
+    } else {
+        Visibility::Hidden
+    };
+    for mut visibility in backdrop_query.iter_mut() {
+        // Only write on change to keep change detection quiet
+        visibility.set_if_neq(requested);
+    }
+}
+
 /// Despawn all game and UI entities
 fn despawn_all_game_and_ui(
     mut commands: Commands,
@@ -0,0 +1,207 @@
+//! Startup window and logging configuration, read from environment variables.
+//!
+//! These are read in `main` before the app installs its logger, so invalid values are collected and
+//! logged by `log_config_warnings` at startup.
+use std::sync::Mutex;
+
+use bevy::log::{Level, LogPlugin};
+use bevy::prelude::*;
+use bevy::window::{
+    CursorGrabMode, CursorOptions, MonitorSelection, PresentMode, WindowMode, WindowPosition,
+    WindowResolution,
+};
+use shared::constants::game_constants::{
+    CURSOR_GRAB_ENV_VAR, CURSOR_VISIBLE_ENV_VAR, LOG_LEVEL_ENV_VAR, RENDER_SCALE_ENV_VAR,
+    WINDOW_MONITOR_ENV_VAR, WINDOW_POSITION_ENV_VAR, WINDOW_RESOLUTION_ENV_VAR, WINDOW_TITLE,
+    WINDOW_TITLE_ENV_VAR,
+};
+use shared::constants::render_constants::{MIN_RENDER_SCALE, RENDER_SCALE};
+
+/// Primary window, from `MONKEY_WINDOW_TITLE`, `MONKEY_WINDOW_MONITOR`, `MONKEY_WINDOW_RESOLUTION` and
+/// `MONKEY_WINDOW_POSITION`. Defaults to borderless fullscreen on the primary monitor (on native).
+pub fn primary_window() -> Window {
+    let title = std::env::var(WINDOW_TITLE_ENV_VAR).unwrap_or_else(|_| WINDOW_TITLE.into());
+
+    let monitor = parse_env(WINDOW_MONITOR_ENV_VAR, |value| value.parse::<usize>().ok())
+        .map_or(MonitorSelection::Primary, MonitorSelection::Index);
+    let resolution = parse_env(WINDOW_RESOLUTION_ENV_VAR, |value| {
+        let (width, height) = value.split_once('x')?;
+        Some(WindowResolution::new(width.trim().parse().ok()?, height.trim().parse().ok()?))
+    });
+    let position = parse_env(WINDOW_POSITION_ENV_VAR, |value| {
+        let (x, y) = value.split_once(',')?;
+        Some(IVec2::new(x.trim().parse().ok()?, y.trim().parse().ok()?))
+    });
+
+    // A resolution switches to a windowed window, centered on the monitor unless positioned
+    let (mode, resolution, position) = match resolution {
+        Some(resolution) => (
+            WindowMode::Windowed,
+            resolution,
+            position.map_or(WindowPosition::Centered(monitor), WindowPosition::At),
+        ),
+        None => {
+            if position.is_some() {
+                config_warning(format!(
+                    "{} is ignored in fullscreen, set {}",
+                    WINDOW_POSITION_ENV_VAR, WINDOW_RESOLUTION_ENV_VAR
+                ));
+            }
+            (
+                WindowMode::BorderlessFullscreen(monitor),
+                WindowResolution::default(),
+                WindowPosition::Automatic,
+            )
+        }
+    };
+
+    Window {
+        title,
+        #[cfg(target_arch = "wasm32")]
+        canvas: Some("#game-canvas".into()),
+        fit_canvas_to_parent: true,
+        prevent_default_event_handling: true,
+        // The web canvas follows its parent element
+        mode: if cfg!(target_arch = "wasm32") { WindowMode::Windowed } else { mode },
+        resolution,
+        position,
+        present_mode: PresentMode::AutoVsync,
+        ..default()
+    }
+}
+
+/// Cursor grab mode and visibility, from `MONKEY_CURSOR_GRAB` and `MONKEY_CURSOR_VISIBLE`.
+/// Defaults to a locked, hidden cursor on native and a free, visible one on the web.
+pub fn cursor_options() -> CursorOptions {
+    let default_grab = if cfg!(target_arch = "wasm32") {
+        CursorGrabMode::None
+    } else {
+        CursorGrabMode::Locked
+    };
+
+    let grab_mode = match env_var(CURSOR_GRAB_ENV_VAR).as_deref() {
+        None => default_grab,
+        Some("locked") => CursorGrabMode::Locked,
+        Some("confined") => CursorGrabMode::Confined,
+        Some("none") => CursorGrabMode::None,
+        Some(other) => {
+            config_warning(format!(
+                "{}={} is not one of locked, confined or none, using {:?}",
+                CURSOR_GRAB_ENV_VAR, other, default_grab
+            ));
+            default_grab
+        }
+    };
+
+    let default_visible = grab_mode == CursorGrabMode::None;
+    let visible = match env_var(CURSOR_VISIBLE_ENV_VAR).as_deref() {
+        None => default_visible,
+        Some("1" | "true") => true,
+        Some("0" | "false") => false,
+        Some(other) => {
+            config_warning(format!(
+                "{}={} is not a boolean, using {}",
+                CURSOR_VISIBLE_ENV_VAR, other, default_visible
+            ));
+            default_visible
+        }
+    };
+
+    CursorOptions {
+        grab_mode,
+        visible,
+        ..default()
+    }
+}
+
+/// Resolution scale of the 3D scene, from `MONKEY_RENDER_SCALE`. Defaults to `RENDER_SCALE`.
+pub fn render_scale() -> f32 {
+    parse_env(RENDER_SCALE_ENV_VAR, |value| value.parse::<f32>().ok().and_then(clamp_render_scale))
+        .unwrap_or(RENDER_SCALE)
+}
+
+/// Clamps a render scale to [`MIN_RENDER_SCALE`, 1.0], `None` when it is not a positive number.
+pub fn clamp_render_scale(scale: f32) -> Option<f32> {
+    (scale.is_finite() && scale > 0.0).then(|| scale.clamp(MIN_RENDER_SCALE, 1.0))
+}
+
+/// Bevy's log plugin, with the level of the game's own messages from `MONKEY_LOG_LEVEL` added to the
+/// default filter. The engine's messages keep their level, and `RUST_LOG` still replaces the whole filter.
+pub fn log_plugin() -> LogPlugin {
+    let plugin = LogPlugin::default();
+    match parse_env(LOG_LEVEL_ENV_VAR, parse_log_level) {
+        Some(level) => LogPlugin {
+            filter: format!("{},game_node={}", plugin.filter, level.as_str().to_lowercase()),
+            ..plugin
+        },
+        None => plugin,
+    }
+}
+
+/// Log level named by `value`. There is no "off": errors always show.
+pub fn parse_log_level(value: &str) -> Option<Level> {
+    match value {
+        "error" => Some(Level::ERROR),
+        "warn" | "warning" => Some(Level::WARN),
+        "info" => Some(Level::INFO),
+        "debug" => Some(Level::DEBUG),
+        "trace" => Some(Level::TRACE),
+        _ => None,
+    }
+}
+
+/// Warnings about the configuration, kept until the logger is installed.
+static CONFIG_WARNINGS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+fn config_warning(message: String) {
+    if let Ok(mut warnings) = CONFIG_WARNINGS.lock() {
+        warnings.push(message);
+    }
+}
+
+/// Startup system logging the warnings of the configuration read before the app started.
+pub fn log_config_warnings() {
+    let warnings = CONFIG_WARNINGS.lock().map(|mut warnings| std::mem::take(&mut *warnings));
+    for message in warnings.unwrap_or_default() {
+        warn!("{}", message);
+    }
+}
+
+/// Lowercased value of an environment variable (always unset on the web).
+fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok().map(|value| value.trim().to_lowercase())
+}
+
+/// Parses an environment variable, warning and falling back to `None` when the value is invalid.
+fn parse_env<T>(name: &str, parse: impl FnOnce(&str) -> Option<T>) -> Option<T> {
+    let value = env_var(name)?;
+    let parsed = parse(&value);
+    if parsed.is_none() {
+        config_warning(format!("Invalid value {}={}, using the default", name, value));
+    }
+    parsed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_levels_never_hide_errors() {
+        assert_eq!(parse_log_level("warn"), Some(Level::WARN));
+        assert_eq!(parse_log_level("trace"), Some(Level::TRACE));
+        assert_eq!(parse_log_level("off"), None);
+        assert_eq!(parse_log_level("loud"), None);
+    }
+
+    #[test]
+    fn invalid_values_are_kept_for_the_logger() {
+        let name = "MONKEY_WINDOW_CONFIG_TEST";
+        std::env::set_var(name, "not a number");
+        assert_eq!(parse_env(name, |value| value.parse::<u32>().ok()), None);
+        std::env::remove_var(name);
+
+        let warnings = CONFIG_WARNINGS.lock().unwrap();
+        assert!(warnings.iter().any(|message| message.contains(name)));
+    }
+}
@@ -1,23 +1,50 @@
 use bevy::prelude::*;
 
+use crate::command_handler::{apply_input_lockout, read_shared_memory, CommandFrame, PendingCommands};
+
 #[cfg(target_arch = "wasm32")]
 use crate::command_handler::SharedMemResource;
 
 #[cfg(target_arch = "wasm32")]
-use shared::open_shared_memory;
+use shared::{allocate_shared_memory, is_cross_origin_isolated, open_shared_memory};
+
+/// Single-process mode of the web build, when the page cannot share memory with a Controller
+/// (no cross-origin isolation, so no SharedArrayBuffer): the game is driven by the keyboard instead.
+#[derive(Resource)]
+pub struct StandaloneMode;
 
 /// Plugin to add wasm shared memory to the Bevy systems
 pub struct WebAdapterPlugin;
 
 impl Plugin for WebAdapterPlugin {
-    fn build(&self, _app: &mut App) {
+    fn build(&self, app: &mut App) {
         #[cfg(target_arch = "wasm32")]
-        _app.add_systems(Startup, init_web_shm);
+        app.add_systems(Startup, init_web_shm);
+
+        // Like the replay, after the shared memory read so the keyboard adds to it
+        app.add_systems(
+            PreUpdate,
+            apply_standalone_keyboard
+                .after(read_shared_memory)
+                .before(apply_input_lockout)
+                .run_if(resource_exists::<StandaloneMode>),
+        );
     }
 }
 
 #[cfg(target_arch = "wasm32")]
 fn init_web_shm(mut commands: Commands) {
+    if !is_cross_origin_isolated() {
+        // The memory still lives in the wasm heap, only JS cannot view it: allocate it here instead,
+        // without the console error `create_shared_memory_wasm` gives a frontend expecting a view
+        allocate_shared_memory();
+        warn!(
+            "SharedArrayBuffer unavailable (page not cross-origin isolated): running standalone without a Controller. \
+             Keys: Left/Right rotate, Up/Down zoom, Home recenters the camera, Space checks the alignment, Enter starts a new round."
+        );
+        commands.insert_resource(StandaloneMode);
+    }
+
     match open_shared_memory("monkey_game") {
         Ok(handle) => {
              info!("Web Shared Memory attached.");
@@ -28,3 +55,29 @@ fn init_web_shm(mut commands: Commands) {
         }
     }
 }
+
+/// Commands of the standalone keyboard scheme for this frame.
+/// `first_frame` starts the first round, which the Controller would otherwise request.
+pub fn standalone_keyboard_frame(keys: &ButtonInput<KeyCode>, first_frame: bool) -> CommandFrame {
+    CommandFrame {
+        rotate_left: keys.pressed(KeyCode::ArrowLeft),
+        rotate_right: keys.pressed(KeyCode::ArrowRight),
+        zoom_in: keys.pressed(KeyCode::ArrowUp),
+        zoom_out: keys.pressed(KeyCode::ArrowDown),
+        check_alignment: keys.just_pressed(KeyCode::Space),
+        reset: first_frame || keys.just_pressed(KeyCode::Enter),
+        recenter: keys.just_pressed(KeyCode::Home),
+        ..default()
+    }
+}
+
+/// Turns the keyboard into pending commands in `StandaloneMode`.
+fn apply_standalone_keyboard(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut pending: PendingCommands,
+    mut started: Local<bool>,
+) {
+    let frame = standalone_keyboard_frame(&keys, !*started);
+    *started = true;
+    pending.apply(&frame);
+}
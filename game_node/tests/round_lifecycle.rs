@@ -0,0 +1,78 @@
+//! End-to-end round lifecycle: the Controller writes a config and commands in shared memory, the game
+//! plugins run headless, and the game structure is read back as the Controller would.
+use std::sync::atomic::Ordering;
+
+use bevy::prelude::*;
+use game_node::{
+    command_handler::CommandHandlerPlugin,
+    state_emitter::StateEmitterPlugin,
+    utils::{
+        objects::{DoorWinEntities, PyramidFace, RoundStartTimestamp},
+        systems_logic::SystemsLogicPlugin,
+    },
+};
+use shared::constants::game_constants::{RESET_INPUT_LOCKOUT_FRAMES, SHM_DIR_ENV_VAR};
+use shared::open_shared_memory;
+
+/// Game app without window or rendering, with the plugins of `main` that drive the round
+fn headless_game_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default(), bevy::mesh::MeshPlugin, bevy::input::InputPlugin))
+        .init_asset::<StandardMaterial>()
+        .init_asset::<Image>()
+        .init_resource::<UiScale>()
+        .add_plugins((CommandHandlerPlugin, SystemsLogicPlugin, StateEmitterPlugin))
+        .insert_resource(DoorWinEntities::default())
+        .insert_resource(RoundStartTimestamp::default());
+    app
+}
+
+#[test]
+fn round_lifecycle_updates_the_game_structure() {
+    // The game creates its memory at startup: keep it away from a game running on this machine
+    let dir = std::env::temp_dir().join(format!("monkey_round_lifecycle_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::env::set_var(SHM_DIR_ENV_VAR, &dir);
+
+    let mut app = headless_game_app();
+    app.update();
+    let controller = open_shared_memory("monkey_game").unwrap();
+    let shm = controller.get();
+    let gs_game = &shm.game_structure_game;
+    assert_eq!(gs_game.round_index.load(Ordering::Relaxed), 0);
+    assert!(!gs_game.scene_valid.load(Ordering::Relaxed));
+
+    // Config, then a reset held for one frame
+    shm.game_structure_control.decorations_count[0].store(5, Ordering::Relaxed);
+    shm.game_structure_control.target_door.store(1, Ordering::Relaxed);
+    shm.game_structure_control.bump_config_epoch();
+    shm.commands.reset.store(true, Ordering::Release);
+    app.update();
+    shm.commands.reset.store(false, Ordering::Release);
+    for _ in 0..RESET_INPUT_LOCKOUT_FRAMES {
+        app.update();
+    }
+
+    let world = app.world_mut();
+    assert_eq!(world.query::<&PyramidFace>().iter(world).count(), 3);
+    assert_eq!(gs_game.round_index.load(Ordering::Relaxed), 1);
+    assert_eq!(gs_game.target_door.load(Ordering::Relaxed), 1);
+    assert!(shm.config_is_current());
+    assert!(gs_game.scene_valid.load(Ordering::Relaxed));
+    assert!(gs_game.decorations_placed[0].load(Ordering::Relaxed) > 0);
+    let frame_number = gs_game.frame_number.load(Ordering::Relaxed);
+
+    // An alignment check, held for one frame
+    let attempts = gs_game.attempts.load(Ordering::Relaxed);
+    shm.commands.check_alignment.store(true, Ordering::Relaxed);
+    app.update();
+    shm.commands.check_alignment.store(false, Ordering::Relaxed);
+    app.update();
+
+    assert_eq!(gs_game.attempts.load(Ordering::Relaxed), attempts + 1);
+    assert!(f32::from_bits(gs_game.current_alignment.load(Ordering::Relaxed)).is_finite());
+    assert!(gs_game.frame_number.load(Ordering::Relaxed) >= frame_number);
+
+    drop(app);
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -4,10 +4,43 @@
 pub mod game_constants {
     pub const REFRESH_RATE_HZ: f64 = 60.0; // Hz
 
-    pub const UNLOCK_SOL_NR: usize = 3; // Number of consecutive correct disalignments to unlock
+    // Timing integrity: warn when wall-clock time drifts this far from frame_number / REFRESH_RATE_HZ
+    pub const FRAME_DRIFT_WARN_MS: f32 = 100.0;
+
+    // Low-power mode: after this many frames without commands, input or animation the update rate
+    // drops to IDLE_UPDATE_HZ until the next command (0 disables it). A command arriving while idle
+    // is picked up with up to 1 / IDLE_UPDATE_HZ of latency, so keep it disabled for timed trials.
+    pub const IDLE_TIMEOUT_FRAMES: u32 = 0;
+    pub const IDLE_UPDATE_HZ: f64 = 10.0;
+
+    // Frame time statistics (mean, p95, p99) are computed over and written once per this period
+    pub const FRAME_TIME_STATS_PERIOD_SECS: f64 = 1.0;
+
+    // Frames after a reset during which check commands are ignored, so a check meant for the previous
+    // round (e.g. sent together with the reset) is not counted against the new one
+    pub const RESET_INPUT_LOCKOUT_FRAMES: u32 = 3;
+
+    // Consecutive correct checks needed to win the round, a wrong check restarts the count (1 = first correct check)
+    pub const CONSECUTIVE_REQUIRED: u32 = 1;
+
+    // target_screen_x/y while the target door center is not in the viewport (or before the first frame)
+    pub const TARGET_SCREEN_OFFSCREEN: f32 = -1.0;
 
     // Cosine alignment with door to win
     pub const COSINE_ALIGNMENT_TO_WIN: f32 = 0.95; // approx ~8 degrees
+    // Alignment with the target door above which the subject counts as oriented towards it (target_engaged)
+    pub const ENGAGEMENT_THRESHOLD: f32 = 0.5; // approx ~60 degrees
+    // Door alignments closer than this are a tie, won by the lowest door index
+    pub const ALIGNMENT_TIE_EPSILON: f32 = 1e-5;
+
+    // Attempts counter: value at round start and which checks increment it
+    // (0 = AttemptsPolicy::AllChecks, 1 = AttemptsPolicy::FailuresOnly)
+    pub const ATTEMPTS_BASE: u32 = 0;
+    pub const ATTEMPTS_INCREMENT_POLICY: u32 = 0;
+
+    // Practice mode for subject familiarization: the target door stays faintly lit and the checks are
+    // counted in the practice_* fields instead of attempts / win_time
+    pub const PRACTICE_MODE: bool = false;
 
     // Seeds for the random number generator, one per face.
     // If two faces share the same seed (and same count/size), they get identical decorations.
@@ -21,9 +54,67 @@ pub mod game_constants {
     pub const SCORE_BAR_HEIGHT: f32 = 20.0; // pixels (scaled by UiScale)
     pub const SCORE_BAR_TOP_OFFSET: f32 = 50.0; // pixels from top (scaled by UiScale)
     pub const SCORE_BAR_BORDER_THICKNESS: f32 = 2.0; // pixels (scaled by UiScale)
+    pub const SCORE_BAR_ORIENTATION: u32 = 0; // ScoreBarOrientation::LeftToRight
+    pub const SCORE_BAR_SEGMENTS: u32 = 0; // 0 = continuous fill
+    pub const SCORE_BAR_MAX_SEGMENTS: u32 = 50; // more are clamped, they would be thinner than their gaps
+
+    // Fixation cross at screen center (drawn above the scene, below the score bar)
+    pub const SHOW_FIXATION: bool = false;
+    pub const FIXATION_SIZE: f32 = 24.0; // pixels per arm length (scaled by UiScale)
+    pub const FIXATION_THICKNESS_RATIO: f32 = 0.125; // bar thickness relative to the size
+    pub const FIXATION_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
+    // Feedback on the door the camera points at: its frame glows with this emission (linear RGB).
+    // Off for real experiments, it tells the subject which door a check would be judged against
+    pub const SHOW_BEST_DOOR_HINT: bool = false;
+    pub const BEST_DOOR_HINT_EMISSIVE: [f32; 3] = [0.25, 0.22, 0.12];
+
+    // Count of the decorations on faces turned towards the camera, emitted every frame when enabled
+    pub const EMIT_VISIBLE_DECORATIONS: bool = false;
+
+    // Animations timed by the logic frames counted since they started (frames / REFRESH_RATE_HZ)
+    // instead of the wall clock, so a recorded session replays with the same frames at any frame rate
+    pub const DETERMINISTIC_TIMING: bool = false;
+
+    // Hands-free sessions: a won round starts the next one by itself, AUTO_RESET_DELAY_SECS after the
+    // reveal animation ends, as a reset command would
+    pub const AUTO_RESET_AFTER_RESULT: bool = false;
+    pub const AUTO_RESET_DELAY_SECS: f32 = 1.0;
 
     // Loading screen duration in seconds (time for scene to render/stabilize)
     pub const LOADING_DURATION_SECS: f32 = 0.3;
+
+    // Environment variable with the path of the per-round config JSONL log (disabled when unset)
+    pub const ROUND_LOG_ENV_VAR: &str = "MONKEY_ROUND_LOG";
+
+    // Environment variables with the path of the command stream to record / replay (disabled when unset)
+    pub const COMMAND_RECORD_ENV_VAR: &str = "MONKEY_RECORD_COMMANDS";
+    pub const COMMAND_REPLAY_ENV_VAR: &str = "MONKEY_REPLAY_COMMANDS";
+
+    // Environment variable with the directory of the shared memory files (OS temp dir when unset or empty).
+    // The game and the Python controller only share memory when both run with the same value
+    pub const SHM_DIR_ENV_VAR: &str = "MONKEY_SHM_DIR";
+
+    // Environment variables for the cursor: grab mode ("locked", "confined" or "none") and visibility
+    // ("1"/"true" or "0"/"false"). Unset: locked and hidden on native, free and visible on the web.
+    pub const CURSOR_GRAB_ENV_VAR: &str = "MONKEY_CURSOR_GRAB";
+    pub const CURSOR_VISIBLE_ENV_VAR: &str = "MONKEY_CURSOR_VISIBLE";
+
+    // Environment variables for the window: title, monitor index, windowed resolution ("1280x720",
+    // unset keeps borderless fullscreen) and position of the windowed window ("x,y" on the desktop).
+    pub const WINDOW_TITLE: &str = "Monkey 3D Game";
+    pub const WINDOW_TITLE_ENV_VAR: &str = "MONKEY_WINDOW_TITLE";
+    pub const WINDOW_MONITOR_ENV_VAR: &str = "MONKEY_WINDOW_MONITOR";
+    pub const WINDOW_RESOLUTION_ENV_VAR: &str = "MONKEY_WINDOW_RESOLUTION";
+    pub const WINDOW_POSITION_ENV_VAR: &str = "MONKEY_WINDOW_POSITION";
+
+    // Environment variable for the resolution scale of the 3D scene (e.g. "0.5" renders it at half the
+    // window resolution, upscaled to the window). The UI is always drawn at the window resolution.
+    pub const RENDER_SCALE_ENV_VAR: &str = "MONKEY_RENDER_SCALE";
+
+    // Environment variable for the level of the game's own log messages ("error", "warn", "info",
+    // "debug" or "trace"), e.g. "warn" for quiet sessions. Bevy's logs and RUST_LOG are left as they are
+    pub const LOG_LEVEL_ENV_VAR: &str = "MONKEY_LOG_LEVEL";
 }
 
 /// 3D camera
@@ -34,18 +125,36 @@ pub mod camera_3d_constants {
 
     pub const CAMERA_3D_INITIAL_RADIUS: f32 = 15.0; 
 
-    pub const CAMERA_3D_SPEED_ROTATE: f32 = 0.05;
-    pub const CAMERA_3D_SPEED_ZOOM: f32 = 0.10;
+    // Rotation (degrees/s) and zoom (radius units/s) speeds while a rotate or zoom command is held,
+    // scaled by the frame time: 0.05 rad and 0.1 per frame at 60 Hz, whatever the refresh rate
+    pub const ROTATE_SPEED_DPS: f32 = 3.0 * 180.0 / std::f32::consts::PI;
+    pub const ZOOM_SPEED_UPS: f32 = 6.0;
+    // Longest frame time (in logic frames) a held command is scaled by, so a hitch does not make the camera jump
+    pub const HELD_COMMAND_MAX_DELTA_FRAMES: f32 = 3.0;
 
     // Radius range for the camera's orbit.
     pub const CAMERA_3D_MIN_RADIUS: f32 = 12.0;
     pub const CAMERA_3D_MAX_RADIUS: f32 = 20.0;
+
+    // Camera control scheme: 0 = Orbit (task), 1 = FreeFly (developer inspection)
+    pub const CAMERA_MODE: u32 = 0;
+    // Zoom behavior: 0 = Dolly (moves along the orbit radius), 1 = FovZoom (changes the field of view, fixed distance)
+    pub const ZOOM_MODE: u32 = 0;
+    // Vertical field of view range (radians) of the FovZoom mode
+    pub const CAMERA_3D_MIN_FOV: f32 = 0.2;
+    pub const CAMERA_3D_MAX_FOV: f32 = 1.4;
+    // Free-fly movement speed (units/s) and look speed (rad/s)
+    pub const CAMERA_3D_FREE_FLY_SPEED: f32 = 5.0;
+    pub const CAMERA_3D_FREE_FLY_LOOK_SPEED: f32 = 1.5;
 }
 
 /// Game objects
 pub mod object_constants {
     // Y position from the ground plane.
     pub const GROUND_Y: f32 = 0.0;
+
+    // Whether the curved backdrop behind the pyramid is shown
+    pub const SHOW_BACKDROP: bool = true;
 }
 
 /// Pyramid object
@@ -54,6 +163,9 @@ pub mod pyramid_constants {
     pub const PYRAMID_BASE_RADIUS: f32 = 2.5;
     pub const PYRAMID_HEIGHT: f32 = 4.0;
     pub const PYRAMID_START_ANGLE_OFFSET_RAD: f32 = 0.0;
+    // Turn the pyramid and its base by a random yaw at the start of each round (drawn from the first
+    // decoration seed and the round index), instead of always starting from the same viewpoint
+    pub const RANDOM_START_ORIENTATION: bool = false;
 
     // Angle's offset for the pyramid's base in radians from the camera
     pub const PYRAMID_ANGLE_OFFSET_RAD_MIN: f32 = 0.0 * (std::f32::consts::PI / 180.0);
@@ -61,6 +173,8 @@ pub mod pyramid_constants {
 
     // Angle increment of each side of the pyramid's base in radians
     pub const PYRAMID_ANGLE_INCREMENT_RAD: f32 = 120.0 * (std::f32::consts::PI / 180.0);
+    // Largest distance (world units) of the centroid of the pyramid corners from the origin before it is recentered
+    pub const PYRAMID_CENTER_EPSILON: f32 = 1e-4;
 
     pub const PYRAMID_COLORS: [[f32; 4]; 3] = [
     [1.0, 0.0, 0.0, 1.0], // red, green, blue, alpha
@@ -68,6 +182,24 @@ pub mod pyramid_constants {
     [0.0, 0.0, 1.0, 1.0], // blue
     ];
 
+    // Built-in face color sets selected with palette_index (PALETTE_INDEX_NONE uses the explicit colors)
+    //  0: red / green / blue (same as PYRAMID_COLORS)
+    //  1: yellow / cyan / magenta
+    //  2: white / grey / black
+    //  3: orange / purple / teal
+    //  4: three shades of blue (low contrast)
+    pub const COLOR_PALETTE: [[[f32; 4]; 3]; 5] = [
+        [[1.0, 0.0, 0.0, 1.0], [0.0, 1.0, 0.0, 1.0], [0.0, 0.0, 1.0, 1.0]],
+        [[1.0, 1.0, 0.0, 1.0], [0.0, 1.0, 1.0, 1.0], [1.0, 0.0, 1.0, 1.0]],
+        [[1.0, 1.0, 1.0, 1.0], [0.5, 0.5, 0.5, 1.0], [0.05, 0.05, 0.05, 1.0]],
+        [[1.0, 0.5, 0.0, 1.0], [0.5, 0.0, 0.8, 1.0], [0.0, 0.5, 0.5, 1.0]],
+        [[0.2, 0.3, 0.9, 1.0], [0.3, 0.45, 0.95, 1.0], [0.4, 0.6, 1.0, 1.0]],
+    ];
+    pub const PALETTE_INDEX_NONE: u32 = u32::MAX;
+    pub const PALETTE_INDEX: u32 = PALETTE_INDEX_NONE;
+    // Color space of the face colors: 0 sRGB, 1 linear light (see ColorSpace)
+    pub const COLOR_SPACE: u32 = 0;
+
     // Number of decorations on each pyramid side
     pub const PYRAMID_DECORATIONS_COUNT: [u32; 3] = [
         50,
@@ -81,16 +213,41 @@ pub mod pyramid_constants {
         0.3,
     ];
 
+    // Density mode: decorations per unit of face area, per face (0 keeps PYRAMID_DECORATIONS_COUNT).
+    // The count is then derived from the face area at reset, so the density looks the same whatever
+    // the pyramid size
+    pub const PYRAMID_DECORATIONS_DENSITY: [f32; 3] = [0.0, 0.0, 0.0];
+
     // Index of the target door of the pyramid
     pub const PYRAMID_TARGET_DOOR_INDEX: usize = 0;
 
     // Decorations
     pub const DECORATION_COUNT: u32 = 50;
+    // Star decorations: number of points and inner/outer radius ratio
+    pub const DECORATION_STAR_POINTS: u32 = 5;
+    pub const DECORATION_STAR_POINTS_MIN: u32 = 3;
+    pub const DECORATION_STAR_POINTS_MAX: u32 = 12;
+    pub const DECORATION_STAR_INNER_RATIO: f32 = 0.4;
+    // Offset along the face normal applied to decorations and the door glow to prevent z-fighting.
+    // Too small and they flicker at the far end of the zoom range, too large and they visibly float.
+    pub const DECORATION_DEPTH_OFFSET: f32 = 0.01;
+    // Minimum distance between decoration centers, and by default from a center to the triangle edges, in decoration sizes
+    pub const DECORATION_SPACING: f32 = 2.4;
+    pub const DECORATION_EDGE_MARGIN: f32 = 1.5; // Default of decoration_edge_margin_factor
+    // Random in-plane rotation of every decoration, drawn from the face seed (off keeps them upright)
+    pub const DECORATION_JITTER_ENABLED: bool = false;
+    // Decoration layout: 0 Poisson sampling, 1 triangular grid (see DecorationPlacement)
+    pub const PLACEMENT_MODE: u32 = 0;
+    // Decoration glow in their own color per face, for low-luminance experiments (0 keeps them lit only)
+    pub const DECORATION_EMISSIVE: [f32; 3] = [0.0, 0.0, 0.0];
     // Wooden base
     pub const BASE_HEIGHT: f32 = 0.3;
     pub const BASE_RADIUS: f32 = PYRAMID_BASE_RADIUS * 2.0;
     pub const BASE_COLOR: [f32; 4] = [0.59, 0.29, 0.00, 1.0]; // brown
     pub const BASE_NR_SIDES: usize = 6; // multiple of 3
+    pub const BASE_NR_SIDES_MAX: usize = 12; // narrower sides leave no room for the door holes
+    pub const BASE_TEXTURE: u32 = 0; // BaseTexture::SolidColor
+    pub const BASE_TEXTURE_SIZE: u32 = 256; // pixels per side of the procedural base textures
     pub const BASE_HOLES_LIGHT_Y_OFFSET: f32 = 0.0; // Y offset of the light holes from the Y of the holes itself
     pub const BASE_HOLES_LIGHT_OFFSET_CENTER: f32 = -0.4; // Offset of the light holes from the normal of center of the hole
 
@@ -99,6 +256,9 @@ pub mod pyramid_constants {
     pub const DOOR_ANIM_FADE_OUT: f32 = 0.5; // seconds
     pub const DOOR_ANIM_STAY_OPEN: f32 = 0.5; // seconds
     pub const DOOR_ANIM_FADE_IN: f32 = 0.5; // seconds
+    pub const DOOR_OPEN_STYLE: u32 = 0; // DoorOpenStyle::LightOnly
+    pub const DOOR_SWING_ANGLE_DEG: f32 = 100.0; // Swing of a fully open door leaf around its hinge
+    pub const DOOR_SLIDE_PULL_RATIO: f32 = 0.25; // Sliding door leaf pulled out of the hole by this fraction of its radius before sliding aside
 }
 
 /// Lighting constants
@@ -111,9 +271,43 @@ pub mod lighting_constants {
 
     pub const SPOTLIGHT_LIGHT_INTENSITY: f32 = 5_000_000.0;
     pub const GLOBAL_AMBIENT_LIGHT_INTENSITY: f32 = 200.0;
+    pub const GLOBAL_AMBIENT_LIGHT_COLOR: [f32; 3] = [1.0, 1.0, 1.0]; // sRGB, white
     pub const MAX_SPOTLIGHT_INTENSITY: f32 = 1000000.0;
+    // Calibration multiplier of the face colors, ambient and spotlights, to match a luminance target per monitor
+    pub const BRIGHTNESS_GAIN: f32 = 1.0;
+    // Resting intensity of the target door light in practice mode
+    pub const PRACTICE_TARGET_LIGHT_INTENSITY: f32 = 100000.0;
+    // Intensity the target door light keeps for the whole round, to ease the task (0 = dark until revealed)
+    pub const TARGET_HINT_INTENSITY: f32 = 0.0;
+    // Breathing of that resting light: intensity + depth * sin(2π * hz * t), clamped at 0 (0 Hz = steady)
+    pub const HINT_PULSE_HZ: f32 = 0.0;
+    pub const HINT_PULSE_DEPTH: f32 = 0.0; // same unit as the intensity
 }
 
+/// Post-processing constants
+pub mod render_constants {
+    // Bloom on the emissive door glow
+    #[cfg(target_arch = "wasm32")]
+    pub const BLOOM_ENABLED: bool = false; // Too expensive for WASM targets
+    #[cfg(not(target_arch = "wasm32"))]
+    pub const BLOOM_ENABLED: bool = true;
+
+    // Tonemapping operator: 0 None, 1 Reinhard, 2 ReinhardLuminance, 3 AcesFitted, 4 AgX,
+    // 5 SomewhatBoringDisplayTransform, 6 TonyMcMapface, 7 BlenderFilmic
+    pub const TONEMAPPING: u32 = 6; // TonyMcMapface, Bevy's default
+
+    // Linear distance fog for depth cueing, distances in world units from the camera.
+    // The backdrop sits ~21-29 units from the camera, keep FOG_END beyond it to leave it visible.
+    pub const FOG_ENABLED: bool = false;
+    pub const FOG_COLOR: [f32; 4] = [0.05, 0.05, 0.05, 1.0]; // sRGB + alpha (fog strength)
+    pub const FOG_START: f32 = 20.0;
+    pub const FOG_END: f32 = 40.0;
+
+    // Resolution scale of the 3D scene, 1.0 renders it at the window resolution.
+    // Values are clamped to [MIN_RENDER_SCALE, 1.0]
+    pub const RENDER_SCALE: f32 = 1.0;
+    pub const MIN_RENDER_SCALE: f32 = 0.1;
+}
 
 /// Shared timing constants for stimulus experiments.
 pub mod timing {
@@ -132,3 +326,137 @@ pub mod timing {
         (seconds * REFRESH_RATE_HZ as f32) as u64
     }
 }
+
+pub mod base_layout {
+    use super::pyramid_constants::BASE_NR_SIDES_MAX;
+
+    /// Whether the base can have `door_count` sides: a multiple of 3 (a whole number of doors under
+    /// each pyramid face) up to BASE_NR_SIDES_MAX
+    pub const fn is_valid_door_count(door_count: usize) -> bool {
+        door_count >= 3 && door_count.is_multiple_of(3) && door_count <= BASE_NR_SIDES_MAX
+    }
+}
+
+/// Decoration layout on the pyramid faces, shared with the Controller to check requests before a round.
+pub mod decoration_layout {
+    use super::object_constants::GROUND_Y;
+    use super::pyramid_constants::{BASE_HEIGHT, DECORATION_EDGE_MARGIN, DECORATION_SPACING, PYRAMID_ANGLE_INCREMENT_RAD};
+
+    /// Smallest edge margin, in decoration sizes: the radius of the circle and star meshes, whose edges
+    /// then touch the triangle edges. Wider shapes are held further from the edges by the game.
+    pub const DECORATION_MIN_EDGE_MARGIN: f32 = 1.0;
+
+    /// Fraction of the area available to the spacing disks (diameter DECORATION_SPACING * size) that the
+    /// Poisson sampling typically covers before running out of attempts (random packings jam near 0.55)
+    pub const POISSON_PACKING_DENSITY: f32 = 0.45;
+
+    /// Side and height of one (rectangular) pyramid face, spanning a base side from the top of the wooden base to `height`.
+    pub fn pyramid_face_size(radius: f32, height: f32) -> (f32, f32) {
+        let side = 2.0 * radius * (PYRAMID_ANGLE_INCREMENT_RAD / 2.0).sin();
+        let face_height = height - (GROUND_Y + BASE_HEIGHT);
+        (side, face_height)
+    }
+
+    /// Area of one pyramid face.
+    pub fn pyramid_face_area(radius: f32, height: f32) -> f32 {
+        let (side, face_height) = pyramid_face_size(radius, height);
+        (side * face_height).max(0.0)
+    }
+
+    /// Edge margin in decoration sizes for a `decoration_edge_margin_factor`: DECORATION_EDGE_MARGIN when not
+    /// a finite non-negative value, and at least `shape_extent` (the mesh radius in sizes) so no decoration
+    /// reaches past the triangle.
+    pub fn effective_edge_margin(factor: f32, shape_extent: f32) -> f32 {
+        let factor = if factor.is_finite() && factor >= 0.0 { factor } else { DECORATION_EDGE_MARGIN };
+        factor.max(shape_extent)
+    }
+
+    /// Decorations per face triangle giving `density` decorations per unit of area on a face of `face_area`.
+    pub fn decoration_count_for_density(density: f32, face_area: f32) -> u32 {
+        // Each of the two triangles covers half of the face
+        (density * face_area / 2.0).round() as u32
+    }
+
+    /// Decorations of `size` the Poisson sampling typically places on one triangle of face `face_index`,
+    /// a count per triangle like `decorations_count`. The three faces are congruent, so the index only has to be in 0..3.
+    /// `edge_margin_factor` is the `decoration_edge_margin_factor`, clamped for the narrowest shapes.
+    pub fn max_decorations_for_face(radius: f32, height: f32, face_index: usize, size: f32, edge_margin_factor: f32) -> u32 {
+        if face_index >= 3 || !(size.is_finite() && size > 0.0) {
+            return 0;
+        }
+        // Each face triangle is a right triangle with the face side and height as legs
+        let (side, face_height) = pyramid_face_size(radius, height);
+        let hypotenuse = side.hypot(face_height);
+        let area = side * face_height / 2.0;
+        let perimeter = side + face_height + hypotenuse;
+        let inradius = (side + face_height - hypotenuse) / 2.0;
+        let edge_margin = size * effective_edge_margin(edge_margin_factor, DECORATION_MIN_EDGE_MARGIN);
+        if !(side > 0.0 && face_height > 0.0 && inradius > edge_margin) {
+            return 0;
+        }
+
+        // The centers stay in the triangle shrunk by the edge margin, and the free disk around each center
+        // can reach past it: the disks have the shrunk triangle grown by their radius to share
+        let scale = (inradius - edge_margin) / inradius;
+        let disk_radius = size * DECORATION_SPACING / 2.0;
+        let disk_area = std::f32::consts::PI * disk_radius * disk_radius;
+        let available = area * scale * scale + perimeter * scale * disk_radius + disk_area;
+        ((POISSON_PACKING_DENSITY * available / disk_area).floor() as u32).max(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::decoration_layout::*;
+    use super::object_constants::GROUND_Y;
+    use super::pyramid_constants::{BASE_HEIGHT, DECORATION_EDGE_MARGIN};
+
+    /// Radius and height of a pyramid whose faces are 3 x 4, split into 3-4-5 right triangles of area 6 and inradius 1
+    fn pyramid_3_4_5() -> (f32, f32) {
+        (3.0_f32.sqrt(), GROUND_Y + BASE_HEIGHT + 4.0)
+    }
+
+    #[test]
+    fn face_area_of_known_pyramids() {
+        let (radius, height) = pyramid_3_4_5();
+        assert!((pyramid_face_area(radius, height) - 12.0).abs() < 1e-4);
+        // No face below the top of the wooden base
+        assert_eq!(pyramid_face_area(radius, GROUND_Y + BASE_HEIGHT - 1.0), 0.0);
+        assert_eq!(decoration_count_for_density(2.0, 12.0), 12);
+    }
+
+    #[test]
+    fn max_decorations_follow_the_face_area() {
+        let (radius, height) = pyramid_3_4_5();
+        // Shrunk triangle scale 0.85: (6 * 0.7225 + 12 * 0.85 * 0.12 + pi * 0.0144) / (pi * 0.0144) * 0.45
+        assert_eq!(max_decorations_for_face(radius, height, 0, 0.1, DECORATION_EDGE_MARGIN), 55);
+        // Same for every face, none past the third
+        assert_eq!(max_decorations_for_face(radius, height, 2, 0.1, DECORATION_EDGE_MARGIN), 55);
+        assert_eq!(max_decorations_for_face(radius, height, 3, 0.1, DECORATION_EDGE_MARGIN), 0);
+
+        // About four times as many at half the size, four times fewer on a face of half the side and height
+        let smaller = max_decorations_for_face(radius, height, 0, 0.05, DECORATION_EDGE_MARGIN);
+        assert!((200..=240).contains(&smaller), "{}", smaller);
+        let half_face = max_decorations_for_face(radius / 2.0, GROUND_Y + BASE_HEIGHT + 2.0, 0, 0.05, DECORATION_EDGE_MARGIN);
+        assert_eq!(half_face, max_decorations_for_face(radius, height, 0, 0.1, DECORATION_EDGE_MARGIN));
+
+        // A single decoration when the margins leave room for its center only, none when they do not
+        assert_eq!(max_decorations_for_face(radius, height, 0, 0.6, DECORATION_EDGE_MARGIN), 1);
+        assert_eq!(max_decorations_for_face(radius, height, 0, 0.7, DECORATION_EDGE_MARGIN), 0);
+        assert_eq!(max_decorations_for_face(radius, height, 0, 0.0, DECORATION_EDGE_MARGIN), 0);
+    }
+
+    #[test]
+    fn edge_margin_is_clamped_to_the_decoration_extent() {
+        assert_eq!(effective_edge_margin(0.5, DECORATION_MIN_EDGE_MARGIN), DECORATION_MIN_EDGE_MARGIN);
+        assert_eq!(effective_edge_margin(2.0, DECORATION_MIN_EDGE_MARGIN), 2.0);
+        assert_eq!(effective_edge_margin(f32::NAN, 1.0), DECORATION_EDGE_MARGIN);
+
+        // Closer to the edges leaves room for more decorations, down to the clamp
+        let (radius, height) = pyramid_3_4_5();
+        let default = max_decorations_for_face(radius, height, 0, 0.1, DECORATION_EDGE_MARGIN);
+        let closer = max_decorations_for_face(radius, height, 0, 0.1, 1.0);
+        assert!(closer > default, "{} <= {}", closer, default);
+        assert_eq!(max_decorations_for_face(radius, height, 0, 0.1, 0.0), closer);
+    }
+}
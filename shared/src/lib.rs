@@ -6,15 +6,20 @@
 //! ## Memory Layout
 //!
 //! SharedMemory {
+//!     layout_version: u32,                      // SHARED_MEMORY_LAYOUT_VERSION, at offset 0
 //!     commands: SharedCommands,                 // Controller -> Game (one-way)
 //!     game_structure_contr: SharedGameStructure // Controller -> Game (one-way)
 //!     game_structure_game: SharedGameStructure  // Game ->  Controller (one-way)
-//!
+//!     trial_result: SharedTrialResult           // Game ->  Controller, summary of the last round
 //! }
 //! 
 use core::sync::atomic::{AtomicBool, AtomicU32, AtomicU64};
 use std::sync::atomic::Ordering;
 pub mod constants;
+mod snapshot;
+pub use snapshot::GameStructureSnapshot;
+mod trial_result;
+pub use trial_result::{SharedTrialResult, TrialResult, TrialResultSummary};
 
 
 /// Commands sent from Controller to Game.
@@ -33,6 +38,28 @@ pub struct SharedCommands {
     pub stop_rendering: AtomicBool,
     pub resume_rendering: AtomicBool,
     pub animation_door: AtomicBool,
+    /// Absolute blank screen state (`blank_screen` toggles, these set it deterministically)
+    pub blank_on: AtomicBool,
+    pub blank_off: AtomicBool,
+    /// Regenerate the decorations from the Controller's decoration seeds, without a full reset
+    pub reroll: AtomicBool,
+    /// Free and show the cursor, e.g. for the experimenter to reach the OS
+    pub release_cursor: AtomicBool,
+    /// Move the camera to the orbit pose `set_azimuth` (radians around the pyramid, as `camera_azimuth`)
+    /// and `set_radius` (f32 bits) in one step. Write the targets before raising the flag (Release)
+    pub set_viewpoint: AtomicBool,
+    pub set_azimuth: AtomicU32,
+    pub set_radius: AtomicU32,
+    /// Rebuild only the wooden base from the Controller's `base_nr_sides` and `target_door`,
+    /// keeping the pyramid, its decorations and the camera (e.g. to change the door count between attempts)
+    pub rebuild_base: AtomicBool,
+    /// Debug only: the next check wins the round whatever the camera pose, to test the Controller's trial loop
+    pub force_win: AtomicBool,
+    /// Move the camera back to the round's start pose (the Controller's `camera_x/y/z`), e.g. for a disoriented subject
+    pub recenter: AtomicBool,
+    /// `PresentModeCommand` code applied to the window, kept until the Controller writes another one.
+    /// Not cleared by `write_commands`; `Unchanged` leaves the current mode (e.g. the debug vsync key)
+    pub present_mode: AtomicU32,
 }
 
 impl SharedCommands {
@@ -48,6 +75,17 @@ impl SharedCommands {
             stop_rendering: AtomicBool::new(false),
             resume_rendering: AtomicBool::new(false),
             animation_door: AtomicBool::new(false),
+            blank_on: AtomicBool::new(false),
+            blank_off: AtomicBool::new(false),
+            reroll: AtomicBool::new(false),
+            release_cursor: AtomicBool::new(false),
+            set_viewpoint: AtomicBool::new(false),
+            set_azimuth: AtomicU32::new(0),
+            set_radius: AtomicU32::new(0),
+            rebuild_base: AtomicBool::new(false),
+            force_win: AtomicBool::new(false),
+            recenter: AtomicBool::new(false),
+            present_mode: AtomicU32::new(PresentModeCommand::Unchanged as u32),
         }
     }
 }
@@ -64,6 +102,199 @@ pub enum PyramidType {
     Type2 = 1,
 }
 
+/// Texture applied to the wooden base frames and lid.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BaseTexture {
+    SolidColor = 0,
+    WoodGrain = 1,
+    Checker = 2,
+}
+
+impl BaseTexture {
+    /// Decode the value stored in shared memory, falling back to `SolidColor` for unknown values.
+    pub fn from_u32(value: u32) -> Self {
+        match value {
+            1 => BaseTexture::WoodGrain,
+            2 => BaseTexture::Checker,
+            _ => BaseTexture::SolidColor,
+        }
+    }
+}
+
+/// Camera control scheme.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CameraMode {
+    /// Constrained orbit around the pyramid, driven by the Controller commands
+    Orbit = 0,
+    /// Keyboard driven free-fly, to inspect the scene
+    FreeFly = 1,
+}
+
+impl CameraMode {
+    /// Decode the value stored in shared memory, falling back to `Orbit` for unknown values.
+    pub fn from_u32(value: u32) -> Self {
+        match value {
+            1 => CameraMode::FreeFly,
+            _ => CameraMode::Orbit,
+        }
+    }
+}
+
+/// What the zoom command changes.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ZoomMode {
+    /// Moves the camera along the orbit radius, changing the perspective distortion
+    Dolly = 0,
+    /// Changes the field of view at a fixed distance, only magnifying
+    FovZoom = 1,
+}
+
+impl ZoomMode {
+    /// Decode the value stored in shared memory, falling back to `Dolly` for unknown values.
+    pub fn from_u32(value: u32) -> Self {
+        match value {
+            1 => ZoomMode::FovZoom,
+            _ => ZoomMode::Dolly,
+        }
+    }
+}
+
+/// Layout of the score bar fill.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ScoreBarOrientation {
+    /// Horizontal bar at the top, filling from its left end
+    #[default]
+    LeftToRight = 0,
+    /// Horizontal bar at the top, filling from its center towards both ends
+    CenterOut = 1,
+    /// Vertical bar on the right edge, filling from its bottom
+    Vertical = 2,
+}
+
+impl ScoreBarOrientation {
+    /// Decode the value stored in shared memory, falling back to `LeftToRight` for unknown values.
+    pub fn from_u32(value: u32) -> Self {
+        match value {
+            1 => ScoreBarOrientation::CenterOut,
+            2 => ScoreBarOrientation::Vertical,
+            _ => ScoreBarOrientation::LeftToRight,
+        }
+    }
+}
+
+/// How the face `colors` channels are interpreted.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorSpace {
+    /// Gamma-encoded sRGB, as picked in a color picker (0.5 is about 21% of the white luminance)
+    Srgb = 0,
+    /// Linear light, proportional to the emitted luminance (0.5 is half the white luminance), for calibrated stimuli
+    Linear = 1,
+}
+
+impl ColorSpace {
+    /// Decode the value stored in shared memory, falling back to `Srgb` for unknown values.
+    pub fn from_u32(value: u32) -> Self {
+        match value {
+            1 => ColorSpace::Linear,
+            _ => ColorSpace::Srgb,
+        }
+    }
+}
+
+/// How the decorations are laid out on each face triangle.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecorationPlacement {
+    /// Random Poisson-disk sampling: dense requests may place fewer decorations than asked
+    Poisson = 0,
+    /// Regular triangular lattice with the Poisson spacing, deterministic and as dense as the spacing allows
+    Grid = 1,
+}
+
+impl DecorationPlacement {
+    /// Decode the value stored in shared memory, falling back to `Poisson` for unknown values.
+    pub fn from_u32(value: u32) -> Self {
+        match value {
+            1 => DecorationPlacement::Grid,
+            _ => DecorationPlacement::Poisson,
+        }
+    }
+}
+
+/// How the target door opens during the reveal animation.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DoorOpenStyle {
+    /// The hole stays open and only its light and glow fade in and out
+    LightOnly = 0,
+    /// A door leaf closes the hole, pulled out along the door normal then slid aside
+    Slide = 1,
+    /// A door leaf closes the hole, swung outwards around its left edge
+    Swing = 2,
+}
+
+impl DoorOpenStyle {
+    /// Decode the value stored in shared memory, falling back to `LightOnly` for unknown values.
+    pub fn from_u32(value: u32) -> Self {
+        match value {
+            1 => DoorOpenStyle::Slide,
+            2 => DoorOpenStyle::Swing,
+            _ => DoorOpenStyle::LightOnly,
+        }
+    }
+}
+
+/// Window present mode requested by the Controller.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PresentModeCommand {
+    /// No request, the window keeps its present mode
+    Unchanged = 0,
+    /// Vsync, with adaptive vsync where supported
+    AutoVsync = 1,
+    /// No vsync, with mailbox presentation where supported to avoid tearing
+    AutoNoVsync = 2,
+    /// No vsync and frames presented at once, the lowest latency but may tear
+    Immediate = 3,
+}
+
+impl PresentModeCommand {
+    /// Decode the value stored in shared memory, falling back to `Unchanged` for unknown values.
+    pub fn from_u32(value: u32) -> Self {
+        match value {
+            1 => PresentModeCommand::AutoVsync,
+            2 => PresentModeCommand::AutoNoVsync,
+            3 => PresentModeCommand::Immediate,
+            _ => PresentModeCommand::Unchanged,
+        }
+    }
+}
+
+/// Which alignment checks increment the `attempts` counter.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttemptsPolicy {
+    /// Every check counts, so a round won at the first try ends with `attempts_base + 1`
+    AllChecks = 0,
+    /// Only failed checks count, so a round won at the first try ends with `attempts_base`
+    FailuresOnly = 1,
+}
+
+impl AttemptsPolicy {
+    /// Decode the value stored in shared memory, falling back to `AllChecks` for unknown values.
+    pub fn from_u32(value: u32) -> Self {
+        match value {
+            1 => AttemptsPolicy::FailuresOnly,
+            _ => AttemptsPolicy::AllChecks,
+        }
+    }
+}
+
 /// Game phases.
 #[repr(u32)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -85,38 +316,180 @@ pub struct SharedGameStructure {
     pub base_radius: AtomicU32,
     pub height: AtomicU32,
     pub start_orient: AtomicU32,
+    /// Turn the pyramid by a random `start_yaw` at each reset, on top of `start_orient`
+    pub random_start_orientation: AtomicBool,
     pub target_door: AtomicU32,
+    /// Number of sides (and doors) of the wooden base, see `constants::base_layout::is_valid_door_count`.
+    /// Also applied by the `rebuild_base` command; `target_door` must be valid for the new count
+    pub base_nr_sides: AtomicU32,
     /// Colors: 3 faces * 4 channels (RGBA) = 12 floats as u32 bits
     pub colors: [AtomicU32; 12],
+    /// Entry of `COLOR_PALETTE` replacing `colors` at reset (`PALETTE_INDEX_NONE` to use `colors`)
+    pub palette_index: AtomicU32,
+    /// Color space of `colors` (and of the palette entries copied into them), see `ColorSpace`
+    pub color_space: AtomicU32,
 
     pub decorations_count: [AtomicU32; 3], // per face
     pub decorations_size: [AtomicU32; 3], // per face
+    /// Decorations per unit of face area (f32 bits), per face. When > 0 it replaces `decorations_count`,
+    /// the resolved count is written back to the game `decorations_count`
+    pub decoration_density: [AtomicU32; 3],
+    /// Star decorations: number of points (clamped 3..=12) and inner radius ratio (f32 bits)
+    pub star_points: AtomicU32,
+    pub star_inner_ratio: AtomicU32,
+    /// Anti z-fighting offset of decorations and door glow along the face normal (f32 bits)
+    pub decoration_depth_offset: AtomicU32,
+    /// Rotate each decoration by a random angle in the face plane, drawn from the face's decoration seed
+    pub decoration_jitter_enabled: AtomicBool,
+    /// Layout of the decorations on the face triangles, see `DecorationPlacement`
+    pub placement_mode: AtomicU32,
+    /// Minimum distance from a decoration center to the triangle edges, in decoration sizes (f32 bits).
+    /// Clamped by the game so no decoration reaches past its triangle, see `decoration_layout::effective_edge_margin`
+    pub decoration_edge_margin_factor: AtomicU32,
+    /// Emissive strength of the decorations relative to their color (f32 bits), per face. 0 keeps them lit only,
+    /// above they glow in their own color and stay visible in dim light
+    pub decoration_emissive: [AtomicU32; 3],
+    /// Base frames/lid texture, see `BaseTexture`
+    pub base_texture: AtomicU32,
 
     // Logic
     pub cosine_alignment_threshold: AtomicU32,
+    /// Attempts counter at round start, and which checks increment it (see `AttemptsPolicy`)
+    pub attempts_base: AtomicU32,
+    pub attempts_increment_policy: AtomicU32,
+    /// Practice round, the game copy is the echo the Controller tags the session with
+    pub practice_mode: AtomicBool,
+    /// Consecutive correct checks needed to win (0 is treated as 1), see `correct_streak`
+    pub consecutive_required: AtomicU32,
 
     // Animation Durations
     pub door_anim_fade_out: AtomicU32,   
     pub door_anim_stay_open: AtomicU32,  
     pub door_anim_fade_in: AtomicU32,    
+    /// `DoorOpenStyle` code, LightOnly spawns no door leaf
+    pub door_open_style: AtomicU32,
 
     // Lighting
     pub main_spotlight_intensity: AtomicU32, 
     pub ambient_brightness: AtomicU32,      
+    /// Ambient light color: 3 channels (sRGB) as f32 bits
+    pub ambient_color: [AtomicU32; 3],
+    /// Display calibration gain (f32 bits) scaling the face colors, the ambient and the spotlight intensities
+    pub brightness_gain: AtomicU32,
     pub max_spotlight_intensity: AtomicU32, 
+    /// Resting intensity of the target door light during the round (f32 bits, 0 = off until revealed)
+    pub target_hint_intensity: AtomicU32,
+    /// Sinusoidal pulse of that resting light: frequency in Hz (0 = steady) and depth in intensity units (f32 bits)
+    pub hint_pulse_hz: AtomicU32,
+    pub hint_pulse_depth: AtomicU32,
+
+    // Rendering
+    pub bloom_enabled: AtomicBool,
+    /// Tonemapping operator index, see `constants::render_constants::TONEMAPPING`
+    pub tonemapping: AtomicU32,
+    /// Distance fog: enabled flag, RGBA color (f32 bits), linear start/end distances (f32 bits)
+    pub fog_enabled: AtomicBool,
+    pub fog_color: [AtomicU32; 4],
+    pub fog_start: AtomicU32,
+    pub fog_end: AtomicU32,
+    pub show_backdrop: AtomicBool,
+    /// Camera control scheme, see `CameraMode`
+    pub camera_mode: AtomicU32,
+    /// What the zoom command changes, see `ZoomMode`
+    pub zoom_mode: AtomicU32,
+    /// Speed of the held rotate commands in degrees per second and of the zoom commands in radius units
+    /// per second (f32 bits), independent of the refresh rate
+    pub rotate_speed_dps: AtomicU32,
+    pub zoom_speed_ups: AtomicU32,
+    /// Fixation cross: shown flag, size in UI pixels (f32 bits), RGBA color (f32 bits)
+    pub show_fixation: AtomicBool,
+    pub fixation_size: AtomicU32,
+    pub fixation_color: [AtomicU32; 4],
+    /// Score bar layout, see `ScoreBarOrientation` (applied when the bar is spawned, at each check)
+    pub score_bar_orientation: AtomicU32,
+    /// Discrete score bar: number of segments lit in proportion to the alignment (0 = continuous fill)
+    pub score_bar_segments: AtomicU32,
+    /// Frames without activity before the low-power update rate kicks in (0 = disabled)
+    pub idle_timeout_frames: AtomicU32,
+    /// Highlight the frame of the door currently best aligned with the camera (live)
+    pub show_best_door_hint: AtomicBool,
+    /// Emit `visible_decoration_count` (live, off by default as it walks every decoration each frame)
+    pub emit_visible_decorations: AtomicBool,
+    /// Time the door reveal and the score bar fill by logic frames instead of the wall clock
+    pub deterministic_timing: AtomicBool,
+    /// Alignment the target door must exceed for `target_engaged` (f32 bits, a cosine like `current_alignment`)
+    pub engagement_threshold: AtomicU32,
+    /// Start the next round by itself once the round is won and the reveal is over (live)
+    pub auto_reset_after_result: AtomicBool,
+    /// Wait between the end of the reveal and the automatic reset, in seconds (f32 bits, live)
+    pub auto_reset_delay_secs: AtomicU32,
+    /// Incremented by the Controller after each config write to the control structure (see `bump_config_epoch`)
+    pub config_epoch: AtomicU32,
 
     // Dynamic trials fields
     pub frame_number: AtomicU64,
+    /// Rendered frames since the game launched, advancing even when paused (unlike `frame_number`),
+    /// so a heartbeat that stops advancing means the game hung or crashed
+    pub heartbeat: AtomicU64,
     pub elapsed_secs: AtomicU32,
     pub camera_radius: AtomicU32,
     pub camera_x: AtomicU32,
     pub camera_y: AtomicU32,
     pub camera_z: AtomicU32,
+    /// Bearing of the camera around the pyramid in radians [0, 2π), relative to the pyramid
+    /// (rotating the pyramid changes it as much as moving the camera)
+    pub camera_azimuth: AtomicU32,
     pub attempts: AtomicU32,
     pub current_alignment: AtomicU32,
     pub current_angle: AtomicU32,
     pub is_animating: AtomicBool,
     pub win_time: AtomicU32,
+    /// Counterparts of `attempts` and `win_time` updated instead of them in practice mode
+    pub practice_attempts: AtomicU32,
+    pub practice_win_time: AtomicU32,
+    /// Correct checks in a row in the current round, reset by a wrong check
+    pub correct_streak: AtomicU32,
+    /// Number of rounds started since the game launched (incremented by every reset)
+    pub round_index: AtomicU32,
+    /// Control `config_epoch` of the config the current round was set up with
+    pub config_applied_epoch: AtomicU32,
+    /// Decorations actually rendered (chosen from the seeds), per face triangle `face * 2 + half`:
+    /// shape (0 circle, 1 square, 2 star, 3 triangle) and sRGBA color (f32 bits)
+    pub decoration_shapes: [AtomicU32; 6],
+    pub decoration_colors: [AtomicU32; 24],
+    /// Decorations actually placed per face triangle `face * 2 + half`, below the requested count
+    /// when they do not fit the triangle
+    pub decorations_placed: [AtomicU32; 6],
+    /// Decorations on the pyramid faces turned towards the camera, 0 unless `emit_visible_decorations`
+    pub visible_decoration_count: AtomicU32,
+    /// Screen position of the target door center, normalized to 0..1 from the top left corner of the viewport
+    /// (f32 bits), TARGET_SCREEN_OFFSCREEN when it is not in the viewport. Occlusion is ignored
+    pub target_screen_x: AtomicU32,
+    pub target_screen_y: AtomicU32,
+    /// The target is the door best aligned with the camera and its alignment exceeds `engagement_threshold`
+    pub target_engaged: AtomicBool,
+    /// The target door exists in the scene. False between the despawn of a reset and the respawn of the
+    /// round, while `current_alignment` and `current_angle` keep their stale values
+    pub scene_valid: AtomicBool,
+    /// Yaw the round started with, in radians (f32 bits), 0 unless `random_start_orientation`
+    pub start_yaw: AtomicU32,
+    /// Timing integrity of the round: wall-clock minus frame_number / REFRESH_RATE_HZ in ms (f32 bits)
+    /// and the estimated number of frames dropped because a frame took too long
+    pub frame_drift: AtomicU32,
+    pub dropped_frames: AtomicU64,
+    /// Rendered frame time over the last FRAME_TIME_STATS_PERIOD_SECS, in ms (f32 bits)
+    pub frame_time_mean: AtomicU32,
+    pub frame_time_p95: AtomicU32,
+    pub frame_time_p99: AtomicU32,
+    /// Aggregates over the session (practice rounds excepted), kept across resets: rounds won,
+    /// rounds left before being won, checks and their mean target door alignment (f32 bits)
+    pub session_wins: AtomicU32,
+    pub session_aborted: AtomicU32,
+    pub session_checks: AtomicU32,
+    pub session_mean_alignment: AtomicU32,
+    /// Rounds won in a row (an aborted round restarts it) and the longest such run of the session
+    pub session_win_streak: AtomicU32,
+    pub session_best_win_streak: AtomicU32,
 }
 
 impl SharedGameStructure {
@@ -125,24 +498,68 @@ impl SharedGameStructure {
         use constants::{
             game_constants::{
                 DECORATION_SEEDS,
-                COSINE_ALIGNMENT_TO_WIN},
+                COSINE_ALIGNMENT_TO_WIN,
+                TARGET_SCREEN_OFFSCREEN,
+                ENGAGEMENT_THRESHOLD,
+                ATTEMPTS_BASE,
+                ATTEMPTS_INCREMENT_POLICY,
+                CONSECUTIVE_REQUIRED,
+                PRACTICE_MODE,
+                SHOW_FIXATION,
+                FIXATION_SIZE,
+                FIXATION_COLOR,
+                SCORE_BAR_ORIENTATION,
+                SCORE_BAR_SEGMENTS,
+                IDLE_TIMEOUT_FRAMES,
+                SHOW_BEST_DOOR_HINT,
+                EMIT_VISIBLE_DECORATIONS,
+                DETERMINISTIC_TIMING,
+                AUTO_RESET_AFTER_RESULT,
+                AUTO_RESET_DELAY_SECS},
             pyramid_constants::{
                 PYRAMID_BASE_RADIUS,
                 PYRAMID_HEIGHT,
                 PYRAMID_START_ANGLE_OFFSET_RAD,
+                RANDOM_START_ORIENTATION,
                 PYRAMID_TARGET_DOOR_INDEX,
                 PYRAMID_COLORS,
+                PALETTE_INDEX,
+                COLOR_SPACE,
                 PYRAMID_DECORATIONS_COUNT,
                 PYRAMID_DECORATIONS_SIZE,
+                PYRAMID_DECORATIONS_DENSITY,
+                DECORATION_STAR_POINTS,
+                DECORATION_STAR_INNER_RATIO,
+                DECORATION_DEPTH_OFFSET,
+                DECORATION_JITTER_ENABLED,
+                PLACEMENT_MODE,
+                DECORATION_EDGE_MARGIN,
+                DECORATION_EMISSIVE,
+                BASE_TEXTURE,
                 DOOR_ANIM_FADE_IN,
                 DOOR_ANIM_FADE_OUT,
-                DOOR_ANIM_STAY_OPEN
+                DOOR_ANIM_STAY_OPEN,
+                DOOR_OPEN_STYLE
             },
             lighting_constants::{
                 SPOTLIGHT_LIGHT_INTENSITY,
                 GLOBAL_AMBIENT_LIGHT_INTENSITY,
+                GLOBAL_AMBIENT_LIGHT_COLOR,
+            },
+            render_constants::{
+                BLOOM_ENABLED,
+                TONEMAPPING,
+                FOG_ENABLED,
+                FOG_COLOR,
+                FOG_START,
+                FOG_END,
             },
+            object_constants::SHOW_BACKDROP,
             camera_3d_constants::{
+                CAMERA_MODE,
+                ZOOM_MODE,
+                ROTATE_SPEED_DPS,
+                ZOOM_SPEED_UPS,
                 CAMERA_3D_INITIAL_X,
                 CAMERA_3D_INITIAL_Y,
                 CAMERA_3D_INITIAL_Z,
@@ -161,12 +578,16 @@ impl SharedGameStructure {
             base_radius: AtomicU32::new(PYRAMID_BASE_RADIUS.to_bits()),
             height: AtomicU32::new(PYRAMID_HEIGHT.to_bits()),
             start_orient: AtomicU32::new(PYRAMID_START_ANGLE_OFFSET_RAD.to_bits()),
+            random_start_orientation: AtomicBool::new(RANDOM_START_ORIENTATION),
             target_door: AtomicU32::new(PYRAMID_TARGET_DOOR_INDEX as u32),
+            base_nr_sides: AtomicU32::new(constants::pyramid_constants::BASE_NR_SIDES as u32),
             colors: [
                 AtomicU32::new(PYRAMID_COLORS[0][0].to_bits()), AtomicU32::new(PYRAMID_COLORS[0][1].to_bits()), AtomicU32::new(PYRAMID_COLORS[0][2].to_bits()), AtomicU32::new(PYRAMID_COLORS[0][3].to_bits()),
                 AtomicU32::new(PYRAMID_COLORS[1][0].to_bits()), AtomicU32::new(PYRAMID_COLORS[1][1].to_bits()), AtomicU32::new(PYRAMID_COLORS[1][2].to_bits()), AtomicU32::new(PYRAMID_COLORS[1][3].to_bits()),
                 AtomicU32::new(PYRAMID_COLORS[2][0].to_bits()), AtomicU32::new(PYRAMID_COLORS[2][1].to_bits()), AtomicU32::new(PYRAMID_COLORS[2][2].to_bits()), AtomicU32::new(PYRAMID_COLORS[2][3].to_bits()),
             ],
+            palette_index: AtomicU32::new(PALETTE_INDEX),
+            color_space: AtomicU32::new(COLOR_SPACE),
 
             decorations_count: [
                 AtomicU32::new(PYRAMID_DECORATIONS_COUNT[0]),
@@ -179,29 +600,122 @@ impl SharedGameStructure {
                 AtomicU32::new(PYRAMID_DECORATIONS_SIZE[1].to_bits()),
                 AtomicU32::new(PYRAMID_DECORATIONS_SIZE[2].to_bits()),
             ],
+            decoration_density: [
+                AtomicU32::new(PYRAMID_DECORATIONS_DENSITY[0].to_bits()),
+                AtomicU32::new(PYRAMID_DECORATIONS_DENSITY[1].to_bits()),
+                AtomicU32::new(PYRAMID_DECORATIONS_DENSITY[2].to_bits()),
+            ],
+            star_points: AtomicU32::new(DECORATION_STAR_POINTS),
+            star_inner_ratio: AtomicU32::new(DECORATION_STAR_INNER_RATIO.to_bits()),
+            decoration_depth_offset: AtomicU32::new(DECORATION_DEPTH_OFFSET.to_bits()),
+            decoration_jitter_enabled: AtomicBool::new(DECORATION_JITTER_ENABLED),
+            placement_mode: AtomicU32::new(PLACEMENT_MODE),
+            decoration_edge_margin_factor: AtomicU32::new(DECORATION_EDGE_MARGIN.to_bits()),
+            decoration_emissive: [
+                AtomicU32::new(DECORATION_EMISSIVE[0].to_bits()),
+                AtomicU32::new(DECORATION_EMISSIVE[1].to_bits()),
+                AtomicU32::new(DECORATION_EMISSIVE[2].to_bits()),
+            ],
+            base_texture: AtomicU32::new(BASE_TEXTURE),
 
             cosine_alignment_threshold: AtomicU32::new(COSINE_ALIGNMENT_TO_WIN.to_bits()), // 0.9 approx
+            attempts_base: AtomicU32::new(ATTEMPTS_BASE),
+            attempts_increment_policy: AtomicU32::new(ATTEMPTS_INCREMENT_POLICY),
+            practice_mode: AtomicBool::new(PRACTICE_MODE),
+            consecutive_required: AtomicU32::new(CONSECUTIVE_REQUIRED),
             
             door_anim_fade_out: AtomicU32::new(DOOR_ANIM_FADE_OUT.to_bits()),
             door_anim_stay_open: AtomicU32::new(DOOR_ANIM_STAY_OPEN.to_bits()),
             door_anim_fade_in: AtomicU32::new(DOOR_ANIM_FADE_IN.to_bits()),
+            door_open_style: AtomicU32::new(DOOR_OPEN_STYLE),
             
             main_spotlight_intensity: AtomicU32::new(SPOTLIGHT_LIGHT_INTENSITY.to_bits()),
             ambient_brightness: AtomicU32::new(GLOBAL_AMBIENT_LIGHT_INTENSITY.to_bits()),
+            ambient_color: [
+                AtomicU32::new(GLOBAL_AMBIENT_LIGHT_COLOR[0].to_bits()),
+                AtomicU32::new(GLOBAL_AMBIENT_LIGHT_COLOR[1].to_bits()),
+                AtomicU32::new(GLOBAL_AMBIENT_LIGHT_COLOR[2].to_bits()),
+            ],
+            brightness_gain: AtomicU32::new(constants::lighting_constants::BRIGHTNESS_GAIN.to_bits()),
             max_spotlight_intensity: AtomicU32::new(constants::lighting_constants::MAX_SPOTLIGHT_INTENSITY.to_bits()),
+            target_hint_intensity: AtomicU32::new(constants::lighting_constants::TARGET_HINT_INTENSITY.to_bits()),
+            hint_pulse_hz: AtomicU32::new(constants::lighting_constants::HINT_PULSE_HZ.to_bits()),
+            hint_pulse_depth: AtomicU32::new(constants::lighting_constants::HINT_PULSE_DEPTH.to_bits()),
+
+            bloom_enabled: AtomicBool::new(BLOOM_ENABLED),
+            tonemapping: AtomicU32::new(TONEMAPPING),
+            fog_enabled: AtomicBool::new(FOG_ENABLED),
+            fog_color: [
+                AtomicU32::new(FOG_COLOR[0].to_bits()),
+                AtomicU32::new(FOG_COLOR[1].to_bits()),
+                AtomicU32::new(FOG_COLOR[2].to_bits()),
+                AtomicU32::new(FOG_COLOR[3].to_bits()),
+            ],
+            fog_start: AtomicU32::new(FOG_START.to_bits()),
+            fog_end: AtomicU32::new(FOG_END.to_bits()),
+            show_backdrop: AtomicBool::new(SHOW_BACKDROP),
+            camera_mode: AtomicU32::new(CAMERA_MODE),
+            zoom_mode: AtomicU32::new(ZOOM_MODE),
+            rotate_speed_dps: AtomicU32::new(ROTATE_SPEED_DPS.to_bits()),
+            zoom_speed_ups: AtomicU32::new(ZOOM_SPEED_UPS.to_bits()),
+            show_fixation: AtomicBool::new(SHOW_FIXATION),
+            fixation_size: AtomicU32::new(FIXATION_SIZE.to_bits()),
+            fixation_color: [
+                AtomicU32::new(FIXATION_COLOR[0].to_bits()),
+                AtomicU32::new(FIXATION_COLOR[1].to_bits()),
+                AtomicU32::new(FIXATION_COLOR[2].to_bits()),
+                AtomicU32::new(FIXATION_COLOR[3].to_bits()),
+            ],
+            score_bar_orientation: AtomicU32::new(SCORE_BAR_ORIENTATION),
+            score_bar_segments: AtomicU32::new(SCORE_BAR_SEGMENTS),
+            idle_timeout_frames: AtomicU32::new(IDLE_TIMEOUT_FRAMES),
+            show_best_door_hint: AtomicBool::new(SHOW_BEST_DOOR_HINT),
+            emit_visible_decorations: AtomicBool::new(EMIT_VISIBLE_DECORATIONS),
+            deterministic_timing: AtomicBool::new(DETERMINISTIC_TIMING),
+            engagement_threshold: AtomicU32::new(ENGAGEMENT_THRESHOLD.to_bits()),
+            auto_reset_after_result: AtomicBool::new(AUTO_RESET_AFTER_RESULT),
+            auto_reset_delay_secs: AtomicU32::new(AUTO_RESET_DELAY_SECS.to_bits()),
+            config_epoch: AtomicU32::new(0),
 
             // Dynamic trials fields
             frame_number: AtomicU64::new(0),
+            heartbeat: AtomicU64::new(0),
             elapsed_secs: AtomicU32::new(0),
             camera_radius: AtomicU32::new(CAMERA_3D_INITIAL_RADIUS.to_bits()),
             camera_x: AtomicU32::new(CAMERA_3D_INITIAL_X.to_bits()),
             camera_y: AtomicU32::new(CAMERA_3D_INITIAL_Y.to_bits()),
             camera_z: AtomicU32::new(CAMERA_3D_INITIAL_Z.to_bits()),
+            camera_azimuth: AtomicU32::new(0),
             attempts: AtomicU32::new(0),
             current_alignment: AtomicU32::new(f32::to_bits(0.0)),
             current_angle: AtomicU32::new(0),
             is_animating: AtomicBool::new(false),
             win_time: AtomicU32::new(0),
+            practice_attempts: AtomicU32::new(0),
+            practice_win_time: AtomicU32::new(0),
+            correct_streak: AtomicU32::new(0),
+            round_index: AtomicU32::new(0),
+            config_applied_epoch: AtomicU32::new(0),
+            decoration_shapes: [const { AtomicU32::new(0) }; 6],
+            decorations_placed: [const { AtomicU32::new(0) }; 6],
+            decoration_colors: [const { AtomicU32::new(0) }; 24],
+            visible_decoration_count: AtomicU32::new(0),
+            target_screen_x: AtomicU32::new(TARGET_SCREEN_OFFSCREEN.to_bits()),
+            target_screen_y: AtomicU32::new(TARGET_SCREEN_OFFSCREEN.to_bits()),
+            target_engaged: AtomicBool::new(false),
+            scene_valid: AtomicBool::new(false),
+            start_yaw: AtomicU32::new(0),
+            frame_drift: AtomicU32::new(0),
+            dropped_frames: AtomicU64::new(0),
+            frame_time_mean: AtomicU32::new(0),
+            frame_time_p95: AtomicU32::new(0),
+            frame_time_p99: AtomicU32::new(0),
+            session_wins: AtomicU32::new(0),
+            session_aborted: AtomicU32::new(0),
+            session_checks: AtomicU32::new(0),
+            session_mean_alignment: AtomicU32::new(0),
+            session_win_streak: AtomicU32::new(0),
+            session_best_win_streak: AtomicU32::new(0),
         }
     }
 
@@ -212,60 +726,190 @@ impl SharedGameStructure {
         self.base_radius.store(other.base_radius.load(Ordering::Relaxed), Ordering::Relaxed);
         self.height.store(other.height.load(Ordering::Relaxed), Ordering::Relaxed);
         self.start_orient.store(other.start_orient.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.random_start_orientation.store(other.random_start_orientation.load(Ordering::Relaxed), Ordering::Relaxed);
         self.target_door.store(other.target_door.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.base_nr_sides.store(other.base_nr_sides.load(Ordering::Relaxed), Ordering::Relaxed);
         for i in 0..12 {
             self.colors[i].store(other.colors[i].load(Ordering::Relaxed), Ordering::Relaxed);
         }
+        self.palette_index.store(other.palette_index.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.color_space.store(other.color_space.load(Ordering::Relaxed), Ordering::Relaxed);
         for i in 0..3 {
             self.decorations_count[i].store(other.decorations_count[i].load(Ordering::Relaxed), Ordering::Relaxed);
             self.decorations_size[i].store(other.decorations_size[i].load(Ordering::Relaxed), Ordering::Relaxed);
+            self.decoration_density[i].store(other.decoration_density[i].load(Ordering::Relaxed), Ordering::Relaxed);
         }
+        self.star_points.store(other.star_points.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.star_inner_ratio.store(other.star_inner_ratio.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.decoration_depth_offset.store(other.decoration_depth_offset.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.decoration_jitter_enabled.store(other.decoration_jitter_enabled.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.placement_mode.store(other.placement_mode.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.decoration_edge_margin_factor.store(other.decoration_edge_margin_factor.load(Ordering::Relaxed), Ordering::Relaxed);
+        for i in 0..3 {
+            self.decoration_emissive[i].store(other.decoration_emissive[i].load(Ordering::Relaxed), Ordering::Relaxed);
+        }
+        self.base_texture.store(other.base_texture.load(Ordering::Relaxed), Ordering::Relaxed);
         self.cosine_alignment_threshold.store(other.cosine_alignment_threshold.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.attempts_base.store(other.attempts_base.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.attempts_increment_policy.store(other.attempts_increment_policy.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.practice_mode.store(other.practice_mode.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.consecutive_required.store(other.consecutive_required.load(Ordering::Relaxed), Ordering::Relaxed);
         self.door_anim_fade_out.store(other.door_anim_fade_out.load(Ordering::Relaxed), Ordering::Relaxed);
         self.door_anim_stay_open.store(other.door_anim_stay_open.load(Ordering::Relaxed), Ordering::Relaxed);
         self.door_anim_fade_in.store(other.door_anim_fade_in.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.door_open_style.store(other.door_open_style.load(Ordering::Relaxed), Ordering::Relaxed);
         
         self.main_spotlight_intensity.store(other.main_spotlight_intensity.load(Ordering::Relaxed), Ordering::Relaxed);
         self.ambient_brightness.store(other.ambient_brightness.load(Ordering::Relaxed), Ordering::Relaxed);
+        for i in 0..3 {
+            self.ambient_color[i].store(other.ambient_color[i].load(Ordering::Relaxed), Ordering::Relaxed);
+        }
+        self.brightness_gain.store(other.brightness_gain.load(Ordering::Relaxed), Ordering::Relaxed);
         self.max_spotlight_intensity.store(other.max_spotlight_intensity.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.target_hint_intensity.store(other.target_hint_intensity.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.hint_pulse_hz.store(other.hint_pulse_hz.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.hint_pulse_depth.store(other.hint_pulse_depth.load(Ordering::Relaxed), Ordering::Relaxed);
+
+        self.bloom_enabled.store(other.bloom_enabled.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.tonemapping.store(other.tonemapping.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.fog_enabled.store(other.fog_enabled.load(Ordering::Relaxed), Ordering::Relaxed);
+        for i in 0..4 {
+            self.fog_color[i].store(other.fog_color[i].load(Ordering::Relaxed), Ordering::Relaxed);
+        }
+        self.fog_start.store(other.fog_start.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.fog_end.store(other.fog_end.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.show_backdrop.store(other.show_backdrop.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.camera_mode.store(other.camera_mode.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.zoom_mode.store(other.zoom_mode.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.rotate_speed_dps.store(other.rotate_speed_dps.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.zoom_speed_ups.store(other.zoom_speed_ups.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.show_fixation.store(other.show_fixation.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.fixation_size.store(other.fixation_size.load(Ordering::Relaxed), Ordering::Relaxed);
+        for i in 0..4 {
+            self.fixation_color[i].store(other.fixation_color[i].load(Ordering::Relaxed), Ordering::Relaxed);
+        }
+        self.score_bar_orientation.store(other.score_bar_orientation.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.score_bar_segments.store(other.score_bar_segments.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.idle_timeout_frames.store(other.idle_timeout_frames.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.show_best_door_hint.store(other.show_best_door_hint.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.emit_visible_decorations.store(other.emit_visible_decorations.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.deterministic_timing.store(other.deterministic_timing.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.engagement_threshold.store(other.engagement_threshold.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.auto_reset_after_result.store(other.auto_reset_after_result.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.auto_reset_delay_secs.store(other.auto_reset_delay_secs.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.config_epoch.store(other.config_epoch.load(Ordering::Relaxed), Ordering::Relaxed);
 
         self.frame_number.store(other.frame_number.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.heartbeat.store(other.heartbeat.load(Ordering::Relaxed), Ordering::Relaxed);
         self.elapsed_secs.store(other.elapsed_secs.load(Ordering::Relaxed), Ordering::Relaxed);
         self.camera_radius.store(other.camera_radius.load(Ordering::Relaxed), Ordering::Relaxed);
         self.camera_x.store(other.camera_x.load(Ordering::Relaxed), Ordering::Relaxed);
         self.camera_y.store(other.camera_y.load(Ordering::Relaxed), Ordering::Relaxed);
         self.camera_z.store(other.camera_z.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.camera_azimuth.store(other.camera_azimuth.load(Ordering::Relaxed), Ordering::Relaxed);
         self.attempts.store(other.attempts.load(Ordering::Relaxed), Ordering::Relaxed);
         self.current_alignment.store(other.current_alignment.load(Ordering::Relaxed), Ordering::Relaxed);
         self.current_angle.store(other.current_angle.load(Ordering::Relaxed), Ordering::Relaxed);
         self.is_animating.store(other.is_animating.load(Ordering::Relaxed), Ordering::Relaxed);
         self.win_time.store(other.win_time.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.practice_attempts.store(other.practice_attempts.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.practice_win_time.store(other.practice_win_time.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.correct_streak.store(other.correct_streak.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.round_index.store(other.round_index.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.config_applied_epoch.store(other.config_applied_epoch.load(Ordering::Relaxed), Ordering::Relaxed);
+        for i in 0..6 {
+            self.decoration_shapes[i].store(other.decoration_shapes[i].load(Ordering::Relaxed), Ordering::Relaxed);
+        }
+        for i in 0..24 {
+            self.decoration_colors[i].store(other.decoration_colors[i].load(Ordering::Relaxed), Ordering::Relaxed);
+        }
+        for i in 0..6 {
+            self.decorations_placed[i].store(other.decorations_placed[i].load(Ordering::Relaxed), Ordering::Relaxed);
+        }
+        self.visible_decoration_count.store(other.visible_decoration_count.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.target_screen_x.store(other.target_screen_x.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.target_screen_y.store(other.target_screen_y.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.target_engaged.store(other.target_engaged.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.scene_valid.store(other.scene_valid.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.start_yaw.store(other.start_yaw.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.frame_drift.store(other.frame_drift.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.dropped_frames.store(other.dropped_frames.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.frame_time_mean.store(other.frame_time_mean.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.frame_time_p95.store(other.frame_time_p95.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.frame_time_p99.store(other.frame_time_p99.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.session_wins.store(other.session_wins.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.session_aborted.store(other.session_aborted.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.session_checks.store(other.session_checks.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.session_mean_alignment.store(other.session_mean_alignment.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.session_win_streak.store(other.session_win_streak.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.session_best_win_streak.store(other.session_best_win_streak.load(Ordering::Relaxed), Ordering::Relaxed);
+    }
+
+    /// Controller side: marks the config written to the control structure as new, after the fields are stored.
+    pub fn bump_config_epoch(&self) {
+        self.config_epoch.fetch_add(1, Ordering::Release);
     }
 
+    /// Sets the RGBA color (in `color_space`) of one face (`face` < 3) in `colors`, leaving the other faces as they are.
+    /// An active palette would replace the colors at reset, so its colors are written to `colors`
+    /// first and `palette_index` is cleared. Returns false, writing nothing, when there is no such face.
+    pub fn set_face_color(&self, face: usize, rgba: [f32; 4]) -> bool {
+        use constants::pyramid_constants::{COLOR_PALETTE, PALETTE_INDEX_NONE};
+        if face >= self.colors.len() / 4 {
+            return false;
+        }
+        let palette_index = self.palette_index.swap(PALETTE_INDEX_NONE, Ordering::Relaxed);
+        if let Some(palette) = COLOR_PALETTE.get(palette_index as usize) {
+            for (color, channel) in self.colors.iter().zip(palette.iter().flatten()) {
+                color.store(channel.to_bits(), Ordering::Relaxed);
+            }
+        }
+        for (channel, value) in rgba.into_iter().enumerate() {
+            self.colors[face * 4 + channel].store(value.to_bits(), Ordering::Relaxed);
+        }
+        true
+    }
 }
 
 impl Default for SharedGameStructure {
     fn default() -> Self { Self::new() }
 }
 
+/// Version of the `SharedMemory` layout, stored at offset 0 of the region.
+/// Bump it whenever a field is added, removed or reordered, so a frontend built against another
+/// layout (e.g. a cached JS bundle) can refuse to attach instead of reading garbage.
+pub const SHARED_MEMORY_LAYOUT_VERSION: u32 = 39;
+
 /// Combined shared memory region between Controller and Game.
 /// Using sequence number to track updates and synchronize between read and write operations.
 #[repr(C)]
 #[derive(Debug)]
 pub struct SharedMemory {
+    /// Always `SHARED_MEMORY_LAYOUT_VERSION`, kept first so its offset never changes
+    pub layout_version: AtomicU32,
     pub commands: SharedCommands,
     pub game_structure_game: SharedGameStructure,
     pub game_structure_control: SharedGameStructure,
+    pub trial_result: SharedTrialResult,
 }
 
 impl SharedMemory {
     pub const fn new() -> Self {
         Self {
+            layout_version: AtomicU32::new(SHARED_MEMORY_LAYOUT_VERSION),
             commands: SharedCommands::new(),
             game_structure_game: SharedGameStructure::new(),
             game_structure_control: SharedGameStructure::new(),
+            trial_result: SharedTrialResult::new(),
         }
     }
+
+    /// Whether the current round runs the latest config written by the Controller, false while a reset is
+    /// still to apply it.
+    pub fn config_is_current(&self) -> bool {
+        self.game_structure_game.config_applied_epoch.load(Ordering::Acquire)
+            == self.game_structure_control.config_epoch.load(Ordering::Acquire)
+    }
 }
 
 impl Default for SharedMemory {
@@ -289,3 +933,205 @@ cfg_if::cfg_if! {
         pub use web::*;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Snapshot where every field holds a value different from the defaults and from every other field.
+    fn distinct_snapshot() -> GameStructureSnapshot {
+        let mut next = 100u32;
+        let mut n = || {
+            next += 1;
+            next
+        };
+        GameStructureSnapshot {
+            decoration_seeds: [n() as u64, n() as u64, n() as u64],
+            base_radius: n() as f32,
+            height: n() as f32,
+            start_orient: n() as f32,
+            random_start_orientation: !SharedGameStructure::new().random_start_orientation.load(Ordering::Relaxed),
+            target_door: n(),
+            base_nr_sides: n(),
+            colors: std::array::from_fn(|_| n() as f32),
+            palette_index: n(),
+            color_space: n(),
+            decorations_count: [n(), n(), n()],
+            decorations_size: [n() as f32, n() as f32, n() as f32],
+            decoration_density: [n() as f32, n() as f32, n() as f32],
+            star_points: n(),
+            star_inner_ratio: n() as f32,
+            decoration_depth_offset: n() as f32,
+            decoration_jitter_enabled: !SharedGameStructure::new().decoration_jitter_enabled.load(Ordering::Relaxed),
+            placement_mode: n(),
+            decoration_edge_margin_factor: n() as f32,
+            decoration_emissive: [n() as f32, n() as f32, n() as f32],
+            base_texture: n(),
+            cosine_alignment_threshold: n() as f32,
+            attempts_base: n(),
+            attempts_increment_policy: n(),
+            practice_mode: !SharedGameStructure::new().practice_mode.load(Ordering::Relaxed),
+            consecutive_required: n(),
+            door_anim_fade_out: n() as f32,
+            door_anim_stay_open: n() as f32,
+            door_anim_fade_in: n() as f32,
+            door_open_style: n(),
+            main_spotlight_intensity: n() as f32,
+            ambient_brightness: n() as f32,
+            ambient_color: [n() as f32, n() as f32, n() as f32],
+            brightness_gain: n() as f32,
+            max_spotlight_intensity: n() as f32,
+            target_hint_intensity: n() as f32,
+            hint_pulse_hz: n() as f32,
+            hint_pulse_depth: n() as f32,
+            bloom_enabled: !SharedGameStructure::new().bloom_enabled.load(Ordering::Relaxed),
+            tonemapping: n(),
+            fog_enabled: !SharedGameStructure::new().fog_enabled.load(Ordering::Relaxed),
+            fog_color: [n() as f32, n() as f32, n() as f32, n() as f32],
+            fog_start: n() as f32,
+            fog_end: n() as f32,
+            show_backdrop: !SharedGameStructure::new().show_backdrop.load(Ordering::Relaxed),
+            camera_mode: n(),
+            zoom_mode: n(),
+            rotate_speed_dps: n() as f32,
+            zoom_speed_ups: n() as f32,
+            show_fixation: !SharedGameStructure::new().show_fixation.load(Ordering::Relaxed),
+            fixation_size: n() as f32,
+            fixation_color: [n() as f32, n() as f32, n() as f32, n() as f32],
+            score_bar_orientation: n(),
+            score_bar_segments: n(),
+            idle_timeout_frames: n(),
+            show_best_door_hint: !SharedGameStructure::new().show_best_door_hint.load(Ordering::Relaxed),
+            emit_visible_decorations: !SharedGameStructure::new().emit_visible_decorations.load(Ordering::Relaxed),
+            deterministic_timing: !SharedGameStructure::new().deterministic_timing.load(Ordering::Relaxed),
+            engagement_threshold: n() as f32,
+            auto_reset_after_result: !SharedGameStructure::new().auto_reset_after_result.load(Ordering::Relaxed),
+            auto_reset_delay_secs: n() as f32,
+            config_epoch: n(),
+            frame_number: n() as u64,
+            heartbeat: n() as u64,
+            elapsed_secs: n() as f32,
+            camera_radius: n() as f32,
+            camera_x: n() as f32,
+            camera_y: n() as f32,
+            camera_z: n() as f32,
+            camera_azimuth: n() as f32,
+            attempts: n(),
+            current_alignment: n() as f32,
+            current_angle: n() as f32,
+            is_animating: true,
+            win_time: n() as f32,
+            practice_attempts: n(),
+            practice_win_time: n() as f32,
+            correct_streak: n(),
+            round_index: n(),
+            config_applied_epoch: n(),
+            decoration_shapes: std::array::from_fn(|_| n()),
+            decoration_colors: std::array::from_fn(|_| n() as f32),
+            decorations_placed: std::array::from_fn(|_| n()),
+            visible_decoration_count: n(),
+            target_screen_x: n() as f32,
+            target_screen_y: n() as f32,
+            target_engaged: !SharedGameStructure::new().target_engaged.load(Ordering::Relaxed),
+            scene_valid: !SharedGameStructure::new().scene_valid.load(Ordering::Relaxed),
+            start_yaw: n() as f32,
+            frame_drift: n() as f32,
+            dropped_frames: n() as u64,
+            frame_time_mean: n() as f32,
+            frame_time_p95: n() as f32,
+            frame_time_p99: n() as f32,
+            session_wins: n(),
+            session_aborted: n(),
+            session_checks: n(),
+            session_mean_alignment: n() as f32,
+            session_win_streak: n(),
+            session_best_win_streak: n(),
+        }
+    }
+
+    #[test]
+    fn snapshot_round_trips_through_apply_snapshot() {
+        let expected = distinct_snapshot();
+        let gs = SharedGameStructure::new();
+        assert_ne!(gs.snapshot(), expected);
+
+        gs.apply_snapshot(&expected);
+        assert_eq!(gs.snapshot(), expected);
+    }
+
+    #[test]
+    fn reset_all_fields_copies_every_field() {
+        let control = SharedGameStructure::new();
+        control.apply_snapshot(&distinct_snapshot());
+        let game = SharedGameStructure::new();
+
+        game.reset_all_fields(&control);
+        assert_eq!(game.snapshot(), control.snapshot());
+    }
+
+    #[test]
+    fn config_is_current_until_the_next_write() {
+        let shm = SharedMemory::new();
+        assert!(shm.config_is_current());
+
+        shm.game_structure_control.bump_config_epoch();
+        assert!(!shm.config_is_current());
+
+        // What setup_round does once the config is copied
+        let epoch = shm.game_structure_control.config_epoch.load(Ordering::Acquire);
+        shm.game_structure_game.config_applied_epoch.store(epoch, Ordering::Release);
+        assert!(shm.config_is_current());
+    }
+
+    #[test]
+    fn set_face_color_rejects_a_missing_face() {
+        let gs = SharedGameStructure::new();
+        let colors = gs.snapshot().colors;
+        assert!(!gs.set_face_color(3, [1.0; 4]));
+        assert_eq!(gs.snapshot().colors, colors);
+
+        assert!(gs.set_face_color(2, [1.0; 4]));
+        assert_eq!(gs.snapshot().colors[8..], [1.0; 4]);
+    }
+
+    #[test]
+    fn concurrent_snapshots_read_whole_values() {
+        let shm = std::sync::Arc::new(SharedMemory::new());
+        // Small enough for Miri, exact in f32
+        let iterations: u32 = if cfg!(miri) { 200 } else { 100_000 };
+
+        // The game side writes one counter into several fields every "frame"
+        let writer = {
+            let shm = shm.clone();
+            std::thread::spawn(move || {
+                let gs = &shm.game_structure_game;
+                for i in 1..=iterations {
+                    gs.frame_number.store(i as u64, Ordering::Relaxed);
+                    gs.camera_x.store((i as f32).to_bits(), Ordering::Relaxed);
+                    gs.current_alignment.store((i as f32).to_bits(), Ordering::Relaxed);
+                }
+            })
+        };
+
+        // Fields are not read together (there is no seqlock), but each one is a whole value written
+        // by the game, never NaN, and never older than the one read before
+        let mut last = (0u64, 0.0f32, 0.0f32);
+        loop {
+            let done = writer.is_finished();
+            let snapshot = shm.game_structure_game.snapshot();
+            for value in [snapshot.camera_x, snapshot.current_alignment] {
+                assert!(value.is_finite() && value.fract() == 0.0 && value <= iterations as f32, "{}", value);
+            }
+            assert!(snapshot.frame_number <= iterations as u64);
+            assert!(snapshot.frame_number >= last.0);
+            assert!(snapshot.camera_x >= last.1);
+            assert!(snapshot.current_alignment >= last.2);
+            last = (snapshot.frame_number, snapshot.camera_x, snapshot.current_alignment);
+            if done {
+                break;
+            }
+        }
+        writer.join().unwrap();
+        assert_eq!(last, (iterations as u64, iterations as f32, iterations as f32));
+    }
+}
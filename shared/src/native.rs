@@ -1,10 +1,56 @@
-use crate::SharedMemory;
-use std::fs::{OpenOptions};
-use std::io::Write;
+use crate::constants::game_constants::SHM_DIR_ENV_VAR;
+use crate::{SharedMemory, SHARED_MEMORY_LAYOUT_VERSION};
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+/// Why the shared memory could not be created or opened.
+#[derive(Debug)]
+pub enum SharedMemError {
+    /// The backing file could not be created, opened, zeroed, synced or measured
+    File { path: PathBuf, source: io::Error },
+    /// The backing file could not be mapped
+    Mmap(io::Error),
+    /// The backing file does not have the size of this build's `SharedMemory` (e.g. created by another version)
+    Size { expected: usize, actual: u64 },
+    /// The segment was written with another `SHARED_MEMORY_LAYOUT_VERSION`
+    Version { expected: u32, actual: u32 },
+}
+
+impl fmt::Display for SharedMemError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SharedMemError::File { path, source } => {
+                write!(f, "shared memory file {}: {}", path.display(), source)
+            }
+            SharedMemError::Mmap(source) => write!(f, "shared memory mmap failed: {}", source),
+            SharedMemError::Size { expected, actual } => write!(
+                f,
+                "shared memory file has {} bytes, this build expects {}",
+                actual, expected
+            ),
+            SharedMemError::Version { expected, actual } => write!(
+                f,
+                "shared memory layout version {}, this build expects {}",
+                actual, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SharedMemError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SharedMemError::File { source, .. } | SharedMemError::Mmap(source) => Some(source),
+            SharedMemError::Size { .. } | SharedMemError::Version { .. } => None,
+        }
+    }
+}
+
 /// Wrapper for file-based shared memory on native platforms (UNIX).
-/// Location shared data structure: /data/local/tmp/monkey_shm_*
+/// Location shared data structure: `<shm_dir()>/monkey_shm_<name>`, e.g. `MONKEY_SHM_DIR=/data/local/tmp` on Android.
 /// Used by both python.rs binding and game_node.
 pub struct NativeSharedMemory {
     ptr: *mut SharedMemory,
@@ -12,36 +58,24 @@ pub struct NativeSharedMemory {
 
 // Initialize shared memory region (by creating or opening existing)
 impl NativeSharedMemory {
-    pub fn new(name: &str) -> std::io::Result<Self> {
-        let path = std::env::temp_dir().join(format!("monkey_shm_{}", name));
+    pub fn new(name: &str) -> Result<Self, SharedMemError> {
+        let path = shm_path(name);
         let size = std::mem::size_of::<SharedMemory>();
         
+        let file_error = |source| SharedMemError::File { path: path.clone(), source };
         let mut file =  OpenOptions::new()
                 .read(true)
                 .write(true)
                 .create(true)
                 .truncate(true)
-                .open(&path)?;
+                .open(&path)
+                .map_err(file_error)?;
 
         let zeroes = vec![0u8; size];
-        file.write_all(&zeroes)?;
-        file.sync_all()?;
-        
-        #[cfg(unix)]
-        let ptr = unsafe {
-            use std::os::unix::io::AsRawFd;
-            let fd = file.as_raw_fd();
-            let ptr = libc::mmap(
-                std::ptr::null_mut(),
-                size,
-                libc::PROT_READ | libc::PROT_WRITE,
-                libc::MAP_SHARED,
-                fd,
-                0,
-            );
-            ptr as *mut SharedMemory
-        };
+        file.write_all(&zeroes).map_err(file_error)?;
+        file.sync_all().map_err(file_error)?;
         
+        let ptr = map_file(&file, &path)?;
         unsafe {
             std::ptr::write(ptr, SharedMemory::new());
         }
@@ -50,6 +84,25 @@ impl NativeSharedMemory {
         Ok(Self {ptr})
     }
 
+    /// Open a segment created by `new` (e.g. by the game), keeping its content.
+    /// Fails if it was created by a build with another layout, instead of reading past the mapping.
+    pub fn open(name: &str) -> Result<Self, SharedMemError> {
+        let path = shm_path(name);
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&path)
+            .map_err(|source| SharedMemError::File { path: path.clone(), source })?;
+
+        // Unmapped by drop if the version does not match
+        let shm = Self { ptr: map_file(&file, &path)? };
+        let actual = shm.get().layout_version.load(std::sync::atomic::Ordering::Relaxed);
+        if actual != SHARED_MEMORY_LAYOUT_VERSION {
+            return Err(SharedMemError::Version { expected: SHARED_MEMORY_LAYOUT_VERSION, actual });
+        }
+        Ok(shm)
+    }
+
     pub fn get(&self) -> &SharedMemory {
         unsafe { &*self.ptr }
     }
@@ -59,6 +112,49 @@ impl NativeSharedMemory {
     }
 }
 
+/// Directory of the shared memory files: `MONKEY_SHM_DIR` when set (e.g. a tmpfs mount), the OS temp dir otherwise.
+/// Read at every create/open, the game and the Controller must resolve the same directory.
+pub fn shm_dir() -> PathBuf {
+    std::env::var_os(SHM_DIR_ENV_VAR)
+        .filter(|dir| !dir.is_empty())
+        .map_or_else(std::env::temp_dir, PathBuf::from)
+}
+
+// Backing file of the segment name
+fn shm_path(name: &str) -> PathBuf {
+    shm_dir().join(format!("monkey_shm_{}", name))
+}
+
+// Map a backing file (at `path`, for the errors) of exactly the size of SharedMemory
+fn map_file(file: &File, path: &Path) -> Result<*mut SharedMemory, SharedMemError> {
+    let size = std::mem::size_of::<SharedMemory>();
+    let actual = file
+        .metadata()
+        .map_err(|source| SharedMemError::File { path: path.to_path_buf(), source })?
+        .len();
+    if actual != size as u64 {
+        return Err(SharedMemError::Size { expected: size, actual });
+    }
+
+    #[cfg(unix)]
+    let ptr = unsafe {
+        use std::os::unix::io::AsRawFd;
+        let fd = file.as_raw_fd();
+        libc::mmap(
+            std::ptr::null_mut(),
+            size,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_SHARED,
+            fd,
+            0,
+        )
+    };
+    if ptr == libc::MAP_FAILED {
+        return Err(SharedMemError::Mmap(io::Error::last_os_error()));
+    }
+    Ok(ptr as *mut SharedMemory)
+}
+
 // Drop the shared memory mapping
 impl Drop for NativeSharedMemory {
     fn drop(&mut self) {
@@ -80,6 +176,99 @@ unsafe impl Sync for NativeSharedMemory {}
 pub type SharedMemoryHandle = Arc<NativeSharedMemory>;
 
 // Create or open shm, regardless it is filled clean with 0s everytime it is called
-pub fn create_shared_memory(name: &str) -> std::io::Result<SharedMemoryHandle> {
+pub fn create_shared_memory(name: &str) -> Result<SharedMemoryHandle, SharedMemError> {
     Ok(Arc::new(NativeSharedMemory::new(name)?))
 }
+
+// Open an existing shm without clearing it, checking it has the layout of this build
+pub fn open_shared_memory(name: &str) -> Result<SharedMemoryHandle, SharedMemError> {
+    Ok(Arc::new(NativeSharedMemory::open(name)?))
+}
+
+/// Delete the backing file of a segment, e.g. a test segment. Existing mappings stay valid until dropped.
+pub fn remove_shared_memory(name: &str) -> io::Result<()> {
+    std::fs::remove_file(shm_path(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Per process, so concurrent test runs do not share segments
+    fn test_segment(name: &str) -> String {
+        format!("{}_{}", name, std::process::id())
+    }
+
+    #[test]
+    fn bad_path_is_a_file_error() {
+        let err = create_shared_memory("missing_dir/monkey_test").err().unwrap();
+        assert!(matches!(err, SharedMemError::File { .. }), "{}", err);
+    }
+
+    #[test]
+    fn too_small_file_is_not_mapped() {
+        let path = shm_path(&test_segment("test_too_small"));
+        let file = File::create(&path).unwrap();
+        file.set_len(1).unwrap();
+
+        let err = map_file(&file, &path).err().unwrap();
+        assert!(matches!(err, SharedMemError::Size { actual: 1, .. }), "{}", err);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // mmap is a foreign call
+    fn mapping_is_shared_across_threads_and_handles() {
+        let name = test_segment("test_threads");
+        let handle = create_shared_memory(&name).unwrap();
+        let reader = open_shared_memory(&name).unwrap();
+        let writer = {
+            let handle = handle.clone();
+            std::thread::spawn(move || {
+                for i in 1..=10_000u64 {
+                    handle.get().game_structure_game.frame_number.store(i, std::sync::atomic::Ordering::Release);
+                }
+            })
+        };
+
+        // The second mapping of the file sees the writes of the first one, in order
+        let mut last = 0;
+        while last < 10_000 {
+            let frame = reader.get().game_structure_game.frame_number.load(std::sync::atomic::Ordering::Acquire);
+            assert!(frame >= last);
+            last = frame;
+        }
+        writer.join().unwrap();
+        remove_shared_memory(&name).unwrap();
+    }
+
+    #[test]
+    fn truncated_segment_is_not_opened() {
+        let name = test_segment("test_truncated");
+        drop(create_shared_memory(&name).unwrap());
+        let size = std::mem::size_of::<SharedMemory>() as u64;
+        File::options()
+            .write(true)
+            .open(shm_path(&name))
+            .unwrap()
+            .set_len(size - 4)
+            .unwrap();
+
+        let err = open_shared_memory(&name).err().unwrap();
+        assert!(matches!(err, SharedMemError::Size { actual, .. } if actual == size - 4), "{}", err);
+        remove_shared_memory(&name).unwrap();
+    }
+
+    #[test]
+    fn segment_of_another_layout_version_is_not_opened() {
+        let name = test_segment("test_version");
+        let handle = create_shared_memory(&name).unwrap();
+        assert!(open_shared_memory(&name).is_ok());
+
+        let version = &handle.get().layout_version;
+        version.store(SHARED_MEMORY_LAYOUT_VERSION - 1, std::sync::atomic::Ordering::Relaxed);
+        let err = open_shared_memory(&name).err().unwrap();
+        assert!(matches!(err, SharedMemError::Version { .. }), "{}", err);
+        remove_shared_memory(&name).unwrap();
+    }
+}
@@ -1,8 +1,12 @@
 //! Python bindings for shared memroy of native.rs
-use crate::{SharedMemoryHandle, create_shared_memory};
+use crate::{GameStructureSnapshot, SharedMemError, SharedMemoryHandle, create_shared_memory, open_shared_memory};
 use std::sync::atomic::Ordering;
-use pyo3::exceptions::PyValueError;
-use pyo3::{prelude::*};
+use std::time::{Duration, Instant};
+use crate::constants::decoration_layout;
+use pyo3::exceptions::{PyTimeoutError, PyTypeError, PyUserWarning, PyValueError};
+use pyo3::{prelude::*, IntoPyObjectExt};
+use pyo3::types::{PyBool, PyDict, PyList, PyString};
+use serde_json::{Map, Value};
 
 // Python class wrapper of SharedMemoryHandle implementation
 #[pyclass]
@@ -17,83 +21,148 @@ impl SharedMemoryWrapper {
     #[pyo3(signature = (name))]
     /// Create (with file name) or open shared memory segment
     fn new(name: &str) -> PyResult<Self> {
-        let res = create_shared_memory(name);
+        match create_shared_memory(name) {
+            Ok(handle) => Ok(SharedMemoryWrapper { inner: handle }),
+            Err(e) => Err(shm_error_to_py(e)),
+        }
+    }
+
+    /// Directory of the segment files, from `MONKEY_SHM_DIR` (read at each `new`/`open`) or the temp dir.
+    /// Set `os.environ["MONKEY_SHM_DIR"]` before `new`/`open` to the directory the game runs with.
+    #[staticmethod]
+    fn shm_dir() -> std::path::PathBuf {
+        crate::shm_dir()
+    }
 
-        match res {
+    /// Open the segment the game created, without clearing it.
+    /// Raises ValueError if the game was built with another shared memory layout.
+    #[staticmethod]
+    fn open(name: &str) -> PyResult<Self> {
+        match open_shared_memory(name) {
             Ok(handle) => Ok(SharedMemoryWrapper { inner: handle }),
-            Err(e) => Err(PyErr::new::<pyo3::exceptions::PyIOError, _>(e.to_string())),
+            Err(e) => Err(shm_error_to_py(e)),
         }
     }
 
     /// Read the full game structure from shared memory as a dictionary.
-    /// It reads one written by the game.
-    /// Some values need to be read as f32 from bits
+    /// It reads one written by the game. Per face values are nested lists: `colors` 3x4, `decoration_shapes`
+    /// and `decorations_placed` 3x2 (face, triangle), `decoration_colors` 3x2x4 sRGBA.
     fn read_game_structure(&self) -> PyResult<Py<PyAny>> {
         let shm = self.inner.get();
-        let gs= &shm.game_structure_game;
+        let mut fields = snapshot_fields(&shm.game_structure_game.snapshot());
+
+        for (field, key) in RENAMED_FIELDS {
+            if let Some(value) = fields.remove(field) {
+                fields.insert(key.to_string(), value);
+            }
+        }
+        for (field, size) in [("colors", 4), ("decoration_shapes", 2), ("decorations_placed", 2)] {
+            let nested = chunked(fields.remove(field), size);
+            fields.insert(field.to_string(), nested);
+        }
+        let decoration_colors = chunked(Some(chunked(fields.remove("decoration_colors"), 4)), 2);
+        fields.insert("decoration_colors".to_string(), decoration_colors);
+        let camera_position = ["camera_x", "camera_y", "camera_z"].map(|axis| fields.remove(axis).unwrap_or_default());
+        fields.insert("camera_position".to_string(), Value::Array(camera_position.into()));
+        // The Controller's epoch and the one the current round was set up with, see config_is_current
+        let config_epoch = shm.game_structure_control.config_epoch.load(Ordering::Relaxed);
+        fields.insert("config_epoch".to_string(), Value::from(config_epoch));
+
+        Python::attach(|py| Ok(json_to_py(py, Value::Object(fields))?.unbind()))
+    }
+
+    /// Block until `predicate(read_game_structure()[field])` is true and return that game structure.
+    /// Raises `TimeoutError` after `timeout_s` seconds, `KeyError` for an unknown field.
+    #[pyo3(signature = (field, predicate, timeout_s, poll_interval_s = 0.001))]
+    fn wait_for(
+        &self,
+        py: Python<'_>,
+        field: &str,
+        predicate: &Bound<'_, PyAny>,
+        timeout_s: f64,
+        poll_interval_s: f64,
+    ) -> PyResult<Py<PyAny>> {
+        self.poll(py, field, timeout_s, poll_interval_s, || {
+            let gs = self.read_game_structure()?;
+            let value = gs.bind(py).get_item(field)?;
+            Ok(predicate.call1((value,))?.is_truthy()?.then_some(gs))
+        })
+    }
+
+    /// Whether the current round runs the latest config written with `write_game_structure`, `set_decoration_count`
+    /// or `set_face_color`. False until a reset applies it, when the live scene parameters are still the old ones.
+    fn config_is_current(&self) -> bool {
+        self.inner.get().config_is_current()
+    }
+
+    /// Current `heartbeat` of the game, without decoding the whole game structure.
+    /// Advances every rendered frame, paused or not: if it stops advancing the game hung or crashed,
+    /// whereas a paused game keeps it advancing with `frame_number` frozen.
+    fn read_heartbeat(&self) -> u64 {
+        self.inner.get().game_structure_game.heartbeat.load(Ordering::Relaxed)
+    }
+
+    /// Summary of the last round that ended, as a dict (`result_code`, `result_attempts`, `result_time_ms`,
+    /// `result_alignment`), or None when no new one was published since the previous call.
+    /// Reading it clears `result_ready`. `result_code` is `TRIAL_RESULT_WIN` or `TRIAL_RESULT_ABORTED`.
+    fn read_trial_result(&self) -> PyResult<Option<Py<PyAny>>> {
+        let Some(summary) = self.inner.get().trial_result.take() else {
+            return Ok(None);
+        };
 
         Python::attach(|py| {
             let dict = pyo3::types::PyDict::new(py);
+            dict.set_item("result_code", summary.result_code)?;
+            dict.set_item("result_attempts", summary.result_attempts)?;
+            dict.set_item("result_time_ms", summary.result_time_ms)?;
+            dict.set_item("result_alignment", summary.result_alignment)?;
+            Ok(Some(dict.into()))
+        })
+    }
 
-            // Fixed vars in trial
-            dict.set_item("decoration_seeds", [
-                gs.decoration_seeds[0].load(Ordering::Relaxed),
-                gs.decoration_seeds[1].load(Ordering::Relaxed),
-                gs.decoration_seeds[2].load(Ordering::Relaxed),
-            ])?;
-            dict.set_item("base_radius", f32::from_bits(gs.base_radius.load(Ordering::Relaxed)))?;
-            dict.set_item("height", f32::from_bits(gs.height.load(Ordering::Relaxed)))?;
-            dict.set_item("start_orient", f32::from_bits(gs.start_orient.load(Ordering::Relaxed)))?;
-            dict.set_item("target_door", gs.target_door.load(Ordering::Relaxed))?;
-            let mut colors: Vec<Vec<f32>> = Vec::with_capacity(3);  // Colors as 3x4 list
-            for face_idx in 0..3 {
-                let mut face_colors: Vec<f32> = Vec::with_capacity(4);
-                for channel_idx in 0..4 {
-                    let index = face_idx * 4 + channel_idx;
-                    face_colors.push(f32::from_bits(gs.colors[index].load(Ordering::Relaxed)));
-                }
-                colors.push(face_colors);
-            }
-            dict.set_item("colors", colors)?;
-
-            dict.set_item("main_spotlight_intensity", f32::from_bits(gs.main_spotlight_intensity.load(Ordering::Relaxed)))?;
-            dict.set_item("ambient_brightness", f32::from_bits(gs.ambient_brightness.load(Ordering::Relaxed)))?;
-            dict.set_item("max_spotlight_intensity", f32::from_bits(gs.max_spotlight_intensity.load(Ordering::Relaxed)))?;
-            dict.set_item("decoration_count", [
-                gs.decorations_count[0].load(Ordering::Relaxed),
-                gs.decorations_count[1].load(Ordering::Relaxed),
-                gs.decorations_count[2].load(Ordering::Relaxed)
-            ])?;
-            dict.set_item("decoration_size", [
-                f32::from_bits(gs.decorations_size[0].load(Ordering::Relaxed)),
-                f32::from_bits(gs.decorations_size[1].load(Ordering::Relaxed)),
-                f32::from_bits(gs.decorations_size[2].load(Ordering::Relaxed))
-            ])?;
-
-            // Dynamic vars in trial
-            dict.set_item("cosine_alignment_threshold", f32::from_bits(gs.cosine_alignment_threshold.load(Ordering::Relaxed)))?;
-            dict.set_item("door_anim_fade_out", f32::from_bits(gs.door_anim_fade_out.load(Ordering::Relaxed)))?;
-            dict.set_item("door_anim_stay_open", f32::from_bits(gs.door_anim_stay_open.load(Ordering::Relaxed)))?;
-            dict.set_item("door_anim_fade_in", f32::from_bits(gs.door_anim_fade_in.load(Ordering::Relaxed)))?;
-            dict.set_item("frame_number", gs.frame_number.load(Ordering::Relaxed))?;
-            dict.set_item("elapsed_secs", f32::from_bits(gs.elapsed_secs.load(Ordering::Relaxed)))?;
-            dict.set_item("camera_radius", f32::from_bits(gs.camera_radius.load(Ordering::Relaxed)))?;
-            dict.set_item("camera_position", vec![
-                f32::from_bits(gs.camera_x.load(Ordering::Relaxed)),
-                f32::from_bits(gs.camera_y.load(Ordering::Relaxed)),
-                f32::from_bits(gs.camera_z.load(Ordering::Relaxed)),
-            ])?;
-            dict.set_item("nr_attempts", gs.attempts.load(Ordering::Relaxed))?;
-            dict.set_item("cosine_alignment", f32::from_bits(gs.current_alignment.load(Ordering::Relaxed)))?;
-            dict.set_item("current_angle", f32::from_bits(gs.current_angle.load(Ordering::Relaxed)))?;
-            dict.set_item("is_animating", gs.is_animating.load(Ordering::Relaxed))?;
-            dict.set_item("win_elapsed_secs", f32::from_bits(gs.win_time.load(Ordering::Relaxed)))?;
-
-            Ok(dict.into())
+    /// Block until the current round is won and return its `win_elapsed_secs` (never in practice mode).
+    /// Raises `TimeoutError` after `timeout_s` seconds.
+    #[pyo3(signature = (timeout_s, poll_interval_s = 0.001))]
+    fn wait_until_won(&self, py: Python<'_>, timeout_s: f64, poll_interval_s: f64) -> PyResult<f32> {
+        let gs = &self.inner.get().game_structure_game;
+        self.poll(py, "win", timeout_s, poll_interval_s, || {
+            // Zeroed by every reset, set on win
+            let win_time = f32::from_bits(gs.win_time.load(Ordering::Relaxed));
+            Ok((win_time > 0.0).then_some(win_time))
+        })
+    }
+
+    /// Block until the game has set up round `round_index` (or a later one), e.g. to wait for a
+    /// reset to be applied: read `round_index`, write the reset, then wait for `round_index + 1`.
+    /// Raises `TimeoutError` after `timeout_s` seconds.
+    #[pyo3(signature = (round_index, timeout_s, poll_interval_s = 0.001))]
+    fn wait_until_round(
+        &self,
+        py: Python<'_>,
+        round_index: u32,
+        timeout_s: f64,
+        poll_interval_s: f64,
+    ) -> PyResult<u32> {
+        let gs = &self.inner.get().game_structure_game;
+        self.poll(py, "round_index", timeout_s, poll_interval_s, || {
+            let current = gs.round_index.load(Ordering::Relaxed);
+            Ok((current >= round_index).then_some(current))
         })
     }
 
     /// Write commands to shared memory.
+    #[pyo3(signature = (
+        rotate_left, rotate_right, zoom_in, zoom_out, check, reset, blank_screen,
+        stop_rendering, resume_rendering, animation_door,
+        blank_on = false,
+        blank_off = false,
+        reroll = false,
+        release_cursor = false,
+        set_viewpoint = false,
+        rebuild_base = false,
+        force_win = false,
+        recenter = false,
+    ))]
     fn write_commands(
         &mut self,
         rotate_left: bool,
@@ -106,6 +175,14 @@ impl SharedMemoryWrapper {
         stop_rendering: bool,
         resume_rendering: bool,
         animation_door: bool,
+        blank_on: bool,
+        blank_off: bool,
+        reroll: bool,
+        release_cursor: bool,
+        set_viewpoint: bool,
+        rebuild_base: bool,
+        force_win: bool,
+        recenter: bool,
     ) {
         let shm = self.inner.get();
         let cmd = &shm.commands;
@@ -120,101 +197,495 @@ impl SharedMemoryWrapper {
         cmd.stop_rendering.store(stop_rendering, Ordering::Relaxed);
         cmd.resume_rendering.store(resume_rendering, Ordering::Relaxed);
         cmd.animation_door.store(animation_door, Ordering::Relaxed);
-        
+        cmd.blank_on.store(blank_on, Ordering::Relaxed);
+        cmd.blank_off.store(blank_off, Ordering::Relaxed);
+        cmd.reroll.store(reroll, Ordering::Relaxed);
+        cmd.release_cursor.store(release_cursor, Ordering::Relaxed);
+        cmd.set_viewpoint.store(set_viewpoint, Ordering::Release);
+        cmd.rebuild_base.store(rebuild_base, Ordering::Relaxed);
+        cmd.force_win.store(force_win, Ordering::Relaxed);
+        cmd.recenter.store(recenter, Ordering::Relaxed);
+    }
+
+    /// Debug only, to test a trial loop without a participant: win the current round at once, as a
+    /// perfectly aligned check would (reveal animation, `win_elapsed_secs` set). Never use it in a session.
+    /// Held until the next `write_commands`, like a check; ignored right after a reset.
+    fn write_force_win(&mut self) {
+        self.inner.get().commands.force_win.store(true, Ordering::Relaxed);
+    }
+
+    /// Move the camera to an exact orbit pose in one step: `azimuth` in radians around the pyramid
+    /// (wrapped, compare with `camera_azimuth`) and `radius` (clamped to the zoom limits).
+    /// Held until the next `write_commands`, which keeps the last targets with `set_viewpoint=True`.
+    fn write_viewpoint(&mut self, azimuth: f32, radius: f32) {
+        let cmd = &self.inner.get().commands;
+        cmd.set_azimuth.store(azimuth.to_bits(), Ordering::Relaxed);
+        cmd.set_radius.store(radius.to_bits(), Ordering::Relaxed);
+        cmd.set_viewpoint.store(true, Ordering::Release);
+    }
+
+    /// Set the window present mode, a `PRESENT_MODE_*` code: `PRESENT_MODE_AUTO_NO_VSYNC` or
+    /// `PRESENT_MODE_IMMEDIATE` lower the input-to-display latency at the cost of tearing.
+    /// Kept until the next `write_present_mode`; browsers may ignore it in the web build.
+    fn write_present_mode(&mut self, mode: u32) {
+        self.inner.get().commands.present_mode.store(mode, Ordering::Relaxed);
+    }
+
+    /// Set the blank screen to an absolute state, unlike the `blank_screen` toggle.
+    /// The state is re-asserted every frame until the next `write_commands`.
+    fn set_blank_screen(&mut self, active: bool) {
+        let cmd = &self.inner.get().commands;
+        cmd.blank_on.store(active, Ordering::Relaxed);
+        cmd.blank_off.store(!active, Ordering::Relaxed);
     }
 
     /// Write game structure config fields to shared memory.
     /// Write in controller region
-    fn write_game_structure(
-        &mut self,
-        decoration_seeds: [u64; 3],
-        base_radius: f32,
-        height: f32,
-        start_orient: f32,
-        target_door: u32,
-        colors: Vec<Vec<f32>>,
-        decorations_count: [u32; 3],
-        decorations_size: [f32; 3],
-        cosine_alignment_threshold: f32,
-        door_anim_fade_out: f32,
-        door_anim_stay_open: f32,
-        door_anim_fade_in: f32,
-        main_spotlight_intensity: f32,
-        ambient_brightness: f32,
-        max_spotlight_intensity: f32,
-    ) -> PyResult<()> {
-        if colors.len() != 3 || colors.iter().any(|face| face.len() != 4) {
+    /// Keyword arguments are keyed by field name, e.g. `write_game_structure(target_door=2, colors=[...])`;
+    /// missing ones keep their current controller value (the defaults of constants.rs until a first write).
+    /// `colors` is a 3x4 matrix, the other per face fields lists of 3. Raises TypeError for an unknown field.
+    /// `nr_attempts` starts each round at `attempts_base`; with `attempts_increment_policy` 0 every
+    /// check increments it, with 1 only failed checks do (a first-try win leaves it at the base).
+    /// With `practice_mode` the checks go to `practice_nr_attempts` / `practice_win_elapsed_secs` instead.
+    /// The round is won after `consecutive_required` correct checks in a row (`correct_streak`).
+    #[pyo3(signature = (**config))]
+    fn write_game_structure(&mut self, py: Python<'_>, config: Option<&Bound<'_, PyDict>>) -> PyResult<()> {
+        let gs = &self.inner.get().game_structure_control;
+
+        let mut fields = snapshot_fields(&gs.snapshot());
+        for (key, value) in config.into_iter().flatten() {
+            let key: String = key.extract()?;
+            if !fields.contains_key(&key) {
+                return Err(PyTypeError::new_err(format!(
+                    "write_game_structure() got an unexpected keyword argument '{}'",
+                    key
+                )));
+            }
+            let value = if key == "colors" {
+                let colors: Vec<Vec<f32>> = value.extract()?;
+                if colors.len() != 3 || colors.iter().any(|face| face.len() != 4) {
+                    return Err(PyErr::new::<PyValueError, _>(format!(
+                        "expected colors to be a 3x4 matrix, got {:?}",
+                        colors.iter().map(|face| face.len()).collect::<Vec<_>>()
+                    )));
+                }
+                Value::from(colors.concat())
+            } else {
+                py_to_json(&value)?
+            };
+            fields.insert(key, value);
+        }
+        let snapshot: GameStructureSnapshot = serde_json::from_value(Value::Object(fields))
+            .map_err(|e| PyValueError::new_err(format!("invalid game structure field: {}", e)))?;
+        let GameStructureSnapshot {
+            base_radius,
+            height,
+            target_door,
+            base_nr_sides,
+            brightness_gain,
+            decoration_emissive,
+            decoration_edge_margin_factor,
+            engagement_threshold,
+            auto_reset_delay_secs,
+            rotate_speed_dps,
+            zoom_speed_ups,
+            score_bar_segments,
+            placement_mode,
+            decoration_density,
+            decorations_count,
+            decorations_size,
+            ..
+        } = snapshot;
+
+        // The game falls back to the defaults on these, reject them here with a clear error instead
+        if !(base_radius.is_finite() && base_radius > 0.0) {
             return Err(PyErr::new::<PyValueError, _>(format!(
-                "expected colors to be a 3x4 matrix, got {:?}",
-                colors.iter().map(|face| face.len()).collect::<Vec<_>>()
+                "base_radius must be positive, got {}",
+                base_radius
+            )));
+        }
+        let base_top = crate::constants::object_constants::GROUND_Y + crate::constants::pyramid_constants::BASE_HEIGHT;
+        if !(height.is_finite() && height > base_top) {
+            return Err(PyErr::new::<PyValueError, _>(format!(
+                "height must be above the top of the wooden base ({}), got {}",
+                base_top, height
             )));
         }
 
-        let shm = self.inner.get();
-        let gs = &shm.game_structure_control;
+        let door_count = base_nr_sides as usize;
+        if !crate::constants::base_layout::is_valid_door_count(door_count) {
+            return Err(PyErr::new::<PyValueError, _>(format!(
+                "base_nr_sides must be a multiple of 3 in 3..={}, got {}",
+                crate::constants::pyramid_constants::BASE_NR_SIDES_MAX, base_nr_sides
+            )));
+        }
+        if target_door as usize >= door_count {
+            return Err(PyErr::new::<PyValueError, _>(format!(
+                "target_door must be in 0..{}, got {}",
+                door_count, target_door
+            )));
+        }
+        if !(brightness_gain.is_finite() && brightness_gain >= 0.0) {
+            return Err(PyErr::new::<PyValueError, _>(format!(
+                "brightness_gain must be finite and non-negative, got {}",
+                brightness_gain
+            )));
+        }
+        if decoration_emissive.iter().any(|emissive| !(emissive.is_finite() && *emissive >= 0.0)) {
+            return Err(PyErr::new::<PyValueError, _>(format!(
+                "decoration_emissive must be finite and non-negative, got {:?}",
+                decoration_emissive
+            )));
+        }
+        if !(decoration_edge_margin_factor.is_finite() && decoration_edge_margin_factor >= 0.0) {
+            return Err(PyErr::new::<PyValueError, _>(format!(
+                "decoration_edge_margin_factor must be finite and non-negative, got {}",
+                decoration_edge_margin_factor
+            )));
+        }
+        if !engagement_threshold.is_finite() {
+            return Err(PyErr::new::<PyValueError, _>(format!(
+                "engagement_threshold must be finite, got {}",
+                engagement_threshold
+            )));
+        }
+        if !(auto_reset_delay_secs.is_finite() && auto_reset_delay_secs >= 0.0) {
+            return Err(PyErr::new::<PyValueError, _>(format!(
+                "auto_reset_delay_secs must be finite and non-negative, got {}",
+                auto_reset_delay_secs
+            )));
+        }
+        for (name, speed) in [("rotate_speed_dps", rotate_speed_dps), ("zoom_speed_ups", zoom_speed_ups)] {
+            if !(speed.is_finite() && speed >= 0.0) {
+                return Err(PyErr::new::<PyValueError, _>(format!(
+                    "{} must be finite and non-negative, got {}",
+                    name, speed
+                )));
+            }
+        }
+        if score_bar_segments > crate::constants::game_constants::SCORE_BAR_MAX_SEGMENTS {
+            return Err(PyErr::new::<PyValueError, _>(format!(
+                "score_bar_segments must be at most {}, got {}",
+                crate::constants::game_constants::SCORE_BAR_MAX_SEGMENTS, score_bar_segments
+            )));
+        }
+        // The Poisson sampling places what fits and the game reports it in decorations_placed, so a request
+        // above the capacity is only warned about (the grid placement fits more)
+        if crate::DecorationPlacement::from_u32(placement_mode) == crate::DecorationPlacement::Poisson {
+            let face_area = decoration_layout::pyramid_face_area(base_radius, height);
+            for face in 0..3 {
+                let count = if decoration_density[face] > 0.0 {
+                    decoration_layout::decoration_count_for_density(decoration_density[face], face_area)
+                } else {
+                    decorations_count[face]
+                };
+                let capacity = decoration_layout::max_decorations_for_face(
+                    base_radius,
+                    height,
+                    face,
+                    decorations_size[face],
+                    decoration_edge_margin_factor,
+                );
+                if count > capacity {
+                    let message = format!(
+                        "face {} requests {} decorations of size {} per triangle, only about {} fit",
+                        face, count, decorations_size[face], capacity
+                    );
+                    let message = std::ffi::CString::new(message).unwrap_or_default();
+                    PyErr::warn(py, py.get_type::<PyUserWarning>().as_any(), &message, 1)?;
+                }
+            }
+        }
 
-        for i in 0..3 {
-            gs.decoration_seeds[i].store(decoration_seeds[i], Ordering::Relaxed);
+        gs.apply_snapshot(&snapshot);
+        gs.bump_config_epoch();
+        Ok(())
+    }
+
+    /// Write the decoration count of one face (0..3) in the controller region, leaving the rest of the config as is.
+    /// Also clears the face's `decoration_density`, which would otherwise replace the count.
+    /// Applied on the next reset or `reroll`.
+    fn set_decoration_count(&mut self, face: u32, count: u32) -> PyResult<()> {
+        if face >= 3 {
+            return Err(PyErr::new::<PyValueError, _>(format!("face must be in 0..3, got {}", face)));
         }
-        gs.base_radius.store(base_radius.to_bits(), Ordering::Relaxed);
-        gs.height.store(height.to_bits(), Ordering::Relaxed);
-        gs.start_orient.store(start_orient.to_bits(), Ordering::Relaxed);
-        gs.target_door.store(target_door, Ordering::Relaxed);
+        let gs = &self.inner.get().game_structure_control;
+        gs.decorations_count[face as usize].store(count, Ordering::Relaxed);
+        gs.decoration_density[face as usize].store(0.0f32.to_bits(), Ordering::Relaxed);
+        gs.bump_config_epoch();
+        Ok(())
+    }
 
-        for (face_idx, face) in colors.iter().enumerate() {
-            for (channel_idx, value) in face.iter().enumerate() {
-                let index = face_idx * 4 + channel_idx;
-                gs.colors[index].store(value.to_bits(), Ordering::Relaxed);
+    /// Write the three door animation phase durations (seconds) in the controller region, leaving the rest of the config as is.
+    /// Applied when the next animation starts, also within the round; an animation already running keeps its timing.
+    fn set_door_animation(&mut self, fade_out: f32, stay_open: f32, fade_in: f32) -> PyResult<()> {
+        for (name, duration) in [("fade_out", fade_out), ("stay_open", stay_open), ("fade_in", fade_in)] {
+            if !(duration.is_finite() && duration >= 0.0) {
+                return Err(PyErr::new::<PyValueError, _>(format!(
+                    "{} must be finite and non-negative, got {}",
+                    name, duration
+                )));
             }
         }
-        
-        // Store decorations
-        for i in 0..3 {
-            gs.decorations_count[i].store(decorations_count[i], Ordering::Relaxed);
-            gs.decorations_size[i].store(decorations_size[i].to_bits(), Ordering::Relaxed);
-        }
-        gs.cosine_alignment_threshold.store(cosine_alignment_threshold.to_bits(), Ordering::Relaxed);
-        gs.door_anim_fade_out.store(door_anim_fade_out.to_bits(), Ordering::Relaxed);
-        gs.door_anim_stay_open.store(door_anim_stay_open.to_bits(), Ordering::Relaxed);
-        gs.door_anim_fade_in.store(door_anim_fade_in.to_bits(), Ordering::Relaxed);
-        gs.main_spotlight_intensity.store(main_spotlight_intensity.to_bits(), Ordering::Relaxed);
-        gs.ambient_brightness.store(ambient_brightness.to_bits(), Ordering::Relaxed);
-        gs.max_spotlight_intensity.store(max_spotlight_intensity.to_bits(), Ordering::Relaxed);
+        let gs = &self.inner.get().game_structure_control;
+        gs.door_anim_fade_out.store(fade_out.to_bits(), Ordering::Relaxed);
+        gs.door_anim_stay_open.store(stay_open.to_bits(), Ordering::Relaxed);
+        gs.door_anim_fade_in.store(fade_in.to_bits(), Ordering::Relaxed);
+        gs.bump_config_epoch();
+        Ok(())
+    }
+
+    /// Write the RGBA color (in `color_space`) of one face (0..3) in the controller region, leaving the other faces as they are.
+    /// An active `palette_index` is expanded into the colors and cleared. Applied on the next reset.
+    fn set_face_color(&mut self, face: u32, rgba: [f32; 4]) -> PyResult<()> {
+        let gs = &self.inner.get().game_structure_control;
+        if !gs.set_face_color(face as usize, rgba) {
+            return Err(PyErr::new::<PyValueError, _>(format!("face must be in 0..3, got {}", face)));
+        }
+        gs.bump_config_epoch();
         Ok(())
     }
 
+}
+
+impl SharedMemoryWrapper {
+    /// Calls `condition` every `poll_interval_s` until it returns a value or `timeout_s` elapses
+    /// (`math.inf` waits forever). The GIL is released while sleeping and Ctrl+C interrupts the wait.
+    fn poll<T>(
+        &self,
+        py: Python<'_>,
+        what: &str,
+        timeout_s: f64,
+        poll_interval_s: f64,
+        mut condition: impl FnMut() -> PyResult<Option<T>>,
+    ) -> PyResult<T> {
+        if !(timeout_s >= 0.0 && poll_interval_s >= 0.0 && poll_interval_s.is_finite()) {
+            return Err(PyValueError::new_err(format!(
+                "expected non-negative timeout_s and finite poll_interval_s, got {} and {}",
+                timeout_s, poll_interval_s
+            )));
+        }
+        // None when the timeout does not fit in an Instant
+        let deadline = Duration::try_from_secs_f64(timeout_s)
+            .ok()
+            .and_then(|timeout| Instant::now().checked_add(timeout));
+        let interval = Duration::from_secs_f64(poll_interval_s);
+        loop {
+            if let Some(value) = condition()? {
+                return Ok(value);
+            }
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                return Err(PyTimeoutError::new_err(format!(
+                    "timed out after {}s waiting for {}",
+                    timeout_s, what
+                )));
+            }
+            py.detach(|| std::thread::sleep(interval));
+            py.check_signals()?;
+        }
+    }
+}
+
+/// Decorations of `size` the Poisson placement typically fits on one triangle of face `face_index` (0..3)
+/// of a pyramid of `base_radius` and `height`, to compare with a `decorations_count`.
+/// `edge_margin_factor` is the `decoration_edge_margin_factor` of the config.
+#[pyfunction]
+#[pyo3(name = "max_decorations_for_face", signature = (
+    base_radius,
+    height,
+    face_index,
+    size,
+    edge_margin_factor = crate::constants::pyramid_constants::DECORATION_EDGE_MARGIN,
+))]
+fn py_max_decorations_for_face(
+    base_radius: f32,
+    height: f32,
+    face_index: usize,
+    size: f32,
+    edge_margin_factor: f32,
+) -> PyResult<u32> {
+    if face_index >= 3 {
+        return Err(PyErr::new::<PyValueError, _>(format!("face_index must be in 0..3, got {}", face_index)));
+    }
+    Ok(decoration_layout::max_decorations_for_face(base_radius, height, face_index, size, edge_margin_factor))
+}
+
+/// Snapshot fields the dict of `read_game_structure` exposes under another name.
+const RENAMED_FIELDS: [(&str, &str); 12] = [
+    ("decorations_count", "decoration_count"),
+    ("decorations_size", "decoration_size"),
+    ("attempts", "nr_attempts"),
+    ("practice_mode", "is_practice"),
+    ("current_alignment", "cosine_alignment"),
+    ("win_time", "win_elapsed_secs"),
+    ("practice_attempts", "practice_nr_attempts"),
+    ("practice_win_time", "practice_win_elapsed_secs"),
+    ("frame_drift", "frame_drift_ms"),
+    ("frame_time_mean", "frame_time_mean_ms"),
+    ("frame_time_p95", "frame_time_p95_ms"),
+    ("frame_time_p99", "frame_time_p99_ms"),
+];
 
+/// Serialized fields of a snapshot, keyed by field name.
+fn snapshot_fields(snapshot: &GameStructureSnapshot) -> Map<String, Value> {
+    match serde_json::to_value(snapshot) {
+        Ok(Value::Object(fields)) => fields,
+        _ => unreachable!("a snapshot serializes to a map"),
+    }
+}
+
+/// Splits a flat array into consecutive arrays of `size` items.
+fn chunked(value: Option<Value>, size: usize) -> Value {
+    let Some(Value::Array(items)) = value else {
+        return Value::Null;
+    };
+    Value::Array(items.chunks(size).map(|chunk| Value::Array(chunk.to_vec())).collect())
+}
+
+/// Serializable value of a Python keyword argument: bool, int, float or a (nested) sequence of them.
+fn py_to_json(value: &Bound<'_, PyAny>) -> PyResult<Value> {
+    if let Ok(flag) = value.cast::<PyBool>() {
+        return Ok(Value::Bool(flag.is_true()));
+    }
+    if let Ok(integer) = value.extract::<u64>() {
+        return Ok(Value::from(integer));
+    }
+    if let Ok(number) = value.extract::<f64>() {
+        return Ok(Value::from(number));
+    }
+    if value.is_instance_of::<PyString>() {
+        return Err(PyTypeError::new_err(format!("expected a number, got {:?}", value.to_string())));
+    }
+    value.try_iter()?.map(|item| py_to_json(&item?)).collect()
+}
+
+/// Python object of a serialized value; integers stay ints, f32 fields become floats.
+fn json_to_py(py: Python<'_>, value: Value) -> PyResult<Bound<'_, PyAny>> {
+    match value {
+        Value::Null => Ok(py.None().into_bound(py)),
+        Value::Bool(flag) => flag.into_bound_py_any(py),
+        Value::Number(number) => match number.as_u64() {
+            Some(integer) => integer.into_bound_py_any(py),
+            None => number.as_f64().into_bound_py_any(py),
+        },
+        Value::String(text) => text.into_bound_py_any(py),
+        Value::Array(items) => {
+            let items = items.into_iter().map(|item| json_to_py(py, item)).collect::<PyResult<Vec<_>>>()?;
+            Ok(PyList::new(py, items)?.into_any())
+        }
+        Value::Object(fields) => {
+            let dict = PyDict::new(py);
+            for (key, field) in fields {
+                dict.set_item(key, json_to_py(py, field)?)?;
+            }
+            Ok(dict.into_any())
+        }
+    }
+}
+
+/// File system errors keep their OSError subclass (e.g. FileNotFoundError), layout mismatches are ValueError.
+fn shm_error_to_py(err: SharedMemError) -> PyErr {
+    let message = err.to_string();
+    match err {
+        SharedMemError::File { source, .. } => PyErr::from(std::io::Error::new(source.kind(), message)),
+        SharedMemError::Mmap(_) => PyErr::new::<pyo3::exceptions::PyOSError, _>(message),
+        SharedMemError::Size { .. } | SharedMemError::Version { .. } => PyValueError::new_err(message),
+    }
 }
 
 #[pymodule]
 #[pyo3(name = "monkey_shared")]
 fn monkey_shared(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<SharedMemoryWrapper>()?;
+    m.add_function(wrap_pyfunction!(py_max_decorations_for_face, m)?)?;
 
     // Export constants from constants.rs so Python can import them directly.
     use crate::constants::game_constants;
     m.add("REFRESH_RATE_HZ", game_constants::REFRESH_RATE_HZ)?;
     m.add("DECORATION_SEEDS", game_constants::DECORATION_SEEDS.to_vec())?;
     m.add("COSINE_ALIGNMENT_TO_WIN", game_constants::COSINE_ALIGNMENT_TO_WIN)?;
+    m.add("TARGET_SCREEN_OFFSCREEN", game_constants::TARGET_SCREEN_OFFSCREEN)?;
+    m.add("ENGAGEMENT_THRESHOLD", game_constants::ENGAGEMENT_THRESHOLD)?;
+    m.add("ATTEMPTS_BASE", game_constants::ATTEMPTS_BASE)?;
+    m.add("ATTEMPTS_INCREMENT_POLICY", game_constants::ATTEMPTS_INCREMENT_POLICY)?;
+    m.add("PRACTICE_MODE", game_constants::PRACTICE_MODE)?;
+    m.add("CONSECUTIVE_REQUIRED", game_constants::CONSECUTIVE_REQUIRED)?;
+    m.add("SHOW_FIXATION", game_constants::SHOW_FIXATION)?;
+    m.add("FIXATION_SIZE", game_constants::FIXATION_SIZE)?;
+    m.add("FIXATION_COLOR", game_constants::FIXATION_COLOR.to_vec())?;
+    m.add("SCORE_BAR_ORIENTATION", game_constants::SCORE_BAR_ORIENTATION)?;
+    m.add("SCORE_BAR_SEGMENTS", game_constants::SCORE_BAR_SEGMENTS)?;
+    m.add("SCORE_BAR_MAX_SEGMENTS", game_constants::SCORE_BAR_MAX_SEGMENTS)?;
+    m.add("IDLE_TIMEOUT_FRAMES", game_constants::IDLE_TIMEOUT_FRAMES)?;
+    m.add("SHOW_BEST_DOOR_HINT", game_constants::SHOW_BEST_DOOR_HINT)?;
+    m.add("EMIT_VISIBLE_DECORATIONS", game_constants::EMIT_VISIBLE_DECORATIONS)?;
+    m.add("DETERMINISTIC_TIMING", game_constants::DETERMINISTIC_TIMING)?;
+    m.add("AUTO_RESET_AFTER_RESULT", game_constants::AUTO_RESET_AFTER_RESULT)?;
+    m.add("AUTO_RESET_DELAY_SECS", game_constants::AUTO_RESET_DELAY_SECS)?;
+    m.add("SHM_DIR_ENV_VAR", game_constants::SHM_DIR_ENV_VAR)?;
+
+    // Trial result codes of read_trial_result
+    use crate::TrialResult;
+    m.add("TRIAL_RESULT_WIN", TrialResult::Win as u32)?;
+    m.add("TRIAL_RESULT_ABORTED", TrialResult::Aborted as u32)?;
+
+    // Present mode codes of write_present_mode
+    use crate::PresentModeCommand;
+    m.add("PRESENT_MODE_UNCHANGED", PresentModeCommand::Unchanged as u32)?;
+    m.add("PRESENT_MODE_AUTO_VSYNC", PresentModeCommand::AutoVsync as u32)?;
+    m.add("PRESENT_MODE_AUTO_NO_VSYNC", PresentModeCommand::AutoNoVsync as u32)?;
+    m.add("PRESENT_MODE_IMMEDIATE", PresentModeCommand::Immediate as u32)?;
 
     // pyramid_constants
     use crate::constants::pyramid_constants;
     m.add("PYRAMID_BASE_RADIUS", pyramid_constants::PYRAMID_BASE_RADIUS)?;
     m.add("PYRAMID_HEIGHT", pyramid_constants::PYRAMID_HEIGHT)?;
     m.add("PYRAMID_START_ANGLE_OFFSET_RAD", pyramid_constants::PYRAMID_START_ANGLE_OFFSET_RAD)?;
+    m.add("RANDOM_START_ORIENTATION", pyramid_constants::RANDOM_START_ORIENTATION)?;
     m.add("PYRAMID_TARGET_DOOR_INDEX", pyramid_constants::PYRAMID_TARGET_DOOR_INDEX)?;
+    m.add("BASE_NR_SIDES", pyramid_constants::BASE_NR_SIDES)?;
+    m.add("BASE_NR_SIDES_MAX", pyramid_constants::BASE_NR_SIDES_MAX)?;
+    m.add("COLOR_PALETTE", pyramid_constants::COLOR_PALETTE.map(|set| set.to_vec()).to_vec())?;
+    m.add("PALETTE_INDEX_NONE", pyramid_constants::PALETTE_INDEX_NONE)?;
+    m.add("COLOR_SPACE", pyramid_constants::COLOR_SPACE)?;
     m.add("PYRAMID_COLORS", pyramid_constants::PYRAMID_COLORS.iter().map(|f| f.to_vec()).collect::<Vec<Vec<f32>>>())?;
     m.add("PYRAMID_DECORATIONS_COUNT", pyramid_constants::PYRAMID_DECORATIONS_COUNT.to_vec())?;
     m.add("PYRAMID_DECORATIONS_SIZE", pyramid_constants::PYRAMID_DECORATIONS_SIZE.to_vec())?;
+    m.add("PYRAMID_DECORATIONS_DENSITY", pyramid_constants::PYRAMID_DECORATIONS_DENSITY.to_vec())?;
+    m.add("DECORATION_STAR_POINTS", pyramid_constants::DECORATION_STAR_POINTS)?;
+    m.add("DECORATION_STAR_INNER_RATIO", pyramid_constants::DECORATION_STAR_INNER_RATIO)?;
+    m.add("DECORATION_DEPTH_OFFSET", pyramid_constants::DECORATION_DEPTH_OFFSET)?;
+    m.add("DECORATION_SPACING", pyramid_constants::DECORATION_SPACING)?;
+    m.add("DECORATION_EDGE_MARGIN", pyramid_constants::DECORATION_EDGE_MARGIN)?;
+    m.add("DECORATION_JITTER_ENABLED", pyramid_constants::DECORATION_JITTER_ENABLED)?;
+    m.add("PLACEMENT_MODE", pyramid_constants::PLACEMENT_MODE)?;
+    m.add("DECORATION_EMISSIVE", pyramid_constants::DECORATION_EMISSIVE.to_vec())?;
+    m.add("BASE_TEXTURE", pyramid_constants::BASE_TEXTURE)?;
     m.add("DOOR_ANIM_FADE_OUT", pyramid_constants::DOOR_ANIM_FADE_OUT)?;
     m.add("DOOR_ANIM_STAY_OPEN", pyramid_constants::DOOR_ANIM_STAY_OPEN)?;
     m.add("DOOR_ANIM_FADE_IN", pyramid_constants::DOOR_ANIM_FADE_IN)?;
+    m.add("DOOR_OPEN_STYLE", pyramid_constants::DOOR_OPEN_STYLE)?;
 
     // lighting_constants
     use crate::constants::lighting_constants;
     m.add("SPOTLIGHT_LIGHT_INTENSITY", lighting_constants::SPOTLIGHT_LIGHT_INTENSITY)?;
     m.add("GLOBAL_AMBIENT_LIGHT_INTENSITY", lighting_constants::GLOBAL_AMBIENT_LIGHT_INTENSITY)?;
+    m.add("GLOBAL_AMBIENT_LIGHT_COLOR", lighting_constants::GLOBAL_AMBIENT_LIGHT_COLOR.to_vec())?;
     m.add("MAX_SPOTLIGHT_INTENSITY", lighting_constants::MAX_SPOTLIGHT_INTENSITY)?;
+    m.add("BRIGHTNESS_GAIN", lighting_constants::BRIGHTNESS_GAIN)?;
+    m.add("TARGET_HINT_INTENSITY", lighting_constants::TARGET_HINT_INTENSITY)?;
+    m.add("HINT_PULSE_HZ", lighting_constants::HINT_PULSE_HZ)?;
+    m.add("HINT_PULSE_DEPTH", lighting_constants::HINT_PULSE_DEPTH)?;
+
+    // render_constants
+    use crate::constants::render_constants;
+    m.add("BLOOM_ENABLED", render_constants::BLOOM_ENABLED)?;
+    m.add("TONEMAPPING", render_constants::TONEMAPPING)?;
+    m.add("FOG_ENABLED", render_constants::FOG_ENABLED)?;
+    m.add("FOG_COLOR", render_constants::FOG_COLOR.to_vec())?;
+    m.add("FOG_START", render_constants::FOG_START)?;
+    m.add("FOG_END", render_constants::FOG_END)?;
+    m.add("SHOW_BACKDROP", crate::constants::object_constants::SHOW_BACKDROP)?;
 
     // timing
     use crate::constants::timing;
@@ -223,6 +694,10 @@ fn monkey_shared(m: &Bound<'_, PyModule>) -> PyResult<()> {
     // camera_3d_constants
     use crate::constants::camera_3d_constants;
     m.add("CAMERA_3D_INITIAL_RADIUS", camera_3d_constants::CAMERA_3D_INITIAL_RADIUS)?;
+    m.add("CAMERA_MODE", camera_3d_constants::CAMERA_MODE)?;
+    m.add("ZOOM_MODE", camera_3d_constants::ZOOM_MODE)?;
+    m.add("ROTATE_SPEED_DPS", camera_3d_constants::ROTATE_SPEED_DPS)?;
+    m.add("ZOOM_SPEED_UPS", camera_3d_constants::ZOOM_SPEED_UPS)?;
 
     Ok(())
 }
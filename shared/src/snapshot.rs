@@ -0,0 +1,380 @@
+//! Plain (non-atomic) copy of `SharedGameStructure`, for serialization and comparisons.
+//!
+//! f32 fields are stored as values, the bit conversion to and from the atomics happens only here
+//! (`snapshot` to read, `apply_snapshot` to write).
+use crate::SharedGameStructure;
+use core::sync::atomic::{AtomicU32, Ordering};
+use serde::{Deserialize, Serialize};
+
+/// Snapshot of every field of a `SharedGameStructure` at a given instant.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GameStructureSnapshot {
+    // Fixed trials fields
+    pub decoration_seeds: [u64; 3],
+    pub base_radius: f32,
+    pub height: f32,
+    pub start_orient: f32,
+    pub random_start_orientation: bool,
+    pub target_door: u32,
+    pub base_nr_sides: u32,
+    pub colors: [f32; 12],
+    pub palette_index: u32,
+    pub color_space: u32,
+    pub decorations_count: [u32; 3],
+    pub decorations_size: [f32; 3],
+    pub decoration_density: [f32; 3],
+    pub star_points: u32,
+    pub star_inner_ratio: f32,
+    pub decoration_depth_offset: f32,
+    pub decoration_jitter_enabled: bool,
+    pub placement_mode: u32,
+    pub decoration_edge_margin_factor: f32,
+    pub decoration_emissive: [f32; 3],
+    pub base_texture: u32,
+
+    // Logic
+    pub cosine_alignment_threshold: f32,
+    pub attempts_base: u32,
+    pub attempts_increment_policy: u32,
+    pub practice_mode: bool,
+    pub consecutive_required: u32,
+
+    // Animation Durations
+    pub door_anim_fade_out: f32,
+    pub door_anim_stay_open: f32,
+    pub door_anim_fade_in: f32,
+    pub door_open_style: u32,
+
+    // Lighting
+    pub main_spotlight_intensity: f32,
+    pub ambient_brightness: f32,
+    pub ambient_color: [f32; 3],
+    pub brightness_gain: f32,
+    pub max_spotlight_intensity: f32,
+    pub target_hint_intensity: f32,
+    pub hint_pulse_hz: f32,
+    pub hint_pulse_depth: f32,
+
+    // Rendering
+    pub bloom_enabled: bool,
+    pub tonemapping: u32,
+    pub fog_enabled: bool,
+    pub fog_color: [f32; 4],
+    pub fog_start: f32,
+    pub fog_end: f32,
+    pub show_backdrop: bool,
+    pub camera_mode: u32,
+    pub zoom_mode: u32,
+    pub rotate_speed_dps: f32,
+    pub zoom_speed_ups: f32,
+    pub show_fixation: bool,
+    pub fixation_size: f32,
+    pub fixation_color: [f32; 4],
+    pub score_bar_orientation: u32,
+    pub score_bar_segments: u32,
+    pub idle_timeout_frames: u32,
+    pub show_best_door_hint: bool,
+    pub emit_visible_decorations: bool,
+    pub deterministic_timing: bool,
+    pub engagement_threshold: f32,
+    pub auto_reset_after_result: bool,
+    pub auto_reset_delay_secs: f32,
+    pub config_epoch: u32,
+
+    // Dynamic trials fields
+    pub frame_number: u64,
+    pub heartbeat: u64,
+    pub elapsed_secs: f32,
+    pub camera_radius: f32,
+    pub camera_x: f32,
+    pub camera_y: f32,
+    pub camera_z: f32,
+    pub camera_azimuth: f32,
+    pub attempts: u32,
+    pub current_alignment: f32,
+    pub current_angle: f32,
+    pub is_animating: bool,
+    pub win_time: f32,
+    pub practice_attempts: u32,
+    pub practice_win_time: f32,
+    pub correct_streak: u32,
+    pub round_index: u32,
+    pub config_applied_epoch: u32,
+    pub decoration_shapes: [u32; 6],
+    pub decoration_colors: [f32; 24],
+    pub decorations_placed: [u32; 6],
+    pub visible_decoration_count: u32,
+    pub target_screen_x: f32,
+    pub target_screen_y: f32,
+    pub target_engaged: bool,
+    pub scene_valid: bool,
+    pub start_yaw: f32,
+    pub frame_drift: f32,
+    pub dropped_frames: u64,
+    pub frame_time_mean: f32,
+    pub frame_time_p95: f32,
+    pub frame_time_p99: f32,
+    pub session_wins: u32,
+    pub session_aborted: u32,
+    pub session_checks: u32,
+    pub session_mean_alignment: f32,
+    pub session_win_streak: u32,
+    pub session_best_win_streak: u32,
+}
+
+fn load_f32(a: &AtomicU32) -> f32 {
+    f32::from_bits(a.load(Ordering::Relaxed))
+}
+
+impl SharedGameStructure {
+    /// Loads all atomics into a plain snapshot.
+    pub fn snapshot(&self) -> GameStructureSnapshot {
+        GameStructureSnapshot {
+            decoration_seeds: self.decoration_seeds.each_ref().map(|s| s.load(Ordering::Relaxed)),
+            base_radius: load_f32(&self.base_radius),
+            height: load_f32(&self.height),
+            start_orient: load_f32(&self.start_orient),
+            random_start_orientation: self.random_start_orientation.load(Ordering::Relaxed),
+            target_door: self.target_door.load(Ordering::Relaxed),
+            base_nr_sides: self.base_nr_sides.load(Ordering::Relaxed),
+            colors: self.colors.each_ref().map(load_f32),
+            palette_index: self.palette_index.load(Ordering::Relaxed),
+            color_space: self.color_space.load(Ordering::Relaxed),
+            decorations_count: self.decorations_count.each_ref().map(|c| c.load(Ordering::Relaxed)),
+            decorations_size: self.decorations_size.each_ref().map(load_f32),
+            decoration_density: self.decoration_density.each_ref().map(load_f32),
+            star_points: self.star_points.load(Ordering::Relaxed),
+            star_inner_ratio: load_f32(&self.star_inner_ratio),
+            decoration_depth_offset: load_f32(&self.decoration_depth_offset),
+            decoration_jitter_enabled: self.decoration_jitter_enabled.load(Ordering::Relaxed),
+            placement_mode: self.placement_mode.load(Ordering::Relaxed),
+            decoration_edge_margin_factor: load_f32(&self.decoration_edge_margin_factor),
+            decoration_emissive: self.decoration_emissive.each_ref().map(load_f32),
+            base_texture: self.base_texture.load(Ordering::Relaxed),
+
+            cosine_alignment_threshold: load_f32(&self.cosine_alignment_threshold),
+            attempts_base: self.attempts_base.load(Ordering::Relaxed),
+            attempts_increment_policy: self.attempts_increment_policy.load(Ordering::Relaxed),
+            practice_mode: self.practice_mode.load(Ordering::Relaxed),
+            consecutive_required: self.consecutive_required.load(Ordering::Relaxed),
+
+            door_anim_fade_out: load_f32(&self.door_anim_fade_out),
+            door_anim_stay_open: load_f32(&self.door_anim_stay_open),
+            door_anim_fade_in: load_f32(&self.door_anim_fade_in),
+            door_open_style: self.door_open_style.load(Ordering::Relaxed),
+
+            main_spotlight_intensity: load_f32(&self.main_spotlight_intensity),
+            ambient_brightness: load_f32(&self.ambient_brightness),
+            ambient_color: self.ambient_color.each_ref().map(load_f32),
+            brightness_gain: load_f32(&self.brightness_gain),
+            max_spotlight_intensity: load_f32(&self.max_spotlight_intensity),
+            target_hint_intensity: load_f32(&self.target_hint_intensity),
+            hint_pulse_hz: load_f32(&self.hint_pulse_hz),
+            hint_pulse_depth: load_f32(&self.hint_pulse_depth),
+
+            bloom_enabled: self.bloom_enabled.load(Ordering::Relaxed),
+            tonemapping: self.tonemapping.load(Ordering::Relaxed),
+            fog_enabled: self.fog_enabled.load(Ordering::Relaxed),
+            fog_color: self.fog_color.each_ref().map(load_f32),
+            fog_start: load_f32(&self.fog_start),
+            fog_end: load_f32(&self.fog_end),
+            show_backdrop: self.show_backdrop.load(Ordering::Relaxed),
+            camera_mode: self.camera_mode.load(Ordering::Relaxed),
+            zoom_mode: self.zoom_mode.load(Ordering::Relaxed),
+            rotate_speed_dps: load_f32(&self.rotate_speed_dps),
+            zoom_speed_ups: load_f32(&self.zoom_speed_ups),
+            show_fixation: self.show_fixation.load(Ordering::Relaxed),
+            fixation_size: load_f32(&self.fixation_size),
+            fixation_color: self.fixation_color.each_ref().map(load_f32),
+            score_bar_orientation: self.score_bar_orientation.load(Ordering::Relaxed),
+            score_bar_segments: self.score_bar_segments.load(Ordering::Relaxed),
+            idle_timeout_frames: self.idle_timeout_frames.load(Ordering::Relaxed),
+            show_best_door_hint: self.show_best_door_hint.load(Ordering::Relaxed),
+            emit_visible_decorations: self.emit_visible_decorations.load(Ordering::Relaxed),
+            deterministic_timing: self.deterministic_timing.load(Ordering::Relaxed),
+            engagement_threshold: load_f32(&self.engagement_threshold),
+            auto_reset_after_result: self.auto_reset_after_result.load(Ordering::Relaxed),
+            auto_reset_delay_secs: load_f32(&self.auto_reset_delay_secs),
+            config_epoch: self.config_epoch.load(Ordering::Relaxed),
+
+            frame_number: self.frame_number.load(Ordering::Relaxed),
+            heartbeat: self.heartbeat.load(Ordering::Relaxed),
+            elapsed_secs: load_f32(&self.elapsed_secs),
+            camera_radius: load_f32(&self.camera_radius),
+            camera_x: load_f32(&self.camera_x),
+            camera_y: load_f32(&self.camera_y),
+            camera_z: load_f32(&self.camera_z),
+            camera_azimuth: load_f32(&self.camera_azimuth),
+            attempts: self.attempts.load(Ordering::Relaxed),
+            current_alignment: load_f32(&self.current_alignment),
+            current_angle: load_f32(&self.current_angle),
+            is_animating: self.is_animating.load(Ordering::Relaxed),
+            win_time: load_f32(&self.win_time),
+            practice_attempts: self.practice_attempts.load(Ordering::Relaxed),
+            practice_win_time: load_f32(&self.practice_win_time),
+            correct_streak: self.correct_streak.load(Ordering::Relaxed),
+            round_index: self.round_index.load(Ordering::Relaxed),
+            config_applied_epoch: self.config_applied_epoch.load(Ordering::Relaxed),
+            decoration_shapes: self.decoration_shapes.each_ref().map(|s| s.load(Ordering::Relaxed)),
+            decoration_colors: self.decoration_colors.each_ref().map(load_f32),
+            decorations_placed: self.decorations_placed.each_ref().map(|p| p.load(Ordering::Relaxed)),
+            visible_decoration_count: self.visible_decoration_count.load(Ordering::Relaxed),
+            target_screen_x: load_f32(&self.target_screen_x),
+            target_screen_y: load_f32(&self.target_screen_y),
+            target_engaged: self.target_engaged.load(Ordering::Relaxed),
+            scene_valid: self.scene_valid.load(Ordering::Relaxed),
+            start_yaw: load_f32(&self.start_yaw),
+            frame_drift: load_f32(&self.frame_drift),
+            dropped_frames: self.dropped_frames.load(Ordering::Relaxed),
+            frame_time_mean: load_f32(&self.frame_time_mean),
+            frame_time_p95: load_f32(&self.frame_time_p95),
+            frame_time_p99: load_f32(&self.frame_time_p99),
+            session_wins: self.session_wins.load(Ordering::Relaxed),
+            session_aborted: self.session_aborted.load(Ordering::Relaxed),
+            session_checks: self.session_checks.load(Ordering::Relaxed),
+            session_mean_alignment: load_f32(&self.session_mean_alignment),
+            session_win_streak: self.session_win_streak.load(Ordering::Relaxed),
+            session_best_win_streak: self.session_best_win_streak.load(Ordering::Relaxed),
+        }
+    }
+}
+
+fn store_f32(a: &AtomicU32, value: f32) {
+    a.store(value.to_bits(), Ordering::Relaxed);
+}
+
+impl SharedGameStructure {
+    /// Stores every field of a snapshot, the inverse of `snapshot`.
+    /// Covers exactly the fields copied by `reset_all_fields`.
+    pub fn apply_snapshot(&self, snapshot: &GameStructureSnapshot) {
+        for (a, v) in self.decoration_seeds.iter().zip(snapshot.decoration_seeds) {
+            a.store(v, Ordering::Relaxed);
+        }
+        store_f32(&self.base_radius, snapshot.base_radius);
+        store_f32(&self.height, snapshot.height);
+        store_f32(&self.start_orient, snapshot.start_orient);
+        self.random_start_orientation.store(snapshot.random_start_orientation, Ordering::Relaxed);
+        self.target_door.store(snapshot.target_door, Ordering::Relaxed);
+        self.base_nr_sides.store(snapshot.base_nr_sides, Ordering::Relaxed);
+        for (a, v) in self.colors.iter().zip(snapshot.colors) {
+            store_f32(a, v);
+        }
+        self.palette_index.store(snapshot.palette_index, Ordering::Relaxed);
+        self.color_space.store(snapshot.color_space, Ordering::Relaxed);
+        for (a, v) in self.decorations_count.iter().zip(snapshot.decorations_count) {
+            a.store(v, Ordering::Relaxed);
+        }
+        for (a, v) in self.decorations_size.iter().zip(snapshot.decorations_size) {
+            store_f32(a, v);
+        }
+        for (a, v) in self.decoration_density.iter().zip(snapshot.decoration_density) {
+            store_f32(a, v);
+        }
+        self.star_points.store(snapshot.star_points, Ordering::Relaxed);
+        store_f32(&self.star_inner_ratio, snapshot.star_inner_ratio);
+        store_f32(&self.decoration_depth_offset, snapshot.decoration_depth_offset);
+        self.decoration_jitter_enabled.store(snapshot.decoration_jitter_enabled, Ordering::Relaxed);
+        self.placement_mode.store(snapshot.placement_mode, Ordering::Relaxed);
+        store_f32(&self.decoration_edge_margin_factor, snapshot.decoration_edge_margin_factor);
+        for (a, v) in self.decoration_emissive.iter().zip(snapshot.decoration_emissive) {
+            store_f32(a, v);
+        }
+        self.base_texture.store(snapshot.base_texture, Ordering::Relaxed);
+
+        store_f32(&self.cosine_alignment_threshold, snapshot.cosine_alignment_threshold);
+        self.attempts_base.store(snapshot.attempts_base, Ordering::Relaxed);
+        self.attempts_increment_policy.store(snapshot.attempts_increment_policy, Ordering::Relaxed);
+        self.practice_mode.store(snapshot.practice_mode, Ordering::Relaxed);
+        self.consecutive_required.store(snapshot.consecutive_required, Ordering::Relaxed);
+
+        store_f32(&self.door_anim_fade_out, snapshot.door_anim_fade_out);
+        store_f32(&self.door_anim_stay_open, snapshot.door_anim_stay_open);
+        store_f32(&self.door_anim_fade_in, snapshot.door_anim_fade_in);
+        self.door_open_style.store(snapshot.door_open_style, Ordering::Relaxed);
+
+        store_f32(&self.main_spotlight_intensity, snapshot.main_spotlight_intensity);
+        store_f32(&self.ambient_brightness, snapshot.ambient_brightness);
+        for (a, v) in self.ambient_color.iter().zip(snapshot.ambient_color) {
+            store_f32(a, v);
+        }
+        store_f32(&self.brightness_gain, snapshot.brightness_gain);
+        store_f32(&self.max_spotlight_intensity, snapshot.max_spotlight_intensity);
+        store_f32(&self.target_hint_intensity, snapshot.target_hint_intensity);
+        store_f32(&self.hint_pulse_hz, snapshot.hint_pulse_hz);
+        store_f32(&self.hint_pulse_depth, snapshot.hint_pulse_depth);
+
+        self.bloom_enabled.store(snapshot.bloom_enabled, Ordering::Relaxed);
+        self.tonemapping.store(snapshot.tonemapping, Ordering::Relaxed);
+        self.fog_enabled.store(snapshot.fog_enabled, Ordering::Relaxed);
+        for (a, v) in self.fog_color.iter().zip(snapshot.fog_color) {
+            store_f32(a, v);
+        }
+        store_f32(&self.fog_start, snapshot.fog_start);
+        store_f32(&self.fog_end, snapshot.fog_end);
+        self.show_backdrop.store(snapshot.show_backdrop, Ordering::Relaxed);
+        self.camera_mode.store(snapshot.camera_mode, Ordering::Relaxed);
+        self.zoom_mode.store(snapshot.zoom_mode, Ordering::Relaxed);
+        store_f32(&self.rotate_speed_dps, snapshot.rotate_speed_dps);
+        store_f32(&self.zoom_speed_ups, snapshot.zoom_speed_ups);
+        self.show_fixation.store(snapshot.show_fixation, Ordering::Relaxed);
+        store_f32(&self.fixation_size, snapshot.fixation_size);
+        for (a, v) in self.fixation_color.iter().zip(snapshot.fixation_color) {
+            store_f32(a, v);
+        }
+        self.score_bar_orientation.store(snapshot.score_bar_orientation, Ordering::Relaxed);
+        self.score_bar_segments.store(snapshot.score_bar_segments, Ordering::Relaxed);
+        self.idle_timeout_frames.store(snapshot.idle_timeout_frames, Ordering::Relaxed);
+        self.show_best_door_hint.store(snapshot.show_best_door_hint, Ordering::Relaxed);
+        self.emit_visible_decorations.store(snapshot.emit_visible_decorations, Ordering::Relaxed);
+        self.deterministic_timing.store(snapshot.deterministic_timing, Ordering::Relaxed);
+        store_f32(&self.engagement_threshold, snapshot.engagement_threshold);
+        self.auto_reset_after_result.store(snapshot.auto_reset_after_result, Ordering::Relaxed);
+        store_f32(&self.auto_reset_delay_secs, snapshot.auto_reset_delay_secs);
+        self.config_epoch.store(snapshot.config_epoch, Ordering::Relaxed);
+
+        self.frame_number.store(snapshot.frame_number, Ordering::Relaxed);
+        self.heartbeat.store(snapshot.heartbeat, Ordering::Relaxed);
+        store_f32(&self.elapsed_secs, snapshot.elapsed_secs);
+        store_f32(&self.camera_radius, snapshot.camera_radius);
+        store_f32(&self.camera_x, snapshot.camera_x);
+        store_f32(&self.camera_y, snapshot.camera_y);
+        store_f32(&self.camera_z, snapshot.camera_z);
+        store_f32(&self.camera_azimuth, snapshot.camera_azimuth);
+        self.attempts.store(snapshot.attempts, Ordering::Relaxed);
+        store_f32(&self.current_alignment, snapshot.current_alignment);
+        store_f32(&self.current_angle, snapshot.current_angle);
+        self.is_animating.store(snapshot.is_animating, Ordering::Relaxed);
+        store_f32(&self.win_time, snapshot.win_time);
+        self.practice_attempts.store(snapshot.practice_attempts, Ordering::Relaxed);
+        store_f32(&self.practice_win_time, snapshot.practice_win_time);
+        self.correct_streak.store(snapshot.correct_streak, Ordering::Relaxed);
+        self.round_index.store(snapshot.round_index, Ordering::Relaxed);
+        self.config_applied_epoch.store(snapshot.config_applied_epoch, Ordering::Relaxed);
+        for (a, v) in self.decoration_shapes.iter().zip(snapshot.decoration_shapes) {
+            a.store(v, Ordering::Relaxed);
+        }
+        for (a, v) in self.decoration_colors.iter().zip(snapshot.decoration_colors) {
+            store_f32(a, v);
+        }
+        for (a, v) in self.decorations_placed.iter().zip(snapshot.decorations_placed) {
+            a.store(v, Ordering::Relaxed);
+        }
+        self.visible_decoration_count.store(snapshot.visible_decoration_count, Ordering::Relaxed);
+        store_f32(&self.target_screen_x, snapshot.target_screen_x);
+        store_f32(&self.target_screen_y, snapshot.target_screen_y);
+        self.target_engaged.store(snapshot.target_engaged, Ordering::Relaxed);
+        self.scene_valid.store(snapshot.scene_valid, Ordering::Relaxed);
+        store_f32(&self.start_yaw, snapshot.start_yaw);
+        store_f32(&self.frame_drift, snapshot.frame_drift);
+        self.dropped_frames.store(snapshot.dropped_frames, Ordering::Relaxed);
+        store_f32(&self.frame_time_mean, snapshot.frame_time_mean);
+        store_f32(&self.frame_time_p95, snapshot.frame_time_p95);
+        store_f32(&self.frame_time_p99, snapshot.frame_time_p99);
+        self.session_wins.store(snapshot.session_wins, Ordering::Relaxed);
+        self.session_aborted.store(snapshot.session_aborted, Ordering::Relaxed);
+        self.session_checks.store(snapshot.session_checks, Ordering::Relaxed);
+        store_f32(&self.session_mean_alignment, snapshot.session_mean_alignment);
+        self.session_win_streak.store(snapshot.session_win_streak, Ordering::Relaxed);
+        self.session_best_win_streak.store(snapshot.session_best_win_streak, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,172 @@
+//! End-of-round summary, published by the game once per round.
+//!
+//! The Controller reads it when `result_ready` is set instead of reconstructing the outcome from the
+//! incremental fields of the game structure. A sequence counter, odd while the game writes, lets the
+//! reader retry a read overlapping a publish, so a summary is never mixed from two rounds.
+use core::sync::atomic::{fence, AtomicBool, AtomicU32, Ordering};
+use serde::Serialize;
+
+/// How a round ended.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TrialResult {
+    /// No round ended yet
+    None = 0,
+    Win = 1,
+    // 2 and 3 stay unused, the game has no limit on the attempts or the round duration to lose on
+    /// A new round was requested before the current one was won
+    Aborted = 4,
+}
+
+impl TrialResult {
+    /// Decode the value stored in shared memory, falling back to `None` for unknown values.
+    pub fn from_u32(value: u32) -> Self {
+        match value {
+            1 => TrialResult::Win,
+            4 => TrialResult::Aborted,
+            _ => TrialResult::None,
+        }
+    }
+}
+
+/// Plain copy of a published summary.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct TrialResultSummary {
+    /// `TrialResult` code
+    pub result_code: u32,
+    pub result_attempts: u32,
+    pub result_time_ms: u32,
+    pub result_alignment: f32,
+}
+
+/// Summary of the last round that ended, written by the game only.
+#[repr(C)]
+#[derive(Debug)]
+pub struct SharedTrialResult {
+    /// `TrialResult` code
+    pub result_code: AtomicU32,
+    /// Attempts of the round (the practice attempts for a practice round)
+    pub result_attempts: AtomicU32,
+    /// Time from the round start to its end, in milliseconds
+    pub result_time_ms: AtomicU32,
+    /// Target door alignment of the last check (f32 bits)
+    pub result_alignment: AtomicU32,
+    /// Set by the game when a summary is published, cleared by the Controller once read
+    pub result_ready: AtomicBool,
+    /// Odd while the game writes a summary
+    pub sequence: AtomicU32,
+}
+
+impl SharedTrialResult {
+    pub const fn new() -> Self {
+        Self {
+            result_code: AtomicU32::new(TrialResult::None as u32),
+            result_attempts: AtomicU32::new(0),
+            result_time_ms: AtomicU32::new(0),
+            result_alignment: AtomicU32::new(0),
+            result_ready: AtomicBool::new(false),
+            sequence: AtomicU32::new(0),
+        }
+    }
+
+    /// Game side: writes a summary and raises `result_ready`. A summary not read yet is replaced.
+    pub fn publish(&self, summary: &TrialResultSummary) {
+        let sequence = self.sequence.load(Ordering::Relaxed);
+        self.sequence.store(sequence.wrapping_add(1), Ordering::Relaxed);
+        fence(Ordering::Release);
+
+        self.result_code.store(summary.result_code, Ordering::Relaxed);
+        self.result_attempts.store(summary.result_attempts, Ordering::Relaxed);
+        self.result_time_ms.store(summary.result_time_ms, Ordering::Relaxed);
+        self.result_alignment.store(summary.result_alignment.to_bits(), Ordering::Relaxed);
+
+        self.sequence.store(sequence.wrapping_add(2), Ordering::Release);
+        self.result_ready.store(true, Ordering::Release);
+    }
+
+    /// Coherent copy of the last summary and its sequence number, retried while a publish is in progress.
+    pub fn read(&self) -> (TrialResultSummary, u32) {
+        loop {
+            let before = self.sequence.load(Ordering::Acquire);
+            if before % 2 == 1 {
+                std::hint::spin_loop();
+                continue;
+            }
+            let summary = TrialResultSummary {
+                result_code: self.result_code.load(Ordering::Relaxed),
+                result_attempts: self.result_attempts.load(Ordering::Relaxed),
+                result_time_ms: self.result_time_ms.load(Ordering::Relaxed),
+                result_alignment: f32::from_bits(self.result_alignment.load(Ordering::Relaxed)),
+            };
+            fence(Ordering::Acquire);
+            if self.sequence.load(Ordering::Relaxed) == before {
+                return (summary, before);
+            }
+        }
+    }
+
+    /// Controller side: the published summary if it was not read yet, clearing `result_ready`.
+    pub fn take(&self) -> Option<TrialResultSummary> {
+        if !self.result_ready.load(Ordering::Acquire) {
+            return None;
+        }
+        let (summary, sequence) = self.read();
+        self.result_ready.store(false, Ordering::Relaxed);
+        // A summary published meanwhile stays pending for the next call
+        if self.sequence.load(Ordering::Acquire) != sequence {
+            self.result_ready.store(true, Ordering::Relaxed);
+        }
+        Some(summary)
+    }
+}
+
+impl Default for SharedTrialResult {
+    fn default() -> Self { Self::new() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary_is_taken_once() {
+        let shared = SharedTrialResult::new();
+        assert_eq!(shared.take(), None);
+
+        let summary = TrialResultSummary {
+            result_code: TrialResult::Win as u32,
+            result_attempts: 3,
+            result_time_ms: 4200,
+            result_alignment: 0.99,
+        };
+        shared.publish(&summary);
+        assert_eq!(shared.take(), Some(summary));
+        assert_eq!(shared.take(), None);
+    }
+
+    #[test]
+    fn concurrent_reads_never_mix_two_summaries() {
+        let shared = std::sync::Arc::new(SharedTrialResult::new());
+        let iterations: u32 = if cfg!(miri) { 100 } else { 50_000 };
+
+        let writer = {
+            let shared = shared.clone();
+            std::thread::spawn(move || {
+                for i in 1..=iterations {
+                    shared.publish(&TrialResultSummary {
+                        result_code: i,
+                        result_attempts: i,
+                        result_time_ms: i,
+                        result_alignment: i as f32,
+                    });
+                }
+            })
+        };
+        while !writer.is_finished() {
+            let (summary, _) = shared.read();
+            let i = summary.result_code;
+            assert_eq!((summary.result_attempts, summary.result_time_ms, summary.result_alignment), (i, i, i as f32));
+        }
+        writer.join().unwrap();
+    }
+}
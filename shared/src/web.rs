@@ -1,18 +1,48 @@
 //! Web (WASM) shared memory implementation using SharedArrayBuffer.
 
-use crate::SharedMemory;
+use crate::{GameStructureSnapshot, SharedMemory};
+use core::sync::atomic::Ordering;
 use wasm_bindgen::prelude::*;
 use std::sync::OnceLock;
 
 /// Global static instance of shared memory for WASM
 static SHARED_MEMORY: OnceLock<SharedMemory> = OnceLock::new();
 
+/// Whether the page (or worker) is cross-origin isolated (COOP/COEP headers), which
+/// SharedArrayBuffer requires. The frontend should check it before attaching `WebSharedMemory`.
+#[wasm_bindgen]
+pub fn is_cross_origin_isolated() -> bool {
+    // Read from the global scope rather than `window`, so it also works inside workers
+    js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("crossOriginIsolated"))
+        .map(|v| v.is_truthy())
+        .unwrap_or(false)
+}
+
 /// Allocate the shared memory on Rust side and return pointer.
 /// JS will use this pointer to create a view.
 #[wasm_bindgen]
 pub fn create_shared_memory_wasm() -> *mut SharedMemory {
-    let mem_ref = SHARED_MEMORY.get_or_init(|| SharedMemory::new());
-    mem_ref as *const SharedMemory as *mut SharedMemory
+    if !is_cross_origin_isolated() {
+        web_sys::console::error_1(
+            &"Shared memory: the page is not cross-origin isolated, SharedArrayBuffer is unavailable. \
+              Serve it with `Cross-Origin-Opener-Policy: same-origin` and \
+              `Cross-Origin-Embedder-Policy: require-corp`."
+                .into(),
+        );
+    }
+    allocate_shared_memory() as *const SharedMemory as *mut SharedMemory
+}
+
+/// Allocate the shared memory (once) without the cross-origin isolation check, for the game's own
+/// standalone mode where no JS view is expected.
+pub fn allocate_shared_memory() -> &'static SharedMemory {
+    SHARED_MEMORY.get_or_init(|| SharedMemory::new())
+}
+
+/// Converts to a JS value, with u64 fields (decoration seeds, frame counters) as BigInt
+/// since they do not fit in a JS number.
+fn to_js<T: serde::Serialize>(value: &T) -> Result<JsValue, serde_wasm_bindgen::Error> {
+    value.serialize(&serde_wasm_bindgen::Serializer::new().serialize_large_number_types_as_bigints(true))
 }
 
 /// Helper wrapper for WASM side
@@ -28,6 +58,270 @@ impl WebSharedMemory {
         Self { ptr: ptr as *mut SharedMemory }
     }
 
+    fn shm(&self) -> &SharedMemory {
+        unsafe { &*self.ptr }
+    }
+
+    /// Hold (or release) the rotate left command.
+    pub fn set_rotate_left(&self, active: bool) {
+        self.shm().commands.rotate_left.store(active, Ordering::Relaxed);
+    }
+
+    /// Hold (or release) the rotate right command.
+    pub fn set_rotate_right(&self, active: bool) {
+        self.shm().commands.rotate_right.store(active, Ordering::Relaxed);
+    }
+
+    /// Hold (or release) the zoom in command.
+    pub fn set_zoom_in(&self, active: bool) {
+        self.shm().commands.zoom_in.store(active, Ordering::Relaxed);
+    }
+
+    /// Hold (or release) the zoom out command.
+    pub fn set_zoom_out(&self, active: bool) {
+        self.shm().commands.zoom_out.store(active, Ordering::Relaxed);
+    }
+
+    /// Request an alignment check.
+    pub fn trigger_check(&self) {
+        self.shm().commands.check_alignment.store(true, Ordering::Relaxed);
+    }
+
+    /// Request a new round from the control game structure.
+    pub fn trigger_reset(&self) {
+        self.shm().commands.reset.store(true, Ordering::Release);
+    }
+
+    /// Toggle the blank screen.
+    pub fn trigger_blank_screen(&self) {
+        self.shm().commands.blank_screen.store(true, Ordering::Relaxed);
+    }
+
+    /// Set the blank screen to an absolute state, unlike `trigger_blank_screen`.
+    pub fn set_blank_screen(&self, active: bool) {
+        let cmd = &self.shm().commands;
+        cmd.blank_on.store(active, Ordering::Relaxed);
+        cmd.blank_off.store(!active, Ordering::Relaxed);
+    }
+
+    pub fn trigger_stop_rendering(&self) {
+        self.shm().commands.stop_rendering.store(true, Ordering::Relaxed);
+    }
+
+    pub fn trigger_resume_rendering(&self) {
+        self.shm().commands.resume_rendering.store(true, Ordering::Relaxed);
+    }
+
+    pub fn trigger_animation_door(&self) {
+        self.shm().commands.animation_door.store(true, Ordering::Relaxed);
+    }
+
+    /// Regenerate the decorations from the control decoration seeds.
+    pub fn trigger_reroll(&self) {
+        self.shm().commands.reroll.store(true, Ordering::Relaxed);
+    }
+
+    /// Free and show the cursor.
+    pub fn trigger_release_cursor(&self) {
+        self.shm().commands.release_cursor.store(true, Ordering::Relaxed);
+    }
+
+    /// Rebuild the wooden base from the control `base_nr_sides` and `target_door`.
+    pub fn trigger_rebuild_base(&self) {
+        self.shm().commands.rebuild_base.store(true, Ordering::Relaxed);
+    }
+
+    /// Move the camera back to the round's start pose.
+    pub fn trigger_recenter(&self) {
+        self.shm().commands.recenter.store(true, Ordering::Relaxed);
+    }
+
+    /// Debug only: win the current round at once, like the Python `write_force_win`.
+    pub fn trigger_force_win(&self) {
+        self.shm().commands.force_win.store(true, Ordering::Relaxed);
+    }
+
+    /// Move the camera to an exact orbit pose (azimuth in radians, radius clamped by the game).
+    /// Held until the next `write_commands`, like the Python `write_viewpoint`.
+    pub fn set_viewpoint(&self, azimuth: f32, radius: f32) {
+        let cmd = &self.shm().commands;
+        cmd.set_azimuth.store(azimuth.to_bits(), Ordering::Relaxed);
+        cmd.set_radius.store(radius.to_bits(), Ordering::Relaxed);
+        cmd.set_viewpoint.store(true, Ordering::Release);
+    }
+
+    /// Set the window present mode (`PresentModeCommand` code), like the Python `write_present_mode`.
+    /// The browser paces the canvas itself and may ignore it.
+    pub fn set_present_mode(&self, mode: u32) {
+        self.shm().commands.present_mode.store(mode, Ordering::Relaxed);
+    }
+
+    /// Write all commands at once, same semantics as the Python `write_commands`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn write_commands(
+        &self,
+        rotate_left: bool,
+        rotate_right: bool,
+        zoom_in: bool,
+        zoom_out: bool,
+        check: bool,
+        reset: bool,
+        blank_screen: bool,
+        stop_rendering: bool,
+        resume_rendering: bool,
+        animation_door: bool,
+        blank_on: bool,
+        blank_off: bool,
+        reroll: bool,
+        release_cursor: bool,
+        set_viewpoint: bool,
+        rebuild_base: bool,
+        force_win: bool,
+        recenter: bool,
+    ) {
+        let cmd = &self.shm().commands;
+        cmd.rotate_left.store(rotate_left, Ordering::Relaxed);
+        cmd.rotate_right.store(rotate_right, Ordering::Relaxed);
+        cmd.zoom_in.store(zoom_in, Ordering::Relaxed);
+        cmd.zoom_out.store(zoom_out, Ordering::Relaxed);
+        cmd.check_alignment.store(check, Ordering::Relaxed);
+        cmd.reset.store(reset, Ordering::Release);
+        cmd.blank_screen.store(blank_screen, Ordering::Relaxed);
+        cmd.stop_rendering.store(stop_rendering, Ordering::Relaxed);
+        cmd.resume_rendering.store(resume_rendering, Ordering::Relaxed);
+        cmd.animation_door.store(animation_door, Ordering::Relaxed);
+        cmd.blank_on.store(blank_on, Ordering::Relaxed);
+        cmd.blank_off.store(blank_off, Ordering::Relaxed);
+        cmd.reroll.store(reroll, Ordering::Relaxed);
+        cmd.release_cursor.store(release_cursor, Ordering::Relaxed);
+        cmd.set_viewpoint.store(set_viewpoint, Ordering::Release);
+        cmd.rebuild_base.store(rebuild_base, Ordering::Relaxed);
+        cmd.force_win.store(force_win, Ordering::Relaxed);
+        cmd.recenter.store(recenter, Ordering::Relaxed);
+    }
+
+    /// Write game structure config fields in the controller region.
+    /// `config` is a plain object keyed by field name (as in the offsets table), e.g.
+    /// `{ target_door: 2, colors: [...] }`; missing keys keep their current control value.
+    /// Applied on the next `trigger_reset`.
+    pub fn write_game_structure(&self, config: &js_sys::Object) -> Result<(), JsError> {
+        let gs = &self.shm().game_structure_control;
+
+        let merged = to_js(&gs.snapshot())?;
+        js_sys::Object::assign(merged.unchecked_ref(), config);
+        let snapshot: GameStructureSnapshot = serde_wasm_bindgen::from_value(merged)?;
+
+        let door_count = snapshot.base_nr_sides as usize;
+        if !crate::constants::base_layout::is_valid_door_count(door_count) {
+            return Err(JsError::new(&format!(
+                "base_nr_sides must be a multiple of 3 in 3..={}, got {}",
+                crate::constants::pyramid_constants::BASE_NR_SIDES_MAX, door_count
+            )));
+        }
+        if snapshot.target_door as usize >= door_count {
+            return Err(JsError::new(&format!(
+                "target_door must be in 0..{}, got {}",
+                door_count, snapshot.target_door
+            )));
+        }
+
+        gs.apply_snapshot(&snapshot);
+        gs.bump_config_epoch();
+        Ok(())
+    }
+
+    /// Write the decoration count of one face (0..3) in the controller region, same semantics as the
+    /// Python `set_decoration_count` (clears the face's density, applied on the next reset or re-roll).
+    pub fn set_decoration_count(&self, face: u32, count: u32) -> Result<(), JsError> {
+        if face >= 3 {
+            return Err(JsError::new(&format!("face must be in 0..3, got {}", face)));
+        }
+        let gs = &self.shm().game_structure_control;
+        gs.decorations_count[face as usize].store(count, Ordering::Relaxed);
+        gs.decoration_density[face as usize].store(0.0f32.to_bits(), Ordering::Relaxed);
+        gs.bump_config_epoch();
+        Ok(())
+    }
+
+    /// Write the three door animation phase durations (seconds) in the controller region, same semantics as the
+    /// Python `set_door_animation` (applied when the next animation starts, never to a running animation).
+    pub fn set_door_animation(&self, fade_out: f32, stay_open: f32, fade_in: f32) -> Result<(), JsError> {
+        for (name, duration) in [("fade_out", fade_out), ("stay_open", stay_open), ("fade_in", fade_in)] {
+            if !(duration.is_finite() && duration >= 0.0) {
+                return Err(JsError::new(&format!("{} must be finite and non-negative, got {}", name, duration)));
+            }
+        }
+        let gs = &self.shm().game_structure_control;
+        gs.door_anim_fade_out.store(fade_out.to_bits(), Ordering::Relaxed);
+        gs.door_anim_stay_open.store(stay_open.to_bits(), Ordering::Relaxed);
+        gs.door_anim_fade_in.store(fade_in.to_bits(), Ordering::Relaxed);
+        gs.bump_config_epoch();
+        Ok(())
+    }
+
+    /// Write the RGBA color of one face (0..3) in the controller region, same semantics as the
+    /// Python `set_face_color` (expands and clears an active palette, applied on the next reset).
+    pub fn set_face_color(&self, face: u32, rgba: Vec<f32>) -> Result<(), JsError> {
+        let rgba: [f32; 4] = rgba
+            .try_into()
+            .map_err(|rgba: Vec<f32>| JsError::new(&format!("rgba must have 4 channels, got {}", rgba.len())))?;
+        let gs = &self.shm().game_structure_control;
+        if !gs.set_face_color(face as usize, rgba) {
+            return Err(JsError::new(&format!("face must be in 0..3, got {}", face)));
+        }
+        gs.bump_config_epoch();
+        Ok(())
+    }
+
+    /// Whether the current round runs the latest config written by the Controller, false while a reset is pending.
+    pub fn config_is_current(&self) -> bool {
+        self.shm().config_is_current()
+    }
+
+    /// Read the game structure written by the game, decoded (f32 fields from their bits).
+    /// Returns a JS Object { "frame_number": 12n, "camera_x": 1.5, "colors": [...], ... }
+    /// keyed like the offsets table; u64 fields are BigInt.
+    pub fn read_game_structure(&self) -> Result<JsValue, JsError> {
+        Ok(to_js(&self.shm().game_structure_game.snapshot())?)
+    }
+
+    /// Summary of the last round that ended, or null when no new one was published since the previous call.
+    /// Reading it clears `result_ready`, like the Python `read_trial_result`.
+    pub fn read_trial_result(&self) -> Result<JsValue, JsError> {
+        Ok(to_js(&self.shm().trial_result.take())?)
+    }
+
+    /// Layout version written by this build (a u32 at offset 0 of `get_ptr`).
+    /// The frontend compares it with the version it was built for and must not attach on mismatch.
+    pub fn layout_version(&self) -> u32 {
+        self.shm().layout_version.load(Ordering::Relaxed)
+    }
+
+    /// Size in bytes of the `SharedMemory` region at `get_ptr` for this build.
+    pub fn region_size(&self) -> usize {
+        std::mem::size_of::<SharedMemory>()
+    }
+
+    /// Checks the region against the layout version and size (bytes) the frontend was built for.
+    /// Errors on any mismatch, the frontend must then not create views on the region.
+    pub fn check_layout(&self, expected_version: u32, expected_size: usize) -> Result<(), JsError> {
+        let version = self.layout_version();
+        if version != expected_version {
+            return Err(JsError::new(&format!(
+                "shared memory layout version {}, the frontend expects {}",
+                version, expected_version
+            )));
+        }
+        let size = self.region_size();
+        if size != expected_size {
+            return Err(JsError::new(&format!(
+                "shared memory region has {} bytes, the frontend expects {}",
+                size, expected_size
+            )));
+        }
+        Ok(())
+    }
+
     /// Get base pointer to SharedMemory
     pub fn get_ptr(&self) -> usize {
         self.ptr as usize
@@ -38,19 +332,26 @@ impl WebSharedMemory {
         unsafe { &(*self.ptr).commands as *const _ as usize }
     }
 
-    /// Get pointer to SharedGameStructure (for reading/writing game state from JS)
+    /// Get pointer to the game's SharedGameStructure (for reading the game state from JS)
     pub fn get_game_structure_ptr(&self) -> usize {
-        unsafe { &(*self.ptr).game_structure as *const _ as usize }
+        unsafe { &(*self.ptr).game_structure_game as *const _ as usize }
     }
 
-    /// Get offsets of fields within SharedGameStructure
+    /// Get pointer to the Controller's SharedGameStructure (for writing the config from JS)
+    pub fn get_game_structure_control_ptr(&self) -> usize {
+        unsafe { &(*self.ptr).game_structure_control as *const _ as usize }
+    }
+
+    /// Get offsets of fields within SharedGameStructure, for zero-copy reads (see `read_game_structure`).
+    /// Both structures share the layout, so the offsets apply to either pointer.
     /// Returns a JS Object { "frame_number": offset, ... }
     pub fn get_game_structure_offsets(&self) -> JsValue {
-        let base = unsafe { &(*self.ptr).game_structure as *const _ as usize };
-        let gs = unsafe { &(*self.ptr).game_structure };
+        let base = unsafe { &(*self.ptr).game_structure_game as *const _ as usize };
+        let gs = unsafe { &(*self.ptr).game_structure_game };
 
-        let make_offset = |field_ptr: *const _| -> u32 {
-             (field_ptr as usize - base) as u32
+        // Fields have different types, taken as `Any` so a single closure measures them all
+        let make_offset = |field: &dyn std::any::Any| -> u32 {
+            (field as *const dyn std::any::Any as *const () as usize - base) as u32
         };
 
         let offsets = js_sys::Object::new();
@@ -58,41 +359,113 @@ impl WebSharedMemory {
             js_sys::Reflect::set(&offsets, &JsValue::from_str(key), &JsValue::from_f64(val as f64)).unwrap();
         };
 
-        set("decoration_seeds", make_offset(&gs.decoration_seeds as *const _));
-        set("base_radius", make_offset(&gs.base_radius as *const _));
-        set("height", make_offset(&gs.height as *const _));
-        set("start_orient", make_offset(&gs.start_orient as *const _));
-        set("target_door", make_offset(&gs.target_door as *const _));
-        set("colors", make_offset(&gs.colors as *const _));
+        set("decoration_seeds", make_offset(&gs.decoration_seeds));
+        set("base_radius", make_offset(&gs.base_radius));
+        set("height", make_offset(&gs.height));
+        set("start_orient", make_offset(&gs.start_orient));
+        set("random_start_orientation", make_offset(&gs.random_start_orientation));
+        set("target_door", make_offset(&gs.target_door));
+        set("base_nr_sides", make_offset(&gs.base_nr_sides));
+        set("colors", make_offset(&gs.colors));
+        set("palette_index", make_offset(&gs.palette_index));
+        set("color_space", make_offset(&gs.color_space));
+        set("attempts_base", make_offset(&gs.attempts_base));
+        set("attempts_increment_policy", make_offset(&gs.attempts_increment_policy));
+        set("practice_mode", make_offset(&gs.practice_mode));
+        set("consecutive_required", make_offset(&gs.consecutive_required));
 
         // Dynamic Constants
-        set("decoration_count_min", make_offset(&gs.decoration_count_min as *const _));
-        set("decoration_count_max", make_offset(&gs.decoration_count_max as *const _));
-        set("decoration_size_min", make_offset(&gs.decoration_size_min as *const _));
-        set("decoration_size_max", make_offset(&gs.decoration_size_max as *const _));
-
-        set("cosine_alignment_threshold", make_offset(&gs.cosine_alignment_threshold as *const _));
-
-        set("door_anim_fade_out", make_offset(&gs.door_anim_fade_out as *const _));
-        set("door_anim_stay_open", make_offset(&gs.door_anim_stay_open as *const _));
-        set("door_anim_fade_in", make_offset(&gs.door_anim_fade_in as *const _));
-
-        set("main_spotlight_intensity", make_offset(&gs.main_spotlight_intensity as *const _));
-        set("max_spotlight_intensity", make_offset(&gs.max_spotlight_intensity as *const _));
-        set("ambient_brightness", make_offset(&gs.ambient_brightness as *const _));
-
-        set("frame_number", make_offset(&gs.frame_number as *const _));
-        set("elapsed_secs", make_offset(&gs.elapsed_secs as *const _));
-        set("camera_radius", make_offset(&gs.camera_radius as *const _));
-        set("camera_x", make_offset(&gs.camera_x as *const _));
-        set("camera_y", make_offset(&gs.camera_y as *const _));
-        set("camera_z", make_offset(&gs.camera_z as *const _));
-        set("pyramid_yaw", make_offset(&gs.pyramid_yaw as *const _));
-        set("attempts", make_offset(&gs.attempts as *const _));
-        set("alignment", make_offset(&gs.alignment as *const _));
-        set("current_angle", make_offset(&gs.current_angle as *const _));
-        set("is_animating", make_offset(&gs.is_animating as *const _));
-        set("win_time", make_offset(&gs.win_time as *const _));
+        set("decorations_count", make_offset(&gs.decorations_count));
+        set("decorations_size", make_offset(&gs.decorations_size));
+        set("decoration_density", make_offset(&gs.decoration_density));
+        set("star_points", make_offset(&gs.star_points));
+        set("star_inner_ratio", make_offset(&gs.star_inner_ratio));
+        set("decoration_depth_offset", make_offset(&gs.decoration_depth_offset));
+        set("decoration_jitter_enabled", make_offset(&gs.decoration_jitter_enabled));
+        set("placement_mode", make_offset(&gs.placement_mode));
+        set("decoration_edge_margin_factor", make_offset(&gs.decoration_edge_margin_factor));
+        set("decoration_emissive", make_offset(&gs.decoration_emissive));
+        set("base_texture", make_offset(&gs.base_texture));
+
+        set("cosine_alignment_threshold", make_offset(&gs.cosine_alignment_threshold));
+
+        set("door_anim_fade_out", make_offset(&gs.door_anim_fade_out));
+        set("door_anim_stay_open", make_offset(&gs.door_anim_stay_open));
+        set("door_anim_fade_in", make_offset(&gs.door_anim_fade_in));
+        set("door_open_style", make_offset(&gs.door_open_style));
+
+        set("main_spotlight_intensity", make_offset(&gs.main_spotlight_intensity));
+        set("max_spotlight_intensity", make_offset(&gs.max_spotlight_intensity));
+        set("target_hint_intensity", make_offset(&gs.target_hint_intensity));
+        set("hint_pulse_hz", make_offset(&gs.hint_pulse_hz));
+        set("hint_pulse_depth", make_offset(&gs.hint_pulse_depth));
+        set("ambient_brightness", make_offset(&gs.ambient_brightness));
+        set("ambient_color", make_offset(&gs.ambient_color));
+        set("brightness_gain", make_offset(&gs.brightness_gain));
+
+        set("bloom_enabled", make_offset(&gs.bloom_enabled));
+        set("tonemapping", make_offset(&gs.tonemapping));
+        set("fog_enabled", make_offset(&gs.fog_enabled));
+        set("fog_color", make_offset(&gs.fog_color));
+        set("fog_start", make_offset(&gs.fog_start));
+        set("fog_end", make_offset(&gs.fog_end));
+        set("show_backdrop", make_offset(&gs.show_backdrop));
+        set("camera_mode", make_offset(&gs.camera_mode));
+        set("zoom_mode", make_offset(&gs.zoom_mode));
+        set("rotate_speed_dps", make_offset(&gs.rotate_speed_dps));
+        set("zoom_speed_ups", make_offset(&gs.zoom_speed_ups));
+        set("show_fixation", make_offset(&gs.show_fixation));
+        set("fixation_size", make_offset(&gs.fixation_size));
+        set("fixation_color", make_offset(&gs.fixation_color));
+        set("score_bar_orientation", make_offset(&gs.score_bar_orientation));
+        set("score_bar_segments", make_offset(&gs.score_bar_segments));
+        set("idle_timeout_frames", make_offset(&gs.idle_timeout_frames));
+        set("show_best_door_hint", make_offset(&gs.show_best_door_hint));
+        set("emit_visible_decorations", make_offset(&gs.emit_visible_decorations));
+        set("deterministic_timing", make_offset(&gs.deterministic_timing));
+        set("engagement_threshold", make_offset(&gs.engagement_threshold));
+        set("auto_reset_after_result", make_offset(&gs.auto_reset_after_result));
+        set("auto_reset_delay_secs", make_offset(&gs.auto_reset_delay_secs));
+        set("config_epoch", make_offset(&gs.config_epoch));
+
+        set("frame_number", make_offset(&gs.frame_number));
+        set("heartbeat", make_offset(&gs.heartbeat));
+        set("elapsed_secs", make_offset(&gs.elapsed_secs));
+        set("camera_radius", make_offset(&gs.camera_radius));
+        set("camera_x", make_offset(&gs.camera_x));
+        set("camera_y", make_offset(&gs.camera_y));
+        set("camera_z", make_offset(&gs.camera_z));
+        set("camera_azimuth", make_offset(&gs.camera_azimuth));
+        set("attempts", make_offset(&gs.attempts));
+        set("alignment", make_offset(&gs.current_alignment));
+        set("current_angle", make_offset(&gs.current_angle));
+        set("is_animating", make_offset(&gs.is_animating));
+        set("win_time", make_offset(&gs.win_time));
+        set("practice_attempts", make_offset(&gs.practice_attempts));
+        set("correct_streak", make_offset(&gs.correct_streak));
+        set("practice_win_time", make_offset(&gs.practice_win_time));
+        set("round_index", make_offset(&gs.round_index));
+        set("config_applied_epoch", make_offset(&gs.config_applied_epoch));
+        set("decoration_shapes", make_offset(&gs.decoration_shapes));
+        set("decoration_colors", make_offset(&gs.decoration_colors));
+        set("decorations_placed", make_offset(&gs.decorations_placed));
+        set("visible_decoration_count", make_offset(&gs.visible_decoration_count));
+        set("target_screen_x", make_offset(&gs.target_screen_x));
+        set("target_screen_y", make_offset(&gs.target_screen_y));
+        set("target_engaged", make_offset(&gs.target_engaged));
+        set("scene_valid", make_offset(&gs.scene_valid));
+        set("start_yaw", make_offset(&gs.start_yaw));
+        set("frame_drift", make_offset(&gs.frame_drift));
+        set("dropped_frames", make_offset(&gs.dropped_frames));
+        set("frame_time_mean", make_offset(&gs.frame_time_mean));
+        set("frame_time_p95", make_offset(&gs.frame_time_p95));
+        set("frame_time_p99", make_offset(&gs.frame_time_p99));
+        set("session_wins", make_offset(&gs.session_wins));
+        set("session_aborted", make_offset(&gs.session_aborted));
+        set("session_checks", make_offset(&gs.session_checks));
+        set("session_mean_alignment", make_offset(&gs.session_mean_alignment));
+        set("session_win_streak", make_offset(&gs.session_win_streak));
+        set("session_best_win_streak", make_offset(&gs.session_best_win_streak));
         
         offsets.into()
     }